@@ -3,16 +3,32 @@ use std::path::Path;
 use std::io::{BufReader, Read};
 use std::fs::File;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use search::traits::node::Node;
+use search::traits::problem::{Problem, ProblemAlgorithm};
 use search::traits::algorithm::Algorithm;
 use search::bfs::BFS;
 use search::dfs::DFS;
 use search::dijkstra::Dijkstra;
 use search::a_star::AStar;
 use search::dfbnb::DFBnB;
-
-#[derive(Copy, Clone)]
+use search::arena::a_star::AStar as ArenaAStar;
+use search::arena::dijkstra::Dijkstra as ArenaDijkstra;
+use search::traits::mapf::MapfGraph;
+use search::mapf::agents::{parse_agents, Agent};
+use search::mapf::cbs::CBS;
+use search::space_time::{SpaceTime, TimedAction};
+use search::utils::reservation::ReservationTable;
+use search::utils::path::Path as SolutionPath;
+use search::utils::graph::Graph;
+use search::utils::rng::Rng;
+use search::heuristics::landmarks::{Landmarks, LandmarkQuery, Selection};
+use search::contraction::ContractionHierarchy;
+use search::utils::search_tree::{RecordedNode, TreeRecorder};
+use std::cell::RefCell;
+
+#[derive(Copy, Clone, Debug)]
 enum Action { North, South, West, East }
 #[derive(PartialEq)]
 enum Cell { Wall, Empty, Pacman, Goal, Path, Visited }
@@ -152,6 +168,7 @@ impl Labyrinth {
 
 }
 
+#[derive(Clone)]
 struct PacmanNode<'a> {
     state: Rc<Coord>,
     action: Option<Action>,
@@ -196,8 +213,330 @@ impl<'a> Node for PacmanNode<'a> {
     }
 }
 
+impl Problem for Labyrinth {
+    type State = Coord;
+    type Action = Action;
+
+    fn get_root(&self) -> Coord {
+        self.get_first_occurrence(&Cell::Pacman).expect("Cannot find pacman")
+    }
+
+    fn get_successors(&self, state: &Coord) -> Vec<(Action, Coord, isize)> {
+        static ACTIONS: [Action; 4] = [Action::North, Action::South, Action::West, Action::East];
+        ACTIONS.iter()
+            .filter_map(|action| self.take_action(state, action).map(|coord| (*action, coord, 1)))
+            .collect()
+    }
+
+    fn get_heuristic(&self, state: &Coord) -> isize {
+        self.calc_manhattan_distance_to_goal(state)
+    }
+
+    fn is_solution(&self, state: &Coord) -> bool {
+        self.is_coord_goal(state)
+    }
+}
+
+impl MapfGraph for Labyrinth {
+    type Vertex = Coord;
+
+    fn get_neighbours(&self, coord: &Coord) -> Vec<Coord> {
+        static ACTIONS: [Action; 4] = [Action::North, Action::South, Action::West, Action::East];
+        ACTIONS.iter().filter_map(|action| self.take_action(coord, action)).collect()
+    }
+}
+
+fn route_agents(path: &Path, agents_path: &Path, weight: f64) {
+    let labyrinth = Labyrinth::new(path).expect("Cannot load labyrinth");
+    let text = std::fs::read_to_string(agents_path).expect("Cannot load agents");
+    let agents: Vec<Agent<Coord>> = parse_agents(&text).expect("Invalid agents file").into_iter()
+        .map(|agent| Agent { start: Coord { x: agent.start.0, y: agent.start.1 }, goal: Coord { x: agent.goal.0, y: agent.goal.1 } })
+        .collect();
+
+    let mut algo: CBS<Labyrinth> = CBS::new();
+    algo.set_suboptimality(weight).set_time_limit(Duration::from_secs(60));
+    let time_start = Instant::now();
+    let paths = algo.find_paths(&labyrinth, &agents);
+    let duration = time_start.elapsed();
+    let paths = match paths {
+        Some(paths) => paths,
+        None if algo.is_limit_reached() => return println!("Time limit reached"),
+        None => return println!("No solution found"),
+    };
+
+    let mut result = Labyrinth::new(path).expect("Cannot load labyrinth");
+    for (i, (agent, path)) in agents.iter().zip(&paths).enumerate() {
+        for coord in path {
+            result.set_coord(coord, Cell::Path).expect("Can't set result coord");
+        }
+        println!("Agent {}: ({}, {}) -> ({}, {}), cost {}", i, agent.start.x, agent.start.y, agent.goal.x, agent.goal.y, path.len() - 1);
+    }
+    println!("Final:\n{}", result.format());
+    println!("Sum of costs: {} (lower bound {})", algo.get_cost(), algo.get_lower_bound());
+    println!("Constraint tree nodes: {}, low-level expansions: {}", algo.get_expanded(), algo.get_low_level_expanded());
+    println!("Duration: {:.3}ms", duration.as_micros() as f64 / 1000.0);
+}
+
+// Ghosts walk along their row and turn around at walls
+fn get_ghost_paths(labyrinth: &Labyrinth, ghosts: &[Coord], steps: usize) -> Vec<Vec<Coord>> {
+    ghosts.iter().map(|ghost| {
+        let mut path = vec![ghost.clone()];
+        let mut action = Action::East;
+        for _ in 0..steps {
+            let last = path[path.len() - 1].clone();
+            let next = labyrinth.take_action(&last, &action).or_else(|| {
+                action = match action { Action::East => Action::West, _ => Action::East };
+                labyrinth.take_action(&last, &action)
+            });
+            path.push(next.unwrap_or(last));
+        }
+        path
+    }).collect()
+}
+
+// Ghosts (G) are moving obstacles and the food (.) is the goal
+fn avoid_ghosts(path: &Path, horizon: usize) {
+    let text = std::fs::read_to_string(path).expect("Cannot load labyrinth");
+    let find = |target: char| text.lines().enumerate().flat_map(move |(y, row)| {
+        row.chars().enumerate().filter(move |(_, char)| *char == target).map(move |(x, _)| Coord { x, y })
+    });
+    let ghosts: Vec<Coord> = find('G').collect();
+    let mut labyrinth = Labyrinth::new(path).expect("Cannot load labyrinth");
+    for ghost in &ghosts {
+        labyrinth.set_coord(ghost, Cell::Empty).expect("Can't set ghost coord");
+    }
+    labyrinth.goal = find('.').next();
+
+    let mut reservations = ReservationTable::new();
+    let ghost_paths = get_ghost_paths(&labyrinth, &ghosts, horizon);
+    for ghost_path in &ghost_paths {
+        reservations.reserve_path(ghost_path, 0);
+    }
+
+    // Shortest path ignoring the ghosts
+    let mut algo: ArenaAStar<Labyrinth> = ArenaAStar::new();
+    let solution = algo.find_solution(&labyrinth).expect("No solution found");
+    let shortest = SolutionPath::from_arena(algo.get_arena(), algo.get_states(), solution);
+    let collisions = (1..shortest.states.len())
+        .filter(|t| reservations.is_move_reserved(&shortest.states[t - 1], &shortest.states[*t], *t))
+        .count();
+    println!("Ignoring ghosts: length {}, {} collisions", shortest.cost, collisions);
+
+    let mut problem = SpaceTime::new(&labyrinth, horizon);
+    problem.set_reservations(reservations);
+    let mut algo: ArenaAStar<SpaceTime<Labyrinth>> = ArenaAStar::new();
+    let solution = match algo.find_solution(&problem) {
+        Some(solution) => solution,
+        None => return println!("No solution found within {} steps", horizon),
+    };
+    let timed = SolutionPath::from_arena(algo.get_arena(), algo.get_states(), solution);
+    let mut result = Labyrinth::new(path).expect("Cannot load labyrinth");
+    for (coord, _) in &timed.states {
+        result.set_coord(coord, Cell::Path).expect("Can't set result coord");
+    }
+    for (action, (coord, time)) in timed.actions.iter().zip(timed.states.iter().skip(1)) {
+        if let TimedAction::Wait = action { println!("Wait at ({}, {}) until t={}", coord.x, coord.y, time); }
+    }
+    println!("Final:\n{}", result.format());
+    println!("Avoiding ghosts: length {}, arrival at t={}", timed.cost, timed.states[timed.states.len() - 1].1);
+}
+
+// Average states visited by A* between random cells, without landmarks it is Dijkstra
+fn compare_landmarks(path: &Path, count: usize, queries: usize) {
+    let labyrinth = Labyrinth::new(path).expect("Cannot load labyrinth");
+    let graph = Graph::from_problem(&labyrinth);
+    let mut rng = Rng::new(0);
+    let pairs: Vec<(usize, usize)> = (0..queries)
+        .map(|_| (rng.next_below(graph.len()), rng.next_below(graph.len())))
+        .collect();
+
+    let methods = [("none", 0, Selection::Random), ("random", count, Selection::Random),
+        ("farthest", count, Selection::Farthest), ("avoid", count, Selection::Avoid)];
+    for (name, count, selection) in methods {
+        let time_start = Instant::now();
+        let landmarks = Landmarks::new(&graph, count, selection, 0);
+        let precomputation = time_start.elapsed();
+        let mut visited = 0;
+        for (start, goal) in &pairs {
+            let query = LandmarkQuery::new(&graph, &landmarks, *start, *goal);
+            let mut algo: ArenaAStar<LandmarkQuery<Coord>> = ArenaAStar::new();
+            algo.find_solution(&query).expect("No solution found");
+            visited += algo.get_states().len();
+        }
+        println!(
+            "{:<8} visited {:>8.1} per query, precomputed in {:.3}ms",
+            name, visited as f64 / queries as f64, precomputation.as_micros() as f64 / 1000.0,
+        );
+    }
+}
+
+// Time per query between random cells, Dijkstra against the contraction hierarchy
+fn compare_contraction(path: &Path, queries: usize) {
+    let labyrinth = Labyrinth::new(path).expect("Cannot load labyrinth");
+    let graph = Graph::from_problem(&labyrinth);
+    let mut rng = Rng::new(0);
+    let pairs: Vec<(usize, usize)> = (0..queries)
+        .map(|_| (rng.next_below(graph.len()), rng.next_below(graph.len())))
+        .collect();
+
+    let time_start = Instant::now();
+    let distances: Vec<Option<isize>> = pairs.iter().map(|(start, goal)| graph.get_distances(*start, false)[*goal]).collect();
+    let dijkstra = time_start.elapsed() / queries as u32;
+    println!("Dijkstra:    {:.3}ms per query", dijkstra.as_micros() as f64 / 1000.0);
+
+    let time_start = Instant::now();
+    let mut hierarchy = ContractionHierarchy::new(&graph);
+    let precomputation = time_start.elapsed();
+    let time_start = Instant::now();
+    let mut settled = 0;
+    for ((start, goal), distance) in pairs.iter().zip(&distances) {
+        let path = hierarchy.find_path(*start, *goal);
+        assert_eq!(path.map(|path| path.cost), *distance, "Contraction hierarchy disagrees with Dijkstra");
+        settled += hierarchy.get_settled();
+    }
+    let contraction = time_start.elapsed() / queries as u32;
+    println!(
+        "Contraction: {:.3}ms per query, settled {:.1} per query, {} shortcuts precomputed in {:.3}ms",
+        contraction.as_micros() as f64 / 1000.0, settled as f64 / queries as f64,
+        hierarchy.get_shortcuts_added(), precomputation.as_micros() as f64 / 1000.0,
+    );
+}
+
+// Contraction hierarchies of random directed weighted graphs against the arena Dijkstra, unpacked paths
+// must only use edges of the graph and add up to the cost
+fn validate_contraction(graphs: usize, queries: usize) {
+    let mut rng = Rng::new(0);
+    let mut reachable = 0;
+    for _ in 0..graphs {
+        let n = 2 + rng.next_below(40);
+        let mut graph: Graph<usize> = Graph::new();
+        for v in 0..n { graph.add_vertex(v); }
+        for _ in 0..rng.next_below(4 * n) {
+            graph.add_edge(rng.next_below(n), rng.next_below(n), rng.next_below(10) as isize);
+        }
+        // Without landmarks the query is a plain search between two vertices
+        let landmarks = Landmarks::new(&graph, 0, Selection::Random, 0);
+        let mut hierarchy = ContractionHierarchy::new(&graph);
+        for _ in 0..queries {
+            let (start, goal) = (rng.next_below(n), rng.next_below(n));
+            let query = LandmarkQuery::new(&graph, &landmarks, start, goal);
+            let mut algo: ArenaDijkstra<LandmarkQuery<usize>> = ArenaDijkstra::new();
+            let distance = algo.find_solution(&query).map(|node| algo.get_arena().get(node).cost);
+            assert_eq!(hierarchy.get_distance(start, goal), distance, "Contraction hierarchy disagrees with Dijkstra");
+            let Some(path) = hierarchy.find_path(start, goal) else {
+                assert_eq!(distance, None, "Contraction hierarchy found no path");
+                continue;
+            };
+            reachable += 1;
+            assert_eq!(Some(path.cost), distance, "Unpacked path disagrees with Dijkstra");
+            assert_eq!((path.states[0], path.states[path.states.len() - 1]), (start, goal), "Unpacked path has wrong ends");
+            let cost: isize = path.states.windows(2)
+                .map(|edge| graph.get_edges(edge[0]).iter()
+                    .filter(|(to, _)| *to == edge[1])
+                    .map(|(_, cost)| *cost)
+                    .min()
+                    .expect("Unpacked path uses a missing edge"))
+                .sum();
+            assert_eq!(cost, path.cost, "Unpacked path edges do not add up to its cost");
+        }
+    }
+    println!("Contraction: {} queries on {} random graphs agree with Dijkstra, {} reachable", graphs * queries, graphs, reachable);
+}
+
+// Search tree of A* written as Graphviz DOT, render it with `dot -Tsvg`
+fn export_tree(path: &Path, output: &Path, max_nodes: usize) {
+    let labyrinth = Labyrinth::new(path).expect("Cannot load labyrinth");
+    let mut recorder: TreeRecorder<Coord, Action> = TreeRecorder::new(|coord: &Coord| format!("({}, {})", coord.x, coord.y));
+    recorder.set_action_format(|action: &Action| format!("{:?}", action)).set_max_nodes(max_nodes);
+    let recorder = Rc::new(RefCell::new(recorder));
+    let root = PacmanNode {
+        state: Rc::new(labyrinth.get_root()),
+        action: None,
+        cost: 0,
+        parent: None,
+        labyrinth: &labyrinth,
+    };
+    let mut algo: AStar<RecordedNode<PacmanNode>> = AStar::new();
+    let solution = algo.find_solution(Box::new(RecordedNode::new(root, recorder.clone()))).expect("No solution found");
+    solution.mark_solution();
+
+    let recorder = recorder.borrow();
+    recorder.write_dot(output).expect("Cannot write search tree");
+    println!(
+        "Cost {}: {} nodes and {} expansions written to {}, {} left out",
+        solution.get_cost(), recorder.len(), recorder.get_expansions(), output.display(), recorder.get_skipped(),
+    );
+}
+
+fn bench(labyrinth: &Labyrinth, runs: u32) {
+    let time_start = Instant::now();
+    let mut cost = 0;
+    for _ in 0..runs {
+        let node = PacmanNode {
+            state: Rc::new(labyrinth.get_root()),
+            action: None,
+            cost: 0,
+            parent: None,
+            labyrinth,
+        };
+        let mut algo: AStar<PacmanNode> = AStar::new();
+        cost = algo.find_solution(Box::new(node)).expect("No solution found").get_cost();
+    }
+    let boxed = time_start.elapsed() / runs;
+    println!("Box/Rc A*: {:.3}ms (cost {})", boxed.as_micros() as f64 / 1000.0, cost);
+
+    let time_start = Instant::now();
+    for _ in 0..runs {
+        let mut algo: ArenaAStar<Labyrinth> = ArenaAStar::new();
+        let solution = algo.find_solution(labyrinth).expect("No solution found");
+        cost = algo.get_arena().get(solution).cost;
+    }
+    let arena = time_start.elapsed() / runs;
+    println!("Arena A*:  {:.3}ms (cost {})", arena.as_micros() as f64 / 1000.0, cost);
+}
+
 fn main() {
     let path = Path::new("lays/bigMaze.lay");
+
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        bench(&Labyrinth::new(path).expect("Cannot load labyrinth"), 100);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("ghosts") {
+        let args: Vec<String> = std::env::args().collect();
+        avoid_ghosts(Path::new(&args[2]), 500);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("landmarks") {
+        let args: Vec<String> = std::env::args().collect();
+        let count = args.get(3).map(|count| count.parse().expect("Invalid landmark count")).unwrap_or(8);
+        compare_landmarks(Path::new(&args[2]), count, 1000);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("contraction") {
+        let args: Vec<String> = std::env::args().collect();
+        compare_contraction(Path::new(&args[2]), 1000);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("contraction-random") {
+        let args: Vec<String> = std::env::args().collect();
+        let graphs = args.get(2).map(|graphs| graphs.parse().expect("Invalid number of graphs")).unwrap_or(500);
+        validate_contraction(graphs, 100);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("tree") {
+        let args: Vec<String> = std::env::args().collect();
+        let max_nodes = args.get(4).map(|nodes| nodes.parse().expect("Invalid number of nodes")).unwrap_or(500);
+        export_tree(Path::new(&args[2]), Path::new(&args[3]), max_nodes);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("mapf") {
+        let args: Vec<String> = std::env::args().collect();
+        let weight = args.get(4).map(|weight| weight.parse().expect("Invalid weight")).unwrap_or(1.0);
+        route_agents(Path::new(&args[2]), Path::new(&args[3]), weight);
+        return;
+    }
+
     let labyrinth = Labyrinth::new(path).expect("Cannot load labyrinth");
     let pacman = labyrinth.get_first_occurrence(&Cell::Pacman).expect("Cannot find pacman");
 
@@ -245,4 +584,4 @@ fn main() {
     println!("Final:\n{}", result.format());
     println!("Length: {}", length);
     println!("Visited: {}", algo.get_visited().len());
-}
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum Action {
     Flight(String)
 }
\ No newline at end of file
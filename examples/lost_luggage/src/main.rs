@@ -2,6 +2,7 @@ pub mod structs;
 pub mod enums;
 
 use std::collections::HashMap;
+use std::env;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
@@ -21,25 +22,26 @@ use search::dfs::DFS;
 use search::dijkstra::Dijkstra;
 use search::a_star::AStar;
 use search::dfbnb::DFBnB;
+use search::k_shortest::yen::Yen;
+use search::multi_objective::NamoaStar;
+use search::resource_constrained::ResourceConstrained;
+use search::traits::multi_objective::MultiObjectiveNode;
+use search::contraction::ContractionHierarchy;
+use search::utils::graph::Graph;
 
-fn process_luggage(
-    luggage: &Luggage,
-    locations_map: &HashMap<String, &Location>,
-    flights_map: &HashMap<String, &Flight>
-) {
-    let root = Node {
-        location: Rc::new(luggage.origin.clone()),
-        action: None,
-        parent: None,
-        time: 0,
-        luggage: &luggage,
-        locations: &locations_map,
-    };
-    let mut algo: Dijkstra<Node> = Dijkstra::new();
-    let solution = algo.find_solution(Box::new(root)).expect("No solution found");
+// Shortest time in the air, Pareto front of arrival time and flights, earliest arrival within a time
+// in the air, or backup itineraries per luggage and the maximum fraction of stopovers they may share
+struct Options {
+    flying: bool,
+    pareto: bool,
+    max_airborne: Option<isize>,
+    k: usize,
+    max_overlap: Option<f64>,
+}
 
+fn print_plan(solution: &Node, flights_map: &HashMap<String, &Flight>) {
     let mut nodes: Vec<Rc<Node>> = Vec::new();
-    let mut last = Rc::new(*solution);
+    let mut last = Rc::new(solution.clone());
 
     loop {
         nodes.push(last.clone());
@@ -49,9 +51,7 @@ fn process_luggage(
         }
     }
 
-    println!("Solution for luggage {}:", luggage.id);
     println!("Time: {}", nodes[0].time);
-    println!("Visited: {}", algo.get_visited().len());
     println!("Plan:");
     for node in nodes.iter().rev() {
         if let Some(Action::Flight(flight)) = node.action.as_ref() {
@@ -65,10 +65,114 @@ fn process_luggage(
             );
         }
     }
+}
+
+// Ignores departures, a lower bound on arrival times answered for every luggage by one contraction hierarchy
+fn print_flying_times(locations: &[Location]) {
+    let mut graph: Graph<String> = Graph::new();
+    for location in locations {
+        graph.add_vertex(location.id.clone());
+    }
+    for flight in locations.iter().flat_map(|location| location.flights.iter()) {
+        let origin = graph.add_vertex(flight.origin.clone());
+        let destination = graph.add_vertex(flight.destination.clone());
+        graph.add_edge(origin, destination, flight.duration as isize);
+    }
+    let mut hierarchy = ContractionHierarchy::new(&graph);
+
+    for luggage in locations.iter().flat_map(|location| location.luggage.iter()) {
+        let path = match (graph.get_id(&luggage.origin), graph.get_id(&luggage.destination)) {
+            (Some(origin), Some(destination)) => hierarchy.find_path(origin, destination),
+            _ => None,
+        };
+        match path {
+            Some(path) => {
+                let stops: Vec<&str> = path.states.iter().map(|id| graph.get_vertex(*id).as_str()).collect();
+                println!("Luggage {}: {} in the air via {}", luggage.id, path.cost, stops.join("->"));
+            },
+            None => println!("Luggage {}: unreachable", luggage.id),
+        }
+    }
+}
+
+fn process_luggage(
+    luggage: &Luggage,
+    locations_map: &HashMap<String, &Location>,
+    flights_map: &HashMap<String, &Flight>,
+    options: &Options,
+) {
+    let root = Node {
+        location: Rc::new(luggage.origin.clone()),
+        action: None,
+        parent: None,
+        time: 0,
+        luggage: &luggage,
+        locations: &locations_map,
+    };
+    if options.pareto {
+        let mut algo: NamoaStar<Node> = NamoaStar::new();
+        let solutions = algo.find_front(root);
+        if solutions.is_empty() { panic!("No solution found") }
+
+        println!("Pareto front for luggage {}:", luggage.id);
+        println!("Expanded: {}", algo.get_expanded());
+        for solution in solutions {
+            println!("Flights: {}", solution.get_costs()[1]);
+            print_plan(&solution, flights_map);
+        }
+        println!();
+        return;
+    }
+    if let Some(max_airborne) = options.max_airborne {
+        let mut algo: ResourceConstrained<Node> = ResourceConstrained::new(vec![max_airborne]);
+        let solution = algo.find_solution(Box::new(root));
+
+        println!("Solution for luggage {} flying at most {}:", luggage.id, max_airborne);
+        println!("Pruned: {} over the limit, {} dominated", algo.get_infeasible(), algo.get_dominated());
+        match solution {
+            Some(solution) => print_plan(&solution, flights_map),
+            None => println!("No solution found"),
+        }
+        println!();
+        return;
+    }
+    if options.k > 1 {
+        let mut algo: Yen<Node> = Yen::new(options.k);
+        if let Some(max_overlap) = options.max_overlap { algo.set_max_overlap(max_overlap); }
+        let solutions = algo.find_solutions(root);
+        if solutions.is_empty() { panic!("No solution found") }
+
+        println!("Itineraries for luggage {}:", luggage.id);
+        println!("Searches: {}", algo.get_searches());
+        for (i, solution) in solutions.into_iter().enumerate() {
+            println!("Option {}:", i + 1);
+            print_plan(&solution, flights_map);
+        }
+        println!();
+        return;
+    }
+
+    let mut algo: Dijkstra<Node> = Dijkstra::new();
+    let solution = algo.find_solution(Box::new(root)).expect("No solution found");
+
+    println!("Solution for luggage {}:", luggage.id);
+    println!("Visited: {}", algo.get_visited().len());
+    print_plan(&solution, flights_map);
     println!();
 }
 
 fn main() {
+    // `flying`, `pareto`, `airborne <time>`, or an optional number of itineraries per luggage and their maximum overlap
+    let mut args = env::args().skip(1).peekable();
+    let options = Options {
+        flying: args.next_if(|arg| arg == "flying").is_some(),
+        pareto: args.next_if(|arg| arg == "pareto").is_some(),
+        max_airborne: args.next_if(|arg| arg == "airborne")
+            .map(|_| args.next().and_then(|time| time.parse().ok()).expect("Invalid time in the air")),
+        k: args.next().map(|k| k.parse().expect("Invalid number of itineraries")).unwrap_or(1),
+        max_overlap: args.next().map(|overlap| overlap.parse().expect("Invalid overlap")),
+    };
+
     let path = Path::new("configs/config.json");
     let file = File::open(path).expect("Can't open config file");
     let reader = BufReader::new(file);
@@ -85,13 +189,14 @@ fn main() {
         )
         .flatten()
         .collect();
+    if options.flying { return print_flying_times(&locations) }
 
     // Don't use with depth-first algos yet, huge RAM usage because of recursion.
     // TODO: Improve recursion on depth-first algos, maybe tail recursion if possible.
     rayon::scope(|s| {
         for location in locations.iter() {
             for luggage in location.luggage.iter() {
-                s.spawn(|_| process_luggage(luggage, &locations_map, &flights_map));
+                s.spawn(|_| process_luggage(luggage, &locations_map, &flights_map, &options));
             }
         }
     })
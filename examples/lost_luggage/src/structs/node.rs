@@ -4,6 +4,7 @@ use super::location::Location;
 use crate::enums::action::Action;
 use crate::structs::luggage::Luggage;
 
+#[derive(Clone)]
 pub struct Node<'a> {
     pub location: Rc<String>,
     pub action: Option<Action>,
@@ -62,4 +63,35 @@ impl<'a> search::traits::node::Node for Node<'a> {
     fn is_solution(&self) -> bool {
         *self.location == self.luggage.destination
     }
+}
+
+// Arrival time and flights taken
+impl<'a> search::traits::multi_objective::MultiObjectiveNode for Node<'a> {
+    fn get_costs(&self) -> Vec<isize> {
+        let mut flights = 0;
+        let mut current = self.parent.as_deref();
+        while let Some(node) = current {
+            flights += 1;
+            current = node.parent.as_deref();
+        }
+        vec![self.time as isize, flights]
+    }
+}
+
+// Time spent flying
+impl<'a> search::traits::resources::ResourceNode for Node<'a> {
+    fn get_resources(&self) -> Vec<isize> {
+        let mut airborne = 0;
+        let mut current = self;
+        while let (Some(Action::Flight(flight)), Some(parent)) = (current.action.as_ref(), current.parent.as_deref()) {
+            airborne += self.locations.get(parent.location.as_ref()).unwrap()
+                .flights
+                .iter()
+                .find(|candidate| &candidate.id == flight)
+                .unwrap()
+                .duration;
+            current = parent;
+        }
+        vec![airborne as isize]
+    }
 }
\ No newline at end of file
@@ -3,29 +3,215 @@ pub mod structs;
 
 use std::path::Path;
 use std::rc::Rc;
-use std::time::{Instant};
+use std::time::{Duration, Instant};
 
-use search::traits::algorithm::Algorithm;
+use search::traits::algorithm::{Algorithm, AlgorithmExt};
 use search::bfs::BFS;
 use search::dfs::DFS;
 use search::dijkstra::Dijkstra;
 use search::a_star::AStar;
+use search::mcts::MCTS;
+use search::genetic::Genetic;
+use search::local::hill_climbing::{HillClimbing, HillClimbingMode};
+use search::local::simulated_annealing::SimulatedAnnealing;
+use search::local::tabu::TabuSearch;
 use search::traits::node::Node;
+use search::traits::problem::ProblemAlgorithm;
+use search::arena::a_star::AStar as ArenaAStar;
+use search::parallel::hda_star::HDAStar;
+use search::parallel::bfs::BFS as ParallelBFS;
+use search::portfolio::{Portfolio, PortfolioMode};
+use search::arena::bfs::BFS as ArenaBFS;
+use search::arena::dfbnb::DFBnB as ArenaDFBnB;
+use search::csp::solver::CspSolver;
+use search::heuristics::checker::{check_heuristic, Violation};
+use search::utils::validation::Violation as SearchViolation;
 
 use utils::parse;
 use utils::output;
 
 use structs::node::ShipNode;
 use structs::ship::Ship;
+use structs::state::State;
 
 fn get_help(exe: &str) -> String {
-    format!("Usage:\n{} <path> <map> <containers> <heuristic-name> [search-algorithm]\n", exe)
+    format!("Usage:\n{} <path> <map> <containers> <heuristic-name> [search-algorithm] [memory-limit-mb]\n", exe)
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration.as_secs() > 1 {
+        format!("{:.2}s", duration.as_millis() as f64 / 1000.0)
+    } else {
+        format!("{:.2}ms", duration.as_micros() as f64 / 1000.0)
+    }
+}
+
+fn bench(ship: &Ship) {
+    let state = ship.build_initial_state();
+    let root = ShipNode {
+        hash: ship.hash_state(&state),
+        state: Rc::new(state),
+        action: None,
+        cost: 0,
+        parent: None,
+        extra: ship.build_initial_extra(),
+        ship,
+    };
+    let time_start = Instant::now();
+    let mut algo: AStar<ShipNode> = AStar::new();
+    let solution = algo.find_solution(Box::new(root)).expect("No solution was found");
+    println!("Box/Rc A*: {} (cost {})", format_duration(time_start.elapsed()), solution.cost);
+
+    let time_start = Instant::now();
+    let mut algo: ArenaAStar<Ship> = ArenaAStar::new();
+    let solution = algo.find_solution(ship).expect("No solution was found");
+    let cost = algo.get_arena().get(solution).cost;
+    println!("Arena A*:  {} (cost {})", format_duration(time_start.elapsed()), cost);
+
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let time_start = Instant::now();
+    let mut algo: HDAStar<Ship> = HDAStar::new(threads);
+    let cost = algo.find_solution(ship).expect("No solution was found").cost;
+    println!("HDA* ({} threads): {} (cost {})", threads, format_duration(time_start.elapsed()), cost);
+    for (i, stats) in algo.get_stats().iter().enumerate() {
+        println!(
+            "\tThread {}: expanded {}, generated {}, sent {}, received {}",
+            i, stats.expanded, stats.generated, stats.sent, stats.received,
+        );
+    }
+}
+
+fn count_reachable(ship: &Ship) {
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let time_start = Instant::now();
+    let mut algo: ParallelBFS<Ship> = ParallelBFS::new(threads);
+    let reachable = algo.count_reachable(ship);
+    println!("Reachable states: {} ({} threads, {})", reachable, threads, format_duration(time_start.elapsed()));
+    println!("Layer sizes: {:?}", algo.get_layer_sizes());
+}
+
+// Only for small instances, every reachable configuration is generated
+fn check_ship_heuristic(ship: &Ship) {
+    let report = check_heuristic(ship, &|state: &State| ship.get_heuristic_value(state));
+    println!("States: {}, solutions: {}", report.states, report.solutions);
+    println!("Admissible: {}, consistent: {}", report.is_admissible(), report.is_consistent());
+    let violation = match report.violations.first() {
+        Some(violation) => violation,
+        None => return,
+    };
+    println!("{} violations, first one:", report.violations.len());
+    match violation {
+        Violation::Overestimate { state, heuristic, distance } => {
+            println!("Estimate {} over the distance {} to a solution from:\n{}", heuristic, distance, ship.format_state(state));
+        },
+        Violation::Inconsistent { state, action, successor, cost, heuristic, successor_heuristic } => {
+            println!(
+                "Estimate {} over {} + {} after {} from:\n{}\nto:\n{}",
+                heuristic, cost, successor_heuristic, ship.format_action(action),
+                ship.format_state(state), ship.format_state(successor),
+            );
+        },
+    }
+}
+
+// Checks every edge generated by A*, works on any instance A* can solve
+fn validate_search(ship: &Ship) {
+    let mut algo: ArenaAStar<Ship> = ArenaAStar::new();
+    algo.set_validation(true);
+    let solution = algo.find_solution(ship).expect("No solution was found");
+    println!("Cost: {}, violations: {}", algo.get_arena().get(solution).cost, algo.get_violations().len());
+    let states = algo.get_states();
+    for violation in algo.get_violations().iter().take(3) {
+        match violation {
+            SearchViolation::Inconsistent { state, action, successor, cost, heuristic, successor_heuristic } => println!(
+                "Estimate {} over {} + {} after {} from:\n{}\nto:\n{}",
+                heuristic, cost, successor_heuristic, ship.format_action(action),
+                ship.format_state(states.get(*state)), ship.format_state(states.get(*successor)),
+            ),
+            SearchViolation::DecreasingCost { state, action, cost, .. } => println!(
+                "Negative cost {} of {} from:\n{}", cost, ship.format_action(action), ship.format_state(states.get(*state)),
+            ),
+            SearchViolation::NegativeHeuristic { state, heuristic } => println!(
+                "Negative estimate {} for:\n{}", heuristic, ship.format_state(states.get(*state)),
+            ),
+            SearchViolation::NonZeroSolution { state, heuristic } => println!(
+                "Estimate {} for the solution:\n{}", heuristic, ship.format_state(states.get(*state)),
+            ),
+        }
+    }
+}
+
+fn run_portfolio(ship: &Ship) {
+    let mut portfolio = Portfolio::new(PortfolioMode::First);
+    portfolio
+        .add("a_star", ArenaAStar::new())
+        .add("weighted_a_star", ArenaAStar::new_weighted(2.0))
+        .add("dfbnb", ArenaDFBnB::new())
+        .add("bfs", ArenaBFS::new());
+    let time_start = Instant::now();
+    let result = portfolio.run_concurrent(ship).expect("No solution was found");
+    println!(
+        "Winner: {} in {} (cost {}, length {})",
+        result.name, format_duration(time_start.elapsed()), result.path.cost, result.path.states.len(),
+    );
+}
+
+fn compare_genetic(ship: &Ship) {
+    let state = ship.build_initial_state();
+    let root = ShipNode {
+        hash: ship.hash_state(&state),
+        state: Rc::new(state),
+        action: None,
+        cost: 0,
+        parent: None,
+        extra: ship.build_initial_extra(),
+        ship,
+    };
+    let mut algo: ArenaAStar<Ship> = ArenaAStar::new();
+    let solution = algo.find_solution(ship).expect("No solution was found");
+    let optimum = algo.get_arena().get(solution).cost;
+
+    let time_start = Instant::now();
+    let mut algo: Genetic<ShipNode> = Genetic::new();
+    algo.find_solution(Box::new(root));
+    println!("Genetic: {} (A* optimum {})", format_duration(time_start.elapsed()), optimum);
+    for (generation, best) in algo.get_history().iter().enumerate().step_by(10) {
+        match best {
+            Some(cost) => println!(
+                "\tGeneration {}: cost {} (+{:.1}%)",
+                generation, cost, 100.0 * (cost - optimum) as f64 / optimum as f64,
+            ),
+            None => println!("\tGeneration {}: no solution", generation),
+        }
+    }
+}
+
+fn plan_stowage(ship: &Ship) {
+    let (containers, csp) = ship.build_stowage_csp();
+    let mut solver = CspSolver::new();
+    let time_start = Instant::now();
+    let solution = solver.solve(&csp);
+    let duration = time_start.elapsed();
+    println!(
+        "Duration: {}, assignments: {}, backtracks: {}, checks: {}",
+        format_duration(duration), solver.get_nodes(), solver.get_backtracks(), solver.get_checks(),
+    );
+
+    let cells = match solution {
+        Some(cells) => cells,
+        None => return println!("Not every container fits without blocking another one"),
+    };
+    let mut state = ship.build_initial_state();
+    for (container, (s, d)) in containers.into_iter().zip(cells) {
+        state.container_pos[s][d] = Some(container);
+    }
+    println!("{}", ship.format_state(&state));
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
-    if args.len() != 5 && args.len() != 6 {
+    if args.len() < 5 || args.len() > 7 {
         println!("\nInvalid number of arguments\n\n{}", get_help(&args[0]));
         return;
     }
@@ -36,6 +222,7 @@ fn main() {
     let ship_map_path = Path::new(&args[1]).join(args[2].as_str()).into_boxed_path();
     let heuristic_name = args[4].as_str();
     let search_algo_name = args.get(5).map(|s| s.as_str()).unwrap_or("");
+    let memory_limit_mb = args.get(6).map(|s| s.parse::<usize>().expect("Invalid memory limit"));
 
     let containers = parse::parse_containers(&containers_path).unwrap();
     let ship_stacks = parse::parse_ship_map(&ship_map_path).unwrap();
@@ -44,15 +231,58 @@ fn main() {
 
     let ship = Ship::new(containers, ship_stacks, heuristic_name.to_owned());
 
-    let mut search_algo: Box<dyn Algorithm<ShipNode>> = match search_algo_name {
+    if search_algo_name == "bench" {
+        bench(&ship);
+        return;
+    }
+    if search_algo_name == "reachable" {
+        count_reachable(&ship);
+        return;
+    }
+    if search_algo_name == "check" {
+        check_ship_heuristic(&ship);
+        return;
+    }
+    if search_algo_name == "validate" {
+        validate_search(&ship);
+        return;
+    }
+    if search_algo_name == "portfolio" {
+        run_portfolio(&ship);
+        return;
+    }
+    if search_algo_name == "genetic-compare" {
+        compare_genetic(&ship);
+        return;
+    }
+    if search_algo_name == "stowage" {
+        plan_stowage(&ship);
+        return;
+    }
+
+    let mut search_algo: Box<dyn AlgorithmExt<ShipNode>> = match search_algo_name {
         "bfs" => Box::new(BFS::new()),
         "dfs" => Box::new(DFS::new()),
         "dijkstra" => Box::new(Dijkstra::new()),
+        "mcts" => Box::new(MCTS::new()),
+        "hill_climbing" => Box::new(HillClimbing::new(HillClimbingMode::Steepest)),
+        "first_choice" => Box::new(HillClimbing::new(HillClimbingMode::FirstChoice)),
+        "annealing" => Box::new(SimulatedAnnealing::new()),
+        "tabu" => Box::new(TabuSearch::new()),
+        "genetic" => Box::new(Genetic::new()),
         "a_star" | _ => Box::new(AStar::new()),
     };
 
+    let memory_budget = search_algo.get_memory_budget();
+    memory_budget.set_state_size(|state| state.get_heap_size());
+    if let Some(limit) = memory_limit_mb {
+        memory_budget.set_limit(limit << 20);
+    }
+
+    let state = ship.build_initial_state();
     let root = ShipNode {
-        state: Rc::new(ship.build_initial_state()),
+        hash: ship.hash_state(&state),
+        state: Rc::new(state),
         action: None,
         cost: 0,
         parent: None,
@@ -63,8 +293,15 @@ fn main() {
     // Get solution
 
     let time_start = Instant::now();
-    let solution = search_algo.find_solution(Box::new(root)).expect("No solution was found");
+    let solution = search_algo.find_solution(Box::new(root));
     let duration = time_start.elapsed();
+    let memory = search_algo.get_memory_usage().total();
+
+    if search_algo.is_limit_reached() {
+        println!("Memory limit reached after {} ({} MB)", format_duration(duration), memory >> 20);
+        return;
+    }
+    let solution = solution.expect("No solution was found");
 
     let visited = search_algo.get_visited();
 
@@ -98,13 +335,10 @@ fn main() {
         }
     }
 
-    if duration.as_secs() > 1 {
-        stats_str = format!("{}Duration: {:.2}s\n", stats_str, duration.as_millis() as f64 / 1000.0);
-    } else {
-        stats_str = format!("{}Duration: {:.2}ms\n", stats_str, duration.as_micros() as f64 / 1000.0);
-    }
+    stats_str = format!("{}Duration: {}\n", stats_str, format_duration(duration));
     stats_str = format!("{}Length: {}\n", stats_str, nodes.len());
     stats_str = format!("{}Visited: {}\n", stats_str, visited.len());
+    stats_str = format!("{}Memory: {:.2}MB\n", stats_str, memory as f64 / (1 << 20) as f64);
     stats_str = format!("{}Cost: {}\n", stats_str, ship.calc_final_cost(cost));
     stats_str = format!("{}Cost w/o navigation: {}\n", stats_str, cost);
 
@@ -1,9 +1,9 @@
 use super::container::Container;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum ActionType { Load, Unload, Navigate }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct Action {
     pub type_: ActionType,
     pub container: Option<Container>,
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use search::traits::heuristic::Heuristic;
+
+use super::action::{Action, ActionType};
+use super::container::{Container, ContainerType};
+use super::state::State;
+
+fn get_move_cost(type_: ActionType) -> isize {
+    Action {
+        type_,
+        container: None,
+        stack: None,
+        movement: Some(1),
+    }.get_cost()
+}
+
+// Every container still to load is loaded and unloaded, every container inside is unloaded
+pub struct LoadUnload {
+    pub container_counts: HashMap<Container, usize>,
+}
+
+impl Heuristic<State> for LoadUnload {
+    fn get_heuristic(&self, state: &State) -> isize {
+        let inside_ship: Vec<&Container> = state.container_pos.iter().flatten().flatten().collect();
+        let containers_pending: usize = self.container_counts.iter()
+            .filter(|(c, _)| c.dest_port > state.ship_port)
+            .map(|(_, qnt)| *qnt)
+            .sum();
+        let containers_pending_inside = inside_ship.iter()
+            .filter(|c| c.dest_port > state.ship_port)
+            .count();
+        let containers_to_load: isize = (containers_pending - containers_pending_inside)
+            .try_into().expect("Cost overflowed");
+        let containers_inside_ship: isize = inside_ship.len()
+            .try_into().expect("Cost overflowed");
+
+        let ctl_factor = get_move_cost(ActionType::Load);
+        let cis_factor = get_move_cost(ActionType::Unload);
+
+        containers_to_load * ctl_factor
+        + containers_to_load * cis_factor
+        + containers_inside_ship * cis_factor
+    }
+}
+
+// Every container for a later port is unloaded
+pub struct Fast {
+    pub container_counts: HashMap<Container, usize>,
+    pub last_port: usize,
+}
+
+impl Heuristic<State> for Fast {
+    fn get_heuristic(&self, state: &State) -> isize {
+        let cis_factor = get_move_cost(ActionType::Unload);
+
+        let mut count = 0;
+        for dest_port in state.ship_port + 1..self.last_port + 1 {
+            for type_ in ContainerType::get_all() {
+                let container = Container { type_, dest_port };
+                if let Some(qnt) = self.container_counts.get(&container) {
+                    count += qnt;
+                }
+            }
+        }
+        count.try_into().map(|c: isize| c * cis_factor).expect("Heuristic count overflowed")
+    }
+}
\ No newline at end of file
@@ -5,3 +5,6 @@ pub mod state;
 pub mod action;
 pub mod ship;
 pub mod extra;
+pub mod problem;
+pub mod pattern;
+pub mod heuristics;
@@ -5,10 +5,12 @@ use super::ship::Ship;
 use super::state::State;
 use super::action::Action;
 
+#[derive(Clone)]
 pub struct ShipNode<'a> {
     pub state: Rc<State>,
     pub action: Option<Action>,
     pub cost: isize,
+    pub hash: u64,
     pub parent: Option<Rc<Self>>,
     pub extra: Extra,
     pub ship: &'a Ship,
@@ -27,6 +29,7 @@ impl<'a> Node for ShipNode<'a> {
                 state: Rc::new(state),
                 action: Some(action),
                 cost: parent.cost + action.get_cost(),
+                hash: parent.ship.update_hash(parent.hash, &parent.state, &action),
                 parent: Some(parent.clone()),
                 extra,
                 ship: parent.ship,
@@ -39,8 +42,9 @@ impl<'a> Node for ShipNode<'a> {
     fn get_action(&self) -> Option<Action> { self.action }
     fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
     fn get_cost(&self) -> isize { self.cost }
+    fn get_state_hash(&self) -> Option<u64> { Some(self.hash) }
     fn get_heuristic(&self) -> isize {
-        self.ship.get_heuristic_value(&self.state, &self.extra)
+        self.ship.get_heuristic_value(&self.state)
     }
     fn is_solution(&self) -> bool {
         // if let Some(action) = &self.action {
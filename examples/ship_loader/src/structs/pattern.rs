@@ -0,0 +1,106 @@
+use search::traits::problem::Problem;
+use super::action::{Action, ActionType};
+use super::state::State;
+
+// Ship port and the pattern ports (as indices) of the containers in every stack, bottom first
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct PatternState {
+    pub ship_port: usize,
+    pub stacks: Vec<Vec<usize>>,
+}
+
+/*
+ *  Abstract ship keeping only the containers going to some of the ports: container and cell types
+ *  are ignored and every load or unload costs as much as the cheapest one. Patterns over disjoint
+ *  ports can be added together, the ship has to move every container once.
+ */
+
+pub struct PortPattern {
+    ports: Vec<usize>,
+    counts: Vec<usize>,
+    capacities: Vec<usize>,
+    last_port: usize,
+}
+
+impl PortPattern {
+    // `counts` has the number of containers going to every port in `ports`
+    pub fn new(ports: Vec<usize>, counts: Vec<usize>, capacities: Vec<usize>, last_port: usize) -> Self {
+        PortPattern { ports, counts, capacities, last_port }
+    }
+
+    fn get_stack_size(&self, capacity: usize) -> usize {
+        (0..=capacity).map(|length| self.ports.len().pow(length as u32)).sum()
+    }
+
+    pub fn get_size(&self) -> usize {
+        (self.last_port + 1) * self.capacities.iter().map(|capacity| self.get_stack_size(*capacity)).product::<usize>()
+    }
+
+    // Stacks as numbers in base `ports.len()`, after every shorter stack
+    pub fn rank(&self, state: &PatternState) -> usize {
+        state.stacks.iter().zip(&self.capacities).fold(state.ship_port, |rank, (stack, capacity)| {
+            let shorter = self.get_stack_size(stack.len()) - self.ports.len().pow(stack.len() as u32);
+            let value = stack.iter().fold(0, |value, port| value * self.ports.len() + port);
+            rank * self.get_stack_size(*capacity) + shorter + value
+        })
+    }
+
+    pub fn abstract_state(&self, state: &State) -> PatternState {
+        PatternState {
+            ship_port: state.ship_port,
+            stacks: state.container_pos.iter()
+                .map(|stack| stack.iter().flatten()
+                    .filter_map(|container| self.ports.iter().position(|port| *port == container.dest_port))
+                    .collect())
+                .collect(),
+        }
+    }
+}
+
+impl Problem for PortPattern {
+    type State = PatternState;
+    type Action = ();
+
+    fn get_root(&self) -> PatternState {
+        PatternState { ship_port: 0, stacks: self.capacities.iter().map(|_| Vec::new()).collect() }
+    }
+
+    fn get_successors(&self, state: &PatternState) -> Vec<((), PatternState, isize)> {
+        let load = Action { type_: ActionType::Load, container: None, stack: None, movement: Some(1) }.get_cost();
+        let unload = Action { type_: ActionType::Unload, container: None, stack: None, movement: Some(1) }.get_cost();
+        let mut inside = vec![0; self.ports.len()];
+        for port in state.stacks.iter().flatten() {
+            inside[*port] += 1;
+        }
+        let to_load: Vec<usize> = (0..self.ports.len())
+            .filter(|i| self.ports[*i] > state.ship_port && inside[*i] < self.counts[*i])
+            .collect();
+
+        let mut successors = Vec::new();
+        for (s, stack) in state.stacks.iter().enumerate() {
+            if stack.len() < self.capacities[s] {
+                for port in &to_load {
+                    let mut successor = state.clone();
+                    successor.stacks[s].push(*port);
+                    successors.push(((), successor, load));
+                }
+            }
+            if !stack.is_empty() {
+                let mut successor = state.clone();
+                successor.stacks[s].pop();
+                successors.push(((), successor, unload));
+            }
+        }
+        let to_unload = (0..self.ports.len()).any(|i| self.ports[i] == state.ship_port && inside[i] > 0);
+        if to_load.is_empty() && !to_unload && state.ship_port < self.last_port {
+            successors.push(((), PatternState { ship_port: state.ship_port + 1, stacks: state.stacks.clone() }, 0));
+        }
+        successors
+    }
+
+    fn get_heuristic(&self, _state: &PatternState) -> isize { 0 }
+
+    fn is_solution(&self, state: &PatternState) -> bool {
+        state.ship_port == self.last_port && state.stacks.iter().all(|stack| stack.is_empty())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use search::traits::problem::Problem;
+use super::action::Action;
+use super::ship::Ship;
+use super::state::State;
+
+impl Problem for Ship {
+    type State = State;
+    type Action = Action;
+
+    fn get_root(&self) -> State { self.build_initial_state() }
+
+    fn get_successors(&self, state: &State) -> Vec<(Action, State, isize)> {
+        let extra = self.build_extra(state);
+        self.get_possible_actions(state, &extra)
+            .into_iter()
+            .map(|action| {
+                let (successor, _) = self.take_action(state, &extra, &action);
+                (action, successor, action.get_cost())
+            })
+            .collect()
+    }
+
+    fn get_heuristic(&self, state: &State) -> isize {
+        self.get_heuristic_value(state)
+    }
+
+    fn is_solution(&self, state: &State) -> bool { self.is_state_solution(state) }
+    fn get_state_hash(&self, state: &State) -> Option<u64> { Some(self.hash_state(state)) }
+}
\ No newline at end of file
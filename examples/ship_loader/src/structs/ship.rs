@@ -1,17 +1,27 @@
 use std::collections::HashMap;
+use std::iter;
+
+use search::csp::problem::{Csp, VarId};
+use search::heuristics::pdb::{AbstractionFn, PatternDatabase, PdbHeuristic};
+use search::heuristics::combinators::{Max, Selector};
+use search::traits::heuristic::Heuristic;
+use search::utils::zobrist::Zobrist;
 
 use super::action::{Action, ActionType};
 use super::cell::{Cell, CellType};
 use super::container::{Container, ContainerType};
 use super::state::State;
 use super::extra::Extra;
+use super::pattern::PortPattern;
+use super::heuristics::{Fast, LoadUnload};
 
 pub struct Ship {
     container_counts: HashMap<Container, usize>,
     stacks: Vec<Vec<Cell>>,
-    heuristic_name: String,
     last_port: usize,
     stack_height: usize,
+    zobrist: Zobrist,
+    heuristic: Selector<State>,
 }
 
 impl Ship {
@@ -26,7 +36,57 @@ impl Ship {
             container_counts.insert(container, 1 + container_counts.get(&container).unwrap_or(&0));
         }
         let stack_height = stacks.get(0).map(|s| s.len()).unwrap_or(0);
-        Ship { container_counts, stacks, heuristic_name, last_port, stack_height }
+        // One position per cell plus the ship port, values are container kinds or port numbers
+        let zobrist = Zobrist::new(
+            stacks.len() * stack_height + 1,
+            ContainerType::get_all().len() * (last_port + 1) + 1,
+            0x5EED,
+        );
+        let mut ship = Ship { container_counts, stacks, last_port, stack_height, zobrist, heuristic: Selector::new() };
+        ship.heuristic = ship.build_heuristic(&heuristic_name);
+        ship
+    }
+
+    // Unknown names keep the null heuristic
+    fn build_heuristic(&self, name: &str) -> Selector<State> {
+        let load_unload = || LoadUnload { container_counts: self.container_counts.clone() };
+        let fast = || Fast { container_counts: self.container_counts.clone(), last_port: self.last_port };
+        let mut max = Max::new();
+        max.add(load_unload()).add(fast());
+
+        let mut selector = Selector::new();
+        selector
+            .add("null", |_: &State| 0)
+            .add("heuristica1", load_unload())
+            .add("heuristica2", fast())
+            .add("max", max);
+        // Dead ends in the abstraction are dead ends of the ship too
+        if name == "pdb" {
+            let pdb = self.build_pdb_heuristic();
+            selector.add("pdb", move |state: &State| pdb.get_heuristic(state).unwrap_or(isize::MAX / 2));
+        }
+        selector.select(name);
+        selector
+    }
+
+    // Additive pattern databases over pairs of consecutive ports
+    fn build_pdb_heuristic(&self) -> PdbHeuristic<State> {
+        let capacities: Vec<usize> = self.stacks.iter()
+            .map(|stack| stack.iter().filter(|cell| cell.type_ != CellType::Unavailable).count())
+            .collect();
+        let ports: Vec<usize> = (1..=self.last_port).collect();
+        let group = ports.chunks(2).map(|ports| {
+            let counts = ports.iter()
+                .map(|port| self.container_counts.iter().filter(|(c, _)| c.dest_port == *port).map(|(_, qnt)| qnt).sum())
+                .collect();
+            let pattern = PortPattern::new(ports.to_vec(), counts, capacities.clone(), self.last_port);
+            let pdb = PatternDatabase::build(&pattern, pattern.get_size(), |state| pattern.rank(state));
+            let abstraction: AbstractionFn<State> = Box::new(move |state| pattern.rank(&pattern.abstract_state(state)));
+            (pdb, abstraction)
+        }).collect();
+        let mut heuristic = PdbHeuristic::new();
+        heuristic.add_additive(group);
+        heuristic
     }
 
     pub fn build_initial_state(&self) -> State {
@@ -39,10 +99,54 @@ impl Ship {
     }
 
     pub fn build_initial_extra(&self) -> Extra {
-        let state = self.build_initial_state();
+        self.build_extra(&self.build_initial_state())
+    }
+
+    pub fn build_extra(&self, state: &State) -> Extra {
         Extra {
-            containers_inside_ship: self.get_containers_inside_ship(&state),
-            containers_to_load: self.get_containers_to_load(&state),
+            containers_inside_ship: self.get_containers_inside_ship(state),
+            containers_to_load: self.get_containers_to_load(state),
+        }
+    }
+
+    fn get_cell_position(&self, stack: usize, depth: usize) -> usize {
+        stack * self.stack_height + depth
+    }
+
+    fn get_port_position(&self) -> usize {
+        self.stacks.len() * self.stack_height
+    }
+
+    fn get_container_value(&self, container: &Container) -> usize {
+        let type_ = match container.type_ {
+            ContainerType::Standard => 0,
+            ContainerType::Refrigerated => 1,
+        };
+        type_ * (self.last_port + 1) + container.dest_port
+    }
+
+    pub fn hash_state(&self, state: &State) -> u64 {
+        let containers = state.container_pos.iter().enumerate().flat_map(|(s, stack)| {
+            stack.iter().enumerate().filter_map(move |(d, c)| c.map(|c| (s, d, c)))
+        });
+        self.zobrist.hash(
+            containers
+                .map(|(s, d, c)| (self.get_cell_position(s, d), self.get_container_value(&c)))
+                .chain(iter::once((self.get_port_position(), state.ship_port)))
+        )
+    }
+
+    // Hash of the state resulting from `take_action`, without rehashing it
+    pub fn update_hash(&self, hash: u64, state: &State, action: &Action) -> u64 {
+        match action.type_ {
+            ActionType::Navigate => self.zobrist.replace(
+                hash, self.get_port_position(), state.ship_port, state.ship_port + 1,
+            ),
+            ActionType::Load | ActionType::Unload => self.zobrist.toggle(
+                hash,
+                self.get_cell_position(action.stack.unwrap(), self.stack_height - action.movement.unwrap()),
+                self.get_container_value(&action.container.unwrap()),
+            ),
         }
     }
 
@@ -88,6 +192,42 @@ impl Ship {
         state.container_pos.iter().all(|row| row.iter().all(|c| c.is_none()))
     }
 
+    // Loading every container at the first port, one variable per container with its (stack, depth) as value
+    pub fn build_stowage_csp(&self) -> (Vec<Container>, Csp<(usize, usize)>) {
+        let mut containers: Vec<Container> = self.container_counts.iter()
+            .flat_map(|(container, qnt)| iter::repeat_n(*container, *qnt))
+            .collect();
+        containers.sort_by_key(|container| self.get_container_value(container));
+
+        let mut csp = Csp::new();
+        let vars: Vec<VarId> = containers.iter().map(|container| {
+            let cells = self.stacks.iter().enumerate().flat_map(|(s, stack)| {
+                stack.iter().enumerate()
+                    .filter(|(_, cell)| cell.type_.get_compatible_containers().contains(&container.type_))
+                    .map(move |(d, _)| (s, d))
+            });
+            csp.add_variable(cells.collect())
+        }).collect();
+        csp.add_all_different(&vars);
+
+        // Containers above leave the ship first, so nothing has to be moved to unload
+        for (i, a) in containers.iter().enumerate() {
+            for (j, b) in containers.iter().enumerate().skip(i + 1) {
+                let (port_a, port_b) = (a.dest_port, b.dest_port);
+                csp.add_binary(vars[i], vars[j], move |(sa, da), (sb, db)| {
+                    sa != sb || (da > db && port_a <= port_b) || (db > da && port_b <= port_a)
+                });
+            }
+        }
+
+        // Every usable cell under a container is taken
+        let stacks = self.stacks.clone();
+        csp.add_constraint(vars, Box::new(move |cells| cells.iter().all(|(s, d)| {
+            (0..*d).all(|below| stacks[*s][below].type_ == CellType::Unavailable || cells.contains(&&(*s, below)))
+        })));
+        (containers, csp)
+    }
+
     fn get_containers_inside_ship(&self, state: &State) -> HashMap<Container, usize> {
         let mut inside_ship: HashMap<Container, usize> = HashMap::new();
         for stack in &state.container_pos {
@@ -147,8 +287,9 @@ impl Ship {
         let are_containers_to_unload = extra.containers_to_unload(state.ship_port)
             .values().any(|qnt| *qnt != 0);
 
-        // Action 1: If no containers are left to load or unload
+        // Action 1: If no containers are left to load or unload (there is nowhere to go after the last port)
         if !are_containers_to_load && !are_containers_to_unload {
+            if state.ship_port == self.last_port { return vec![] }
             return vec![Action {
                 type_: ActionType::Navigate,
                 container: None,
@@ -224,63 +365,8 @@ impl Ship {
         (new, new_extra)
     }
 
-    pub fn get_load_unload_heuristic(&self, extra: &Extra) -> isize {
-        let containers_to_load: isize = extra.containers_to_load.values()
-            .map(|v| *v).reduce(|acc, qnt| acc + qnt)
-            .unwrap_or(0).try_into().expect("Cost overflowed");
-        let containers_inside_ship: isize = extra.containers_inside_ship.values()
-            .map(|v| *v).reduce(|acc, qnt| acc + qnt)
-            .unwrap_or(0).try_into().expect("Cost overflowed");
-
-        let ctl_factor = Action {
-            type_: ActionType::Load,
-            container: None,
-            stack: None,
-            movement: Some(1),
-        }.get_cost();
-
-        let cis_factor = Action {
-            type_: ActionType::Unload,
-            container: None,
-            stack: None,
-            movement: Some(1),
-        }.get_cost();
-
-        containers_to_load * ctl_factor
-        + containers_to_load * cis_factor
-        + containers_inside_ship * cis_factor
-    }
-
-    pub fn get_fast_heuristic(&self, state: &State) -> isize {
-        let cis_factor = Action {
-            type_: ActionType::Unload,
-            container: None,
-            stack: None,
-            movement: Some(1),
-        }.get_cost();
-
-        let mut count = 0;
-        for dest_port in state.ship_port + 1..self.last_port + 1 {
-            for type_ in ContainerType::get_all() {
-                let container = Container { type_, dest_port };
-                if let Some(qnt) = self.container_counts.get(&container) {
-                    count += qnt;
-                }
-            }
-        }
-        count.try_into().map(|c: isize| c * cis_factor).expect("Heuristic count overflowed")
-    }
-
-    pub fn get_null_heuristic(&self) -> isize {
-        0
-    }
-
-    pub fn get_heuristic_value(&self, state: &State, extra: &Extra) -> isize {
-        match self.heuristic_name.as_str() {
-            "heuristica1" => self.get_load_unload_heuristic(extra),
-            "heuristica2" => self.get_fast_heuristic(state),
-            "null" | _ => self.get_null_heuristic(),
-        }
+    pub fn get_heuristic_value(&self, state: &State) -> isize {
+        self.heuristic.get_heuristic(state)
     }
 
     pub fn calc_final_cost(&self, cost: isize) -> isize {
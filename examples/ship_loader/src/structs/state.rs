@@ -1,7 +1,17 @@
+use std::mem::size_of;
 use super::container::Container;
 
 #[derive(Clone, Hash, PartialEq, Eq)]
 pub struct State {
     pub container_pos: Vec<Vec<Option<Container>>>,
     pub ship_port: usize,
+}
+
+impl State {
+    pub fn get_heap_size(&self) -> usize {
+        self.container_pos.capacity() * size_of::<Vec<Option<Container>>>()
+            + self.container_pos.iter()
+                .map(|stack| stack.capacity() * size_of::<Option<Container>>())
+                .sum::<usize>()
+    }
 }
\ No newline at end of file
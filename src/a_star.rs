@@ -1,45 +1,158 @@
 use super::traits::{
     node::Node,
-    algorithm::Algorithm,
+    algorithm::{Algorithm, AlgorithmExt},
     p_tuple::PTuple
 };
-use std::collections::{BinaryHeap, HashMap};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::state_table::StateTable;
+use super::utils::validation::{check_descendant, get_parent, NodeValidator, NodeViolation};
+use std::collections::BinaryHeap;
+use std::mem::size_of;
 use std::rc::Rc;
 
 pub struct AStar<T: Node> {
     p_queue: BinaryHeap<PTuple<Box<T>>>,
-    visited: HashMap<Rc<T::State>, isize>,
+    states: StateTable<Rc<T::State>>,
+    costs: Vec<isize>,
+    expanded: usize,
+    memory: MemoryBudget<T::State>,
+    validator: NodeValidator<T>,
 }
 
 impl<T: Node> AStar<T> {
     pub fn new() -> Self {
         AStar {
             p_queue: BinaryHeap::new(),
-            visited: HashMap::new(),
+            states: StateTable::new(),
+            costs: Vec::new(),
+            expanded: 0,
+            memory: MemoryBudget::new(),
+            validator: NodeValidator::<T>::new(),
         }
     }
 
-    fn cost_fn(n: &Box<T>) -> isize { n.get_cost() + n.get_heuristic() }
+    // Checks the heuristic and costs of every generated edge, see `utils::validation`
+    pub fn set_validation(&mut self, enabled: bool) -> &mut Self {
+        self.validator.set_enabled(enabled);
+        self
+    }
+
+    pub fn get_violations(&self) -> &[NodeViolation<T>] { self.validator.get_violations() }
+
+    fn cost_fn(n: &T) -> isize { n.get_cost() + n.get_heuristic() }
+}
+
+impl<T: Node> Default for AStar<T> {
+    fn default() -> Self { Self::new() }
 }
 
 impl<T: Node> Algorithm<T> for AStar<T> {
     fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
-        self.visited.insert(root.get_state(), root.get_cost());
-        self.p_queue.push(PTuple { priority: AStar::cost_fn(&root), node: root });
+        self.memory.reset();
+        self.validator.clear();
+        if self.validator.is_enabled() {
+            self.validator.check_state(&root.get_state(), root.get_heuristic(), root.is_solution());
+        }
+        self.p_queue.clear();
+        self.states.clear();
+        self.costs.clear();
+        self.expanded = 0;
+        let (_, inserted) = self.states.intern_with(root.get_state(), root.get_state_hash());
+        if inserted {
+            self.memory.add_state(&root.get_state());
+            self.costs.push(root.get_cost());
+        }
+        self.p_queue.push(PTuple { priority: Self::cost_fn(&root), node: root });
 
         while let Some(PTuple {priority: _, node}) = self.p_queue.pop() {
             if node.is_solution() { return Some(node) }
+            if self.memory.check(&self.get_memory_usage()) { return None }
+            self.expanded += 1;
+            let parent = get_parent(&self.validator, &*node);
             for descendant in node.get_descendants() {
-                let visited_cost = *self.visited.get(&descendant.get_state()).unwrap_or(&isize::MAX);
-                if visited_cost <= AStar::cost_fn(&descendant) { continue; }
-                self.visited.insert(descendant.get_state().clone(), descendant.get_cost());
-                self.p_queue.push(PTuple { priority: AStar::cost_fn(&descendant), node: descendant });
+                check_descendant(&mut self.validator, &parent, &*descendant);
+                let (id, inserted) = self.states.intern_with(descendant.get_state(), descendant.get_state_hash());
+                if inserted {
+                    self.costs.push(isize::MAX);
+                    self.memory.add_state(self.states.get(id));
+                }
+                if self.costs[id] <= Self::cost_fn(&descendant) { continue; }
+                self.costs[id] = descendant.get_cost();
+                self.p_queue.push(PTuple { priority: Self::cost_fn(&descendant), node: descendant });
             }
         }
         None
     }
 
     fn get_visited(&self) -> Vec<&Rc<T::State>> {
-        self.visited.keys().collect()
+        self.states.iter().collect()
+    }
+}
+
+impl<T: Node> AlgorithmExt<T> for AStar<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.p_queue.capacity() * size_of::<PTuple<Box<T>>>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.costs.capacity() * size_of::<isize>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.p_queue.len() + self.expanded) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bfs::BFS, dfbnb::DFBnB, dfs::DFS, dijkstra::Dijkstra};
+    use crate::utils::fixtures::GridNode;
+
+    fn solve<A: Algorithm<GridNode>>(algorithm: &mut A) -> Option<isize> {
+        algorithm.find_solution(GridNode::root()).map(|solution| solution.get_cost())
+    }
+
+    #[test]
+    fn searches_again_from_scratch() {
+        let mut dijkstra = Dijkstra::new();
+        let optimum = solve(&mut dijkstra).unwrap();
+        assert_eq!(solve(&mut dijkstra), Some(optimum));
+
+        let mut a_star = AStar::new();
+        assert_eq!(solve(&mut a_star), Some(optimum));
+        let states = a_star.get_states().len();
+        assert_eq!(solve(&mut a_star), Some(optimum));
+        assert_eq!(a_star.get_states().len(), states);
+
+        let mut dfbnb = DFBnB::new();
+        assert_eq!(solve(&mut dfbnb), Some(optimum));
+        assert_eq!(solve(&mut dfbnb), Some(optimum));
+
+        let mut bfs = BFS::new();
+        for _ in 0..2 {
+            let solution = bfs.find_solution(GridNode::root()).unwrap();
+            let steps = std::iter::successors(solution.get_parent(), |node| node.get_parent()).count();
+            assert_eq!(steps, 8);
+        }
+
+        let mut dfs = DFS::new();
+        assert!(solve(&mut dfs).is_some());
+        assert!(solve(&mut dfs).is_some());
+    }
+
+    #[test]
+    fn memory_limit_only_stops_one_search() {
+        let mut a_star = AStar::new();
+        a_star.get_memory_budget().set_limit(1);
+        assert_eq!(solve(&mut a_star), None);
+        assert!(a_star.is_limit_reached());
+        a_star.get_memory_budget().set_limit(usize::MAX);
+        assert!(solve(&mut a_star).is_some());
+        assert!(!a_star.is_limit_reached());
     }
 }
\ No newline at end of file
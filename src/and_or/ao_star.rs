@@ -0,0 +1,265 @@
+use crate::traits::and_or::AndOrProblem;
+use crate::utils::policy::{CostModel, Policy};
+use crate::utils::state_table::{StateId, StateTable};
+
+// Relative change of a value below which it is not propagated any further
+const TOLERANCE: f64 = 1e-12;
+
+struct Connector<A> {
+    action: A,
+    cost: isize,
+    // (node, probability)
+    outcomes: Vec<(usize, f64)>,
+}
+
+struct AndOrNode<A> {
+    state: StateId,
+    parent: Option<usize>,
+    connectors: Vec<Connector<A>>,
+    // Estimated cost of the best policy from the node, infinite if it has none
+    value: f64,
+    best: Option<usize>,
+    solved: bool,
+    expanded: bool,
+    // Ancestor a loop goes back to, and the loops going back to this node
+    target: Option<usize>,
+    loops: Vec<usize>,
+}
+
+/*
+ *  AO* over the tree unfolding of the problem: repeatedly expands an unexpanded node of the best
+ *  partial policy (following the cheapest connector of every node from the root), then revises the
+ *  values of its ancestors. A connector costs its step cost plus the worst or expected value of its
+ *  outcomes, and a node is solved once every outcome of its best connector is.
+ *
+ *  An outcome already on the path to the node is a loop back to that ancestor and costs its value.
+ *  With `Expected` an action may have loops when another outcome with a positive probability makes
+ *  progress (a cancelled flight is taken again), values are revised until they stop changing as in
+ *  value iteration. With `WorstCase` a loop may repeat forever, actions with one are discarded and
+ *  policies are acyclic. With an admissible heuristic the returned policy is optimal for the cost model.
+ */
+
+pub struct AOStar<P: AndOrProblem> {
+    model: CostModel,
+    nodes: Vec<AndOrNode<P::Action>>,
+    states: StateTable<P::State>,
+    expanded: usize,
+}
+
+impl<P: AndOrProblem> AOStar<P> {
+    pub fn new(model: CostModel) -> Self {
+        AOStar {
+            model,
+            nodes: Vec::new(),
+            states: StateTable::new(),
+            expanded: 0,
+        }
+    }
+
+    pub fn find_policy(&mut self, problem: &P) -> Option<Policy<P::State, P::Action>> {
+        self.nodes.clear();
+        self.states.clear();
+        self.expanded = 0;
+        self.add_node(problem, problem.get_root(), None);
+        loop {
+            if self.nodes[0].solved { return Some(self.extract(0)) }
+            if self.nodes[0].value.is_infinite() { return None }
+            let tip = self.select(0)?;
+            self.expand(problem, tip);
+            self.revise(tip);
+        }
+    }
+
+    pub fn get_expanded(&self) -> usize { self.expanded }
+    pub fn get_tree_size(&self) -> usize { self.nodes.len() }
+    pub fn get_states(&self) -> &StateTable<P::State> { &self.states }
+
+    fn add_node(&mut self, problem: &P, state: P::State, parent: Option<usize>) -> usize {
+        let solved = problem.is_solution(&state);
+        let value = if solved { 0.0 } else { problem.get_heuristic(&state) as f64 };
+        let hash = problem.get_state_hash(&state);
+        let (state, _) = self.states.intern_with(state, hash);
+        self.nodes.push(AndOrNode {
+            state, parent, connectors: Vec::new(), value, best: None, solved, expanded: solved, target: None, loops: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    // Leaf back to `target`, nothing below it is left to expand
+    fn add_loop(&mut self, target: usize, parent: usize) -> usize {
+        let (state, value) = (self.nodes[target].state, self.nodes[target].value);
+        self.nodes.push(AndOrNode {
+            state, parent: Some(parent), connectors: Vec::new(), value, best: None, solved: true, expanded: true,
+            target: Some(target), loops: Vec::new(),
+        });
+        let index = self.nodes.len() - 1;
+        self.nodes[target].loops.push(index);
+        index
+    }
+
+    // Unexpanded node of the best partial policy below `index`
+    fn select(&self, index: usize) -> Option<usize> {
+        let node = &self.nodes[index];
+        if node.solved { return None }
+        if !node.expanded { return Some(index) }
+        node.connectors[node.best?].outcomes.iter().find_map(|(child, _)| self.select(*child))
+    }
+
+    fn expand(&mut self, problem: &P, index: usize) {
+        self.expanded += 1;
+        self.nodes[index].expanded = true;
+        // Closest ancestor first
+        let mut path = Vec::new();
+        let mut next = Some(index);
+        while let Some(ancestor) = next {
+            path.push((self.nodes[ancestor].state, ancestor));
+            next = self.nodes[ancestor].parent;
+        }
+
+        let state = self.states.get(self.nodes[index].state).clone();
+        for (action, cost, outcomes) in problem.get_actions(&state) {
+            let targets: Vec<Option<usize>> = outcomes.iter().map(|(outcome, _)| {
+                let id = self.states.get_id_with(outcome, problem.get_state_hash(outcome))?;
+                path.iter().find(|(state, _)| *state == id).map(|(_, ancestor)| *ancestor)
+            }).collect();
+            if targets.iter().any(Option::is_some) {
+                let progress = outcomes.iter().zip(&targets).any(|((_, probability), target)| target.is_none() && *probability > 0.0);
+                if self.model == CostModel::WorstCase || !progress { continue; }
+            }
+            let outcomes = outcomes.into_iter().zip(targets)
+                .map(|((outcome, probability), target)| match target {
+                    Some(target) => (self.add_loop(target, index), probability),
+                    None => (self.add_node(problem, outcome, Some(index)), probability),
+                })
+                .collect();
+            self.nodes[index].connectors.push(Connector { action, cost, outcomes });
+        }
+    }
+
+    // Recomputes the values from `index` up to the root, stops when a node does not change. Loops back
+    // to a changed node take its value and are revised from their parent in turn.
+    fn revise(&mut self, index: usize) {
+        let mut pending = vec![index];
+        while let Some(index) = pending.pop() {
+            self.revise_up(index, &mut pending);
+        }
+    }
+
+    fn revise_up(&mut self, index: usize, pending: &mut Vec<usize>) {
+        let mut next = Some(index);
+        while let Some(index) = next {
+            let mut best: Option<(usize, f64, bool)> = None;
+            for (i, connector) in self.nodes[index].connectors.iter().enumerate() {
+                let outcomes = connector.outcomes.iter().map(|(child, probability)| (*probability, self.nodes[*child].value));
+                let value = connector.cost as f64 + self.model.combine(outcomes);
+                let solved = connector.outcomes.iter().all(|(child, _)| self.nodes[*child].solved);
+                // Solved connectors win ties
+                if best.map(|(_, best, best_solved)| value < best || (value == best && solved && !best_solved)).unwrap_or(true) {
+                    best = Some((i, value, solved));
+                }
+            }
+            let (best, value, solved) = match best {
+                Some((i, value, solved)) => (Some(i), value, solved),
+                None => (None, f64::INFINITY, false),
+            };
+
+            let node = &mut self.nodes[index];
+            let unchanged = node.value == value || (node.value - value).abs() <= TOLERANCE * value.abs().max(1.0);
+            node.value = value;
+            if node.best == best && node.solved == solved && unchanged { break; }
+            node.best = best;
+            node.solved = solved;
+            next = node.parent;
+            for leaf in self.nodes[index].loops.clone() {
+                self.nodes[leaf].value = value;
+                pending.extend(self.nodes[leaf].parent);
+            }
+        }
+    }
+
+    fn extract(&self, index: usize) -> Policy<P::State, P::Action> {
+        let node = &self.nodes[index];
+        let state = self.states.get(node.state).clone();
+        if node.target.is_some() {
+            return Policy { state, action: None, cost: 0, outcomes: Vec::new(), looped: true }
+        }
+        match node.best {
+            Some(best) => {
+                let connector = &node.connectors[best];
+                Policy {
+                    state,
+                    action: Some(connector.action.clone()),
+                    cost: connector.cost,
+                    outcomes: connector.outcomes.iter().map(|(child, probability)| (*probability, self.extract(*child))).collect(),
+                    looped: false,
+                }
+            }
+            None => Policy { state, action: None, cost: 0, outcomes: Vec::new(), looped: false },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::and_or::search::AndOrSearch;
+    use crate::traits::and_or::Outcomes;
+
+    // Home to the destination: driving, or a taxi to the airport and then a train or a flight that
+    // is cancelled half of the time. Waiting at the airport never gets anywhere.
+    struct Trip;
+
+    impl AndOrProblem for Trip {
+        type State = &'static str;
+        type Action = &'static str;
+
+        fn get_root(&self) -> &'static str { "home" }
+
+        fn get_actions(&self, state: &&'static str) -> Vec<(&'static str, isize, Outcomes<&'static str>)> {
+            match *state {
+                "home" => vec![("taxi", 1, vec![("airport", 1.0)]), ("drive", 10, vec![("destination", 1.0)])],
+                "airport" => vec![
+                    ("wait", 0, vec![("airport", 1.0)]),
+                    ("fly", 2, vec![("destination", 0.5), ("airport", 0.5)]),
+                    ("train", 8, vec![("destination", 1.0)]),
+                ],
+                _ => Vec::new(),
+            }
+        }
+
+        fn get_heuristic(&self, _state: &&'static str) -> isize { 0 }
+        fn is_solution(&self, state: &&'static str) -> bool { *state == "destination" }
+    }
+
+    #[test]
+    fn expected_cost_takes_the_flight() {
+        let mut search = AOStar::new(CostModel::Expected);
+        for _ in 0..2 {
+            let policy = search.find_policy(&Trip).unwrap();
+            assert_eq!(policy.get_action(&"home"), Some(&"taxi"));
+            assert_eq!(policy.get_action(&"airport"), Some(&"fly"));
+            assert!(policy.has_loops());
+            // 1 + 2 / (1 - 0.5)
+            assert!((policy.get_cost(CostModel::Expected) - 5.0).abs() < 1e-9);
+            assert_eq!(search.get_states().len(), 3);
+        }
+    }
+
+    #[test]
+    fn worst_case_avoids_loops() {
+        let policy = AOStar::new(CostModel::WorstCase).find_policy(&Trip).unwrap();
+        assert_eq!(policy.get_action(&"airport"), Some(&"train"));
+        assert!(!policy.has_loops());
+        assert_eq!(policy.get_cost(CostModel::WorstCase), 9.0);
+    }
+
+    #[test]
+    fn depth_first_skips_loops_without_progress() {
+        let mut search = AndOrSearch::new();
+        let policy = search.find_policy(&Trip).unwrap();
+        assert_eq!(policy.get_action(&"airport"), Some(&"fly"));
+        let expanded = search.get_expanded();
+        search.find_policy(&Trip).unwrap();
+        assert_eq!(search.get_expanded(), expanded);
+    }
+}
\ No newline at end of file
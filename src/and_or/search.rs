@@ -0,0 +1,75 @@
+use crate::traits::and_or::{AndOrProblem, Outcomes};
+use crate::utils::policy::{Branches, Policy};
+use crate::utils::state_table::StateTable;
+
+/*
+ *  Depth-first AND-OR search: an OR node (state) needs one action whose AND node (outcomes) has a
+ *  policy for every outcome. Reaching a state already on the current path ends that outcome in a loop
+ *  back to it, which is only allowed when another outcome of the action with a positive probability
+ *  makes progress (a cancelled flight is taken again). Such a policy reaches a solution with
+ *  probability 1, but not within a bounded number of actions. Actions are tried in the order given by
+ *  the problem, the policy is the first one found, not the cheapest.
+ */
+
+pub struct AndOrSearch<P: AndOrProblem> {
+    states: StateTable<P::State>,
+    // Indexed by state id
+    on_path: Vec<bool>,
+    expanded: usize,
+}
+
+impl<P: AndOrProblem> AndOrSearch<P> {
+    pub fn new() -> Self {
+        AndOrSearch {
+            states: StateTable::new(),
+            on_path: Vec::new(),
+            expanded: 0,
+        }
+    }
+
+    pub fn find_policy(&mut self, problem: &P) -> Option<Policy<P::State, P::Action>> {
+        self.states.clear();
+        self.on_path.clear();
+        self.expanded = 0;
+        self.or_search(problem, problem.get_root())
+    }
+
+    pub fn get_expanded(&self) -> usize { self.expanded }
+    pub fn get_states(&self) -> &StateTable<P::State> { &self.states }
+
+    fn or_search(&mut self, problem: &P, state: P::State) -> Option<Policy<P::State, P::Action>> {
+        if problem.is_solution(&state) {
+            return Some(Policy { state, action: None, cost: 0, outcomes: Vec::new(), looped: false })
+        }
+        let hash = problem.get_state_hash(&state);
+        let (id, inserted) = self.states.intern_with(state.clone(), hash);
+        if inserted { self.on_path.push(false); }
+        if self.on_path[id] {
+            return Some(Policy { state, action: None, cost: 0, outcomes: Vec::new(), looped: true })
+        }
+
+        self.on_path[id] = true;
+        self.expanded += 1;
+        let mut policy = None;
+        for (action, cost, outcomes) in problem.get_actions(&state) {
+            if let Some(outcomes) = self.and_search(problem, outcomes) {
+                policy = Some(Policy { state, action: Some(action), cost, outcomes, looped: false });
+                break;
+            }
+        }
+        self.on_path[id] = false;
+        policy
+    }
+
+    fn and_search(&mut self, problem: &P, outcomes: Outcomes<P::State>) -> Option<Branches<P::State, P::Action>> {
+        let branches: Branches<P::State, P::Action> = outcomes.into_iter()
+            .map(|(state, probability)| self.or_search(problem, state).map(|policy| (probability, policy)))
+            .collect::<Option<_>>()?;
+        // Retrying forever is not a plan
+        branches.iter().any(|(probability, policy)| !policy.looped && *probability > 0.0).then_some(branches)
+    }
+}
+
+impl<P: AndOrProblem> Default for AndOrSearch<P> {
+    fn default() -> Self { Self::new() }
+}
\ No newline at end of file
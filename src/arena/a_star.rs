@@ -0,0 +1,162 @@
+use crate::traits::{
+    problem::{Problem, ProblemAlgorithm, Step},
+    p_tuple::PTuple
+};
+use crate::utils::arena::{Arena, ArenaNode, NodeId};
+use crate::utils::memory::{MemoryBudget, MemoryUsage};
+use crate::utils::state_table::{StateId, StateTable};
+use crate::utils::validation::{check_successor, Validator, Violation};
+use std::collections::BinaryHeap;
+use std::mem::size_of;
+
+pub struct AStar<P: Problem> {
+    p_queue: BinaryHeap<PTuple<NodeId>>,
+    arena: Arena<P::Action>,
+    states: StateTable<P::State>,
+    costs: Vec<isize>,
+    memory: MemoryBudget<P::State>,
+    weight: f64,
+    validator: Validator<StateId, P::Action>,
+}
+
+impl<P: Problem> AStar<P> {
+    pub fn new() -> Self {
+        Self::new_weighted(1.0)
+    }
+
+    // f = g + weight * h, solutions cost at most `weight` times the optimum
+    pub fn new_weighted(weight: f64) -> Self {
+        AStar {
+            p_queue: BinaryHeap::new(),
+            arena: Arena::new(),
+            states: StateTable::new(),
+            costs: Vec::new(),
+            memory: MemoryBudget::new(),
+            weight,
+            validator: Validator::new(),
+        }
+    }
+
+    // Checks the heuristic and that costs are monotonic on every generated edge, see `utils::validation`
+    pub fn set_validation(&mut self, enabled: bool) -> &mut Self {
+        self.validator.set_enabled(enabled);
+        self
+    }
+
+    // States are ids in `get_states`
+    pub fn get_violations(&self) -> &[Violation<StateId, P::Action>] { self.validator.get_violations() }
+
+    fn cost_fn(&self, cost: isize, heuristic: isize) -> isize {
+        cost + (self.weight * heuristic as f64).round() as isize
+    }
+}
+
+impl<P: Problem> Default for AStar<P> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P: Problem> ProblemAlgorithm<P> for AStar<P> {
+    fn start(&mut self, problem: &P) {
+        self.p_queue.clear();
+        self.arena.clear();
+        self.states.clear();
+        self.costs.clear();
+        self.memory.reset();
+        self.validator.clear();
+        let root = problem.get_root();
+        let priority = self.cost_fn(0, problem.get_heuristic(&root));
+        let hash = problem.get_state_hash(&root);
+        self.memory.add_state(&root);
+        let (state, _) = self.states.intern_with(root, hash);
+        if self.validator.is_enabled() {
+            let root = self.states.get(state);
+            self.validator.check_state(&state, problem.get_heuristic(root), problem.is_solution(root));
+        }
+        self.costs.push(0);
+        let node = self.arena.push(ArenaNode { state, action: None, parent: None, cost: 0 });
+        self.p_queue.push(PTuple { priority, node });
+    }
+
+    fn step(&mut self, problem: &P) -> Step {
+        let Some(PTuple {priority: _, node}) = self.p_queue.pop() else { return Step::Exhausted };
+        let ArenaNode { state, cost, .. } = *self.arena.get(node);
+        // Stale entry, a cheaper path to this state was found after pushing it
+        if cost > self.costs[state] { return Step::Running }
+        if problem.is_solution(self.states.get(state)) { return Step::Solved(node) }
+        if self.memory.check(&self.get_memory_usage()) { return Step::Exhausted }
+        for (action, successor, step_cost) in problem.get_successors(self.states.get(state)) {
+            let successor_cost = cost + step_cost;
+            let hash = problem.get_state_hash(&successor);
+            let (successor_state, inserted) = self.states.intern_with(successor, hash);
+            check_successor(&mut self.validator, problem, &self.states, state, &action, step_cost, successor_state);
+            if inserted {
+                self.costs.push(isize::MAX);
+                self.memory.add_state(self.states.get(successor_state));
+            }
+            if self.costs[successor_state] <= successor_cost { continue; }
+            self.costs[successor_state] = successor_cost;
+            let priority = self.cost_fn(successor_cost, problem.get_heuristic(self.states.get(successor_state)));
+            let successor_node = self.arena.push(ArenaNode {
+                state: successor_state,
+                action: Some(action),
+                parent: Some(node),
+                cost: successor_cost,
+            });
+            self.p_queue.push(PTuple { priority, node: successor_node });
+        }
+        Step::Running
+    }
+
+    fn get_arena(&self) -> &Arena<P::Action> { &self.arena }
+    fn get_states(&self) -> &StateTable<P::State> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.p_queue.capacity() * size_of::<PTuple<NodeId>>(),
+            closed: self.states.get_memory_usage()
+                + self.costs.capacity() * size_of::<isize>()
+                + self.memory.get_state_bytes(),
+            nodes: self.arena.get_memory_usage(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<P::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::{bfs::BFS, dfbnb::DFBnB, dijkstra::Dijkstra};
+    use crate::utils::fixtures::Grid;
+    use crate::utils::path::Path;
+
+    fn solve<A: ProblemAlgorithm<Grid>>(algorithm: &mut A) -> Path<(usize, usize), char> {
+        let solution = algorithm.find_solution(&Grid).unwrap();
+        Path::from_arena(algorithm.get_arena(), algorithm.get_states(), solution)
+    }
+
+    #[test]
+    fn finds_the_cheapest_path_again() {
+        let expected = solve(&mut Dijkstra::new()).cost;
+        let mut a_star = AStar::new();
+        for _ in 0..2 {
+            let path = solve(&mut a_star);
+            assert_eq!(path.cost, expected);
+            assert_eq!(path.states.first(), Some(&(0, 0)));
+            assert_eq!(path.states.last(), Some(&(4, 4)));
+            assert_eq!(path.actions.len(), path.states.len() - 1);
+        }
+        let mut dfbnb = DFBnB::new();
+        assert_eq!(solve(&mut dfbnb).cost, expected);
+        assert_eq!(solve(&mut dfbnb).cost, expected);
+        assert_eq!(solve(&mut BFS::new()).actions.len(), 8);
+    }
+
+    #[test]
+    fn weighted_cost_is_bounded() {
+        let optimum = solve(&mut AStar::new()).cost;
+        let cost = solve(&mut AStar::new_weighted(3.0)).cost;
+        assert!(optimum <= cost && cost <= 3 * optimum);
+    }
+}
\ No newline at end of file
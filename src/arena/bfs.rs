@@ -0,0 +1,77 @@
+use crate::traits::problem::{Problem, ProblemAlgorithm, Step};
+use crate::utils::arena::{Arena, ArenaNode, NodeId};
+use crate::utils::memory::{MemoryBudget, MemoryUsage};
+use crate::utils::state_table::StateTable;
+use std::collections::VecDeque;
+use std::mem::size_of;
+
+pub struct BFS<P: Problem> {
+    queue: VecDeque<NodeId>,
+    arena: Arena<P::Action>,
+    states: StateTable<P::State>,
+    memory: MemoryBudget<P::State>,
+}
+
+impl<P: Problem> BFS<P> {
+    pub fn new() -> Self {
+        BFS {
+            queue: VecDeque::new(),
+            arena: Arena::new(),
+            states: StateTable::new(),
+            memory: MemoryBudget::new(),
+        }
+    }
+}
+
+impl<P: Problem> Default for BFS<P> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P: Problem> ProblemAlgorithm<P> for BFS<P> {
+    fn start(&mut self, problem: &P) {
+        self.queue.clear();
+        self.arena.clear();
+        self.states.clear();
+        self.memory.reset();
+        let root = problem.get_root();
+        let hash = problem.get_state_hash(&root);
+        self.memory.add_state(&root);
+        let (state, _) = self.states.intern_with(root, hash);
+        let root = self.arena.push(ArenaNode { state, action: None, parent: None, cost: 0 });
+        self.queue.push_front(root);
+    }
+
+    fn step(&mut self, problem: &P) -> Step {
+        let Some(node) = self.queue.pop_back() else { return Step::Exhausted };
+        let ArenaNode { state, cost, .. } = *self.arena.get(node);
+        if problem.is_solution(self.states.get(state)) { return Step::Solved(node) }
+        if self.memory.check(&self.get_memory_usage()) { return Step::Exhausted }
+        for (action, successor, step_cost) in problem.get_successors(self.states.get(state)) {
+            let hash = problem.get_state_hash(&successor);
+            let (successor_state, inserted) = self.states.intern_with(successor, hash);
+            if !inserted { continue; }
+            self.memory.add_state(self.states.get(successor_state));
+            self.queue.push_front(self.arena.push(ArenaNode {
+                state: successor_state,
+                action: Some(action),
+                parent: Some(node),
+                cost: cost + step_cost,
+            }));
+        }
+        Step::Running
+    }
+
+    fn get_arena(&self) -> &Arena<P::Action> { &self.arena }
+    fn get_states(&self) -> &StateTable<P::State> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.queue.capacity() * size_of::<NodeId>(),
+            closed: self.states.get_memory_usage() + self.memory.get_state_bytes(),
+            nodes: self.arena.get_memory_usage(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<P::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
\ No newline at end of file
@@ -0,0 +1,131 @@
+use crate::traits::problem::{Problem, ProblemAlgorithm, Step};
+use crate::utils::arena::{Arena, ArenaNode, NodeId};
+use crate::utils::memory::{MemoryBudget, MemoryUsage};
+use crate::utils::state_table::{StateId, StateTable};
+use crate::utils::validation::{check_successor, Validator, Violation};
+use std::mem::size_of;
+
+/*
+ *  You can use this algorithm ONLY if the cost function is monotonic
+ *
+ *  Anytime: every better solution is reported with `Step::Improved`,
+ *  the last one is returned as `Step::Solved` once the search space is exhausted
+ */
+
+pub struct DFBnB<P: Problem> {
+    stack: Vec<NodeId>,
+    arena: Arena<P::Action>,
+    states: StateTable<P::State>,
+    costs: Vec<isize>,
+    memory: MemoryBudget<P::State>,
+    best: Option<NodeId>,
+    validator: Validator<StateId, P::Action>,
+}
+
+impl<P: Problem> DFBnB<P> {
+    pub fn new() -> Self {
+        DFBnB {
+            stack: Vec::new(),
+            arena: Arena::new(),
+            states: StateTable::new(),
+            costs: Vec::new(),
+            memory: MemoryBudget::new(),
+            best: None,
+            validator: Validator::new(),
+        }
+    }
+
+    // Checks the heuristic and that costs are monotonic on every generated edge within the bound, see `utils::validation`
+    pub fn set_validation(&mut self, enabled: bool) -> &mut Self {
+        self.validator.set_enabled(enabled);
+        self
+    }
+
+    // States are ids in `get_states`
+    pub fn get_violations(&self) -> &[Violation<StateId, P::Action>] { self.validator.get_violations() }
+
+    fn is_bounded(&self, cost: isize) -> bool {
+        self.best.map(|best| cost >= self.arena.get(best).cost).unwrap_or(false)
+    }
+}
+
+impl<P: Problem> Default for DFBnB<P> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P: Problem> ProblemAlgorithm<P> for DFBnB<P> {
+    fn start(&mut self, problem: &P) {
+        self.stack.clear();
+        self.arena.clear();
+        self.states.clear();
+        self.costs.clear();
+        self.memory.reset();
+        self.validator.clear();
+        self.best = None;
+        let root = problem.get_root();
+        let hash = problem.get_state_hash(&root);
+        self.memory.add_state(&root);
+        let (state, _) = self.states.intern_with(root, hash);
+        if self.validator.is_enabled() {
+            let root = self.states.get(state);
+            self.validator.check_state(&state, problem.get_heuristic(root), problem.is_solution(root));
+        }
+        self.costs.push(0);
+        let node = self.arena.push(ArenaNode { state, action: None, parent: None, cost: 0 });
+        self.stack.push(node);
+    }
+
+    fn step(&mut self, problem: &P) -> Step {
+        let Some(node) = self.stack.pop() else {
+            return self.best.map(Step::Solved).unwrap_or(Step::Exhausted)
+        };
+        let ArenaNode { state, cost, .. } = *self.arena.get(node);
+        // Stale entry, a cheaper path to this state was found after pushing it
+        if cost > self.costs[state] || self.is_bounded(cost) { return Step::Running }
+        if problem.is_solution(self.states.get(state)) {
+            self.best = Some(node);
+            return Step::Improved(node)
+        }
+        if self.memory.check(&self.get_memory_usage()) { return Step::Exhausted }
+
+        let mut descendants = Vec::new();
+        for (action, successor, step_cost) in problem.get_successors(self.states.get(state)) {
+            let successor_cost = cost + step_cost;
+            if self.is_bounded(successor_cost) { continue; }
+            let hash = problem.get_state_hash(&successor);
+            let (successor_state, inserted) = self.states.intern_with(successor, hash);
+            check_successor(&mut self.validator, problem, &self.states, state, &action, step_cost, successor_state);
+            if inserted {
+                self.costs.push(isize::MAX);
+                self.memory.add_state(self.states.get(successor_state));
+            }
+            if self.costs[successor_state] <= successor_cost { continue; }
+            self.costs[successor_state] = successor_cost;
+            descendants.push(self.arena.push(ArenaNode {
+                state: successor_state,
+                action: Some(action),
+                parent: Some(node),
+                cost: successor_cost,
+            }));
+        }
+        // First descendant on top, same order as the recursive version
+        self.stack.extend(descendants.into_iter().rev());
+        Step::Running
+    }
+
+    fn get_arena(&self) -> &Arena<P::Action> { &self.arena }
+    fn get_states(&self) -> &StateTable<P::State> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.stack.capacity() * size_of::<NodeId>(),
+            closed: self.states.get_memory_usage()
+                + self.costs.capacity() * size_of::<isize>()
+                + self.memory.get_state_bytes(),
+            nodes: self.arena.get_memory_usage(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<P::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
\ No newline at end of file
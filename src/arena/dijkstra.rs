@@ -0,0 +1,94 @@
+use crate::traits::{
+    problem::{Problem, ProblemAlgorithm, Step},
+    p_tuple::PTuple
+};
+use crate::utils::arena::{Arena, ArenaNode, NodeId};
+use crate::utils::memory::{MemoryBudget, MemoryUsage};
+use crate::utils::state_table::StateTable;
+use std::collections::BinaryHeap;
+use std::mem::size_of;
+
+pub struct Dijkstra<P: Problem> {
+    p_queue: BinaryHeap<PTuple<NodeId>>,
+    arena: Arena<P::Action>,
+    states: StateTable<P::State>,
+    costs: Vec<isize>,
+    memory: MemoryBudget<P::State>,
+}
+
+impl<P: Problem> Dijkstra<P> {
+    pub fn new() -> Self {
+        Dijkstra {
+            p_queue: BinaryHeap::new(),
+            arena: Arena::new(),
+            states: StateTable::new(),
+            costs: Vec::new(),
+            memory: MemoryBudget::new(),
+        }
+    }
+}
+
+impl<P: Problem> Default for Dijkstra<P> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P: Problem> ProblemAlgorithm<P> for Dijkstra<P> {
+    fn start(&mut self, problem: &P) {
+        self.p_queue.clear();
+        self.arena.clear();
+        self.states.clear();
+        self.costs.clear();
+        self.memory.reset();
+        let root = problem.get_root();
+        let hash = problem.get_state_hash(&root);
+        self.memory.add_state(&root);
+        let (state, _) = self.states.intern_with(root, hash);
+        self.costs.push(0);
+        let node = self.arena.push(ArenaNode { state, action: None, parent: None, cost: 0 });
+        self.p_queue.push(PTuple { priority: 0, node });
+    }
+
+    fn step(&mut self, problem: &P) -> Step {
+        let Some(PTuple {priority: _, node}) = self.p_queue.pop() else { return Step::Exhausted };
+        let ArenaNode { state, cost, .. } = *self.arena.get(node);
+        // Stale entry, a cheaper path to this state was found after pushing it
+        if cost > self.costs[state] { return Step::Running }
+        if problem.is_solution(self.states.get(state)) { return Step::Solved(node) }
+        if self.memory.check(&self.get_memory_usage()) { return Step::Exhausted }
+        for (action, successor, step_cost) in problem.get_successors(self.states.get(state)) {
+            let successor_cost = cost + step_cost;
+            let hash = problem.get_state_hash(&successor);
+            let (successor_state, inserted) = self.states.intern_with(successor, hash);
+            if inserted {
+                self.costs.push(isize::MAX);
+                self.memory.add_state(self.states.get(successor_state));
+            }
+            if self.costs[successor_state] <= successor_cost { continue; }
+            self.costs[successor_state] = successor_cost;
+            let successor_node = self.arena.push(ArenaNode {
+                state: successor_state,
+                action: Some(action),
+                parent: Some(node),
+                cost: successor_cost,
+            });
+            self.p_queue.push(PTuple { priority: successor_cost, node: successor_node });
+        }
+        Step::Running
+    }
+
+    fn get_arena(&self) -> &Arena<P::Action> { &self.arena }
+    fn get_states(&self) -> &StateTable<P::State> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.p_queue.capacity() * size_of::<PTuple<NodeId>>(),
+            closed: self.states.get_memory_usage()
+                + self.costs.capacity() * size_of::<isize>()
+                + self.memory.get_state_bytes(),
+            nodes: self.arena.get_memory_usage(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<P::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
\ No newline at end of file
@@ -1,28 +1,50 @@
 use super::traits::node::Node;
-use super::traits::algorithm::Algorithm;
-use std::collections::{VecDeque, HashSet};
+use super::traits::algorithm::{Algorithm, AlgorithmExt};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::state_table::StateTable;
+use std::collections::VecDeque;
+use std::mem::size_of;
 use std::rc::Rc;
 
 pub struct BFS<T: Node> {
     queue: VecDeque<Box<T>>,
-    visited: HashSet<Rc<T::State>>,
+    states: StateTable<Rc<T::State>>,
+    expanded: usize,
+    memory: MemoryBudget<T::State>,
 }
 
 impl<T: Node> BFS<T> {
     pub fn new() -> Self {
-        BFS { queue: VecDeque::new(), visited: HashSet::new() }
+        BFS {
+            queue: VecDeque::new(),
+            states: StateTable::new(),
+            expanded: 0,
+            memory: MemoryBudget::new(),
+        }
     }
 }
 
+impl<T: Node> Default for BFS<T> {
+    fn default() -> Self { Self::new() }
+}
+
 impl<T: Node> Algorithm<T> for BFS<T> {
     fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
-        self.visited.insert(root.get_state());
+        self.memory.reset();
+        self.queue.clear();
+        self.states.clear();
+        self.expanded = 0;
+        self.states.intern_with(root.get_state(), root.get_state_hash());
+        self.memory.add_state(&root.get_state());
         self.queue.push_front(root);
         while let Some(node) = self.queue.pop_back() {
             if node.is_solution() { return Some(node) }
+            if self.memory.check(&self.get_memory_usage()) { return None }
+            self.expanded += 1;
             for descendant in node.get_descendants() {
-                if self.visited.contains(&descendant.get_state()) { continue; }
-                self.visited.insert(descendant.get_state().clone());
+                let (id, inserted) = self.states.intern_with(descendant.get_state(), descendant.get_state_hash());
+                if !inserted { continue; }
+                self.memory.add_state(self.states.get(id));
                 self.queue.push_front(descendant);
             }
         }
@@ -30,6 +52,23 @@ impl<T: Node> Algorithm<T> for BFS<T> {
     }
 
     fn get_visited(&self) -> Vec<&Rc<T::State>> {
-        self.visited.iter().collect()
+        self.states.iter().collect()
     }
+}
+
+impl<T: Node> AlgorithmExt<T> for BFS<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.queue.capacity() * size_of::<Box<T>>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.queue.len() + self.expanded) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
 }
\ No newline at end of file
@@ -0,0 +1,291 @@
+use super::traits::p_tuple::PTuple;
+use super::utils::graph::{Graph, VertexId};
+use super::utils::path::Path;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+// (other end, cost, contracted vertex the shortcut goes through)
+type Edge = (VertexId, isize, Option<VertexId>);
+// Distance and the previous vertex with the middle of the edge from it, per vertex reached
+type Tree = HashMap<VertexId, (isize, Option<(VertexId, Option<VertexId>)>)>;
+
+/*
+ *  Contraction hierarchy over a static graph: vertices are contracted from least to most important
+ *  (edge difference plus contracted neighbours, updated lazily), adding a shortcut between two
+ *  neighbours whenever no witness path avoiding the contracted vertex is as cheap. Queries are a
+ *  bidirectional Dijkstra only moving up the hierarchy, shortcuts are unpacked recursively.
+ */
+
+pub struct ContractionHierarchy {
+    rank: Vec<usize>,
+    // Edges to higher ranked vertices, and from higher ranked vertices
+    up: Vec<Vec<Edge>>,
+    down: Vec<Vec<Edge>>,
+    // Cheapest edge or shortcut between every pair, for unpacking
+    edges: HashMap<(VertexId, VertexId), (isize, Option<VertexId>)>,
+    witness_limit: usize,
+    shortcuts: usize,
+    settled: usize,
+}
+
+impl ContractionHierarchy {
+    pub fn new<V: Eq + Hash>(graph: &Graph<V>) -> Self {
+        Self::with_witness_limit(graph, 500)
+    }
+
+    // Witness searches give up after settling `witness_limit` vertices, adding the shortcut
+    pub fn with_witness_limit<V: Eq + Hash>(graph: &Graph<V>, witness_limit: usize) -> Self {
+        let n = graph.len();
+        let mut hierarchy = ContractionHierarchy {
+            rank: vec![usize::MAX; n],
+            up: vec![Vec::new(); n],
+            down: vec![Vec::new(); n],
+            edges: HashMap::new(),
+            witness_limit,
+            shortcuts: 0,
+            settled: 0,
+        };
+        let mut outgoing: Vec<HashMap<VertexId, isize>> = vec![HashMap::new(); n];
+        let mut incoming: Vec<HashMap<VertexId, isize>> = vec![HashMap::new(); n];
+        for from in 0..n {
+            for &(to, cost) in graph.get_edges(from) {
+                if from == to { continue; }
+                hierarchy.add_edge(&mut outgoing, &mut incoming, from, to, cost, None);
+            }
+        }
+
+        let mut contracted_neighbours = vec![0; n];
+        let mut p_queue: BinaryHeap<PTuple<VertexId>> = BinaryHeap::new();
+        for v in 0..n {
+            let priority = hierarchy.get_importance(&outgoing, &incoming, &contracted_neighbours, v);
+            p_queue.push(PTuple { priority, node: v });
+        }
+        let mut next_rank = 0;
+        while let Some(PTuple { priority, node }) = p_queue.pop() {
+            if hierarchy.rank[node] != usize::MAX { continue; }
+            // Lazy update, contract only if it is still the least important
+            let importance = hierarchy.get_importance(&outgoing, &incoming, &contracted_neighbours, node);
+            if importance > priority && p_queue.peek().map(|next| importance > next.priority).unwrap_or(false) {
+                p_queue.push(PTuple { priority: importance, node });
+                continue;
+            }
+
+            for (w, u, cost) in hierarchy.get_shortcuts(&outgoing, &incoming, node) {
+                hierarchy.shortcuts += 1;
+                hierarchy.add_edge(&mut outgoing, &mut incoming, u, w, cost, Some(node));
+            }
+            hierarchy.rank[node] = next_rank;
+            next_rank += 1;
+            for (w, cost) in outgoing[node].drain() {
+                incoming[w].remove(&node);
+                contracted_neighbours[w] += 1;
+                hierarchy.up[node].push((w, cost, hierarchy.edges[&(node, w)].1));
+            }
+            for (u, cost) in incoming[node].drain() {
+                outgoing[u].remove(&node);
+                contracted_neighbours[u] += 1;
+                hierarchy.down[node].push((u, cost, hierarchy.edges[&(u, node)].1));
+            }
+        }
+        hierarchy
+    }
+
+    fn add_edge(
+        &mut self,
+        outgoing: &mut [HashMap<VertexId, isize>],
+        incoming: &mut [HashMap<VertexId, isize>],
+        from: VertexId, to: VertexId, cost: isize, middle: Option<VertexId>,
+    ) {
+        if outgoing[from].get(&to).map(|current| *current <= cost).unwrap_or(false) { return }
+        outgoing[from].insert(to, cost);
+        incoming[to].insert(from, cost);
+        self.edges.insert((from, to), (cost, middle));
+    }
+
+    fn get_importance(
+        &self,
+        outgoing: &[HashMap<VertexId, isize>],
+        incoming: &[HashMap<VertexId, isize>],
+        contracted_neighbours: &[isize],
+        v: VertexId,
+    ) -> isize {
+        let shortcuts = self.get_shortcuts(outgoing, incoming, v).len() as isize;
+        shortcuts - (outgoing[v].len() + incoming[v].len()) as isize + contracted_neighbours[v]
+    }
+
+    // Shortcuts (to, from, cost) needed to contract `v`
+    fn get_shortcuts(
+        &self,
+        outgoing: &[HashMap<VertexId, isize>],
+        incoming: &[HashMap<VertexId, isize>],
+        v: VertexId,
+    ) -> Vec<(VertexId, VertexId, isize)> {
+        let mut shortcuts = Vec::new();
+        for (&u, &cost_in) in &incoming[v] {
+            let limit = outgoing[v].values().max().map(|cost_out| cost_in + cost_out).unwrap_or(0);
+            let witnesses = self.find_witnesses(outgoing, u, v, limit);
+            for (&w, &cost_out) in &outgoing[v] {
+                if w == u { continue; }
+                let through = cost_in + cost_out;
+                if witnesses.get(&w).map(|witness| *witness <= through).unwrap_or(false) { continue; }
+                shortcuts.push((w, u, through));
+            }
+        }
+        shortcuts
+    }
+
+    // Dijkstra from `source` over the remaining graph without `avoid`, up to a cost and number of settled vertices
+    fn find_witnesses(&self, outgoing: &[HashMap<VertexId, isize>], source: VertexId, avoid: VertexId, limit: isize) -> HashMap<VertexId, isize> {
+        let mut distances: HashMap<VertexId, isize> = HashMap::from([(source, 0)]);
+        let mut p_queue: BinaryHeap<PTuple<VertexId>> = BinaryHeap::from([PTuple { priority: 0, node: source }]);
+        let mut settled = 0;
+        while let Some(PTuple { priority, node }) = p_queue.pop() {
+            if distances[&node] < priority { continue; }
+            if priority > limit || settled == self.witness_limit { break; }
+            settled += 1;
+            for (&next, &cost) in &outgoing[node] {
+                if next == avoid { continue; }
+                let distance = priority + cost;
+                if distances.get(&next).map(|current| *current <= distance).unwrap_or(false) { continue; }
+                distances.insert(next, distance);
+                p_queue.push(PTuple { priority: distance, node: next });
+            }
+        }
+        distances
+    }
+
+    pub fn get_rank(&self, v: VertexId) -> usize { self.rank[v] }
+    pub fn get_shortcuts_added(&self) -> usize { self.shortcuts }
+    // Vertices settled by the last query
+    pub fn get_settled(&self) -> usize { self.settled }
+
+    pub fn get_distance(&mut self, start: VertexId, goal: VertexId) -> Option<isize> {
+        self.search(start, goal).map(|(cost, _, _, _)| cost)
+    }
+
+    // Actions are the vertices moved to
+    pub fn find_path(&mut self, start: VertexId, goal: VertexId) -> Option<Path<VertexId, VertexId>> {
+        let (cost, meeting, forward, backward) = self.search(start, goal)?;
+        let mut states = Vec::new();
+        let mut current = meeting;
+        while let Some((parent, middle)) = forward[&current].1 {
+            self.unpack(parent, current, middle, &mut states);
+            current = parent;
+        }
+        states.push(start);
+        states.reverse();
+        let mut current = meeting;
+        while let Some((child, middle)) = backward[&current].1 {
+            let mut segment = Vec::new();
+            self.unpack(current, child, middle, &mut segment);
+            states.extend(segment.into_iter().rev());
+            current = child;
+        }
+        let actions = states[1..].to_vec();
+        Some(Path { states, actions, cost })
+    }
+
+    // Vertices of the edge after `from` up to `to`, pushed in reverse order
+    fn unpack(&self, from: VertexId, to: VertexId, middle: Option<VertexId>, states: &mut Vec<VertexId>) {
+        match middle {
+            None => states.push(to),
+            Some(middle) => {
+                self.unpack(middle, to, self.edges[&(middle, to)].1, states);
+                self.unpack(from, middle, self.edges[&(from, middle)].1, states);
+            },
+        }
+    }
+
+    // Cost, meeting vertex and both search trees
+    fn search(&mut self, start: VertexId, goal: VertexId) -> Option<(isize, VertexId, Tree, Tree)> {
+        let mut trees: [Tree; 2] = [HashMap::from([(start, (0, None))]), HashMap::from([(goal, (0, None))])];
+        let mut p_queues: [BinaryHeap<PTuple<VertexId>>; 2] = [
+            BinaryHeap::from([PTuple { priority: 0, node: start }]),
+            BinaryHeap::from([PTuple { priority: 0, node: goal }]),
+        ];
+        let mut best: Option<(isize, VertexId)> = None;
+        self.settled = 0;
+        loop {
+            // Side with the cheapest vertex left, both stop once it is not cheaper than the best path
+            let side = match (p_queues[0].peek(), p_queues[1].peek()) {
+                (Some(a), Some(b)) => if a.priority <= b.priority { 0 } else { 1 },
+                (Some(_), None) => 0,
+                (None, Some(_)) => 1,
+                (None, None) => break,
+            };
+            let PTuple { priority, node } = p_queues[side].pop().unwrap();
+            if best.map(|(cost, _)| priority >= cost).unwrap_or(false) { break; }
+            if trees[side][&node].0 < priority { continue; }
+            self.settled += 1;
+            if let Some((other, _)) = trees[1 - side].get(&node) {
+                if best.map(|(cost, _)| priority + other < cost).unwrap_or(true) { best = Some((priority + other, node)); }
+            }
+            let edges = if side == 0 { &self.up[node] } else { &self.down[node] };
+            for &(next, cost, middle) in edges {
+                let distance = priority + cost;
+                if trees[side].get(&next).map(|(current, _)| *current <= distance).unwrap_or(false) { continue; }
+                trees[side].insert(next, (distance, Some((node, middle))));
+                p_queues[side].push(PTuple { priority: distance, node: next });
+            }
+        }
+        let (cost, meeting) = best?;
+        let [forward, backward] = trees;
+        Some((cost, meeting, forward, backward))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rng::Rng;
+
+    fn random_graph(rng: &mut Rng, vertices: usize, edges: usize) -> Graph<usize> {
+        let mut graph = Graph::new();
+        for v in 0..vertices { graph.add_vertex(v); }
+        for _ in 0..edges {
+            let (from, to) = (rng.next_below(vertices), rng.next_below(vertices));
+            if from != to { graph.add_edge(from, to, 1 + rng.next_below(9) as isize); }
+        }
+        graph
+    }
+
+    // Cheapest edge between two vertices, several may be added
+    fn get_edge_cost(graph: &Graph<usize>, from: VertexId, to: VertexId) -> Option<isize> {
+        graph.get_edges(from).iter().filter(|(next, _)| *next == to).map(|(_, cost)| *cost).min()
+    }
+
+    #[test]
+    fn matches_dijkstra_and_unpacks_shortcuts() {
+        let mut rng = Rng::new(3);
+        for _ in 0..10 {
+            let graph = random_graph(&mut rng, 25, 70);
+            let mut hierarchy = ContractionHierarchy::new(&graph);
+            assert!(hierarchy.get_shortcuts_added() > 0);
+            for start in 0..graph.len() {
+                let distances = graph.get_distances(start, false);
+                for (goal, distance) in distances.into_iter().enumerate() {
+                    assert_eq!(hierarchy.get_distance(start, goal), distance);
+                    let Some(path) = hierarchy.find_path(start, goal) else { continue };
+                    assert_eq!((path.states[0], path.states[path.states.len() - 1]), (start, goal));
+                    let cost: isize = path.states.windows(2).map(|step| get_edge_cost(&graph, step[0], step[1]).unwrap()).sum();
+                    assert_eq!(Some(cost), distance);
+                    assert_eq!(path.cost, cost);
+                    assert_eq!(path.actions, path.states[1..]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn witness_limit_keeps_distances_exact() {
+        let mut rng = Rng::new(5);
+        let graph = random_graph(&mut rng, 30, 90);
+        let mut exact = ContractionHierarchy::new(&graph);
+        let mut limited = ContractionHierarchy::with_witness_limit(&graph, 1);
+        for start in 0..graph.len() {
+            for goal in 0..graph.len() {
+                assert_eq!(limited.get_distance(start, goal), exact.get_distance(start, goal));
+            }
+        }
+    }
+}
\ No newline at end of file
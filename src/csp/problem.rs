@@ -0,0 +1,102 @@
+pub type VarId = usize;
+// Receives the values of the scope variables, in scope order
+pub type CheckFn<V> = Box<dyn Fn(&[&V]) -> bool>;
+
+pub struct Constraint<V> {
+    pub scope: Vec<VarId>,
+    pub check: CheckFn<V>,
+}
+
+/*
+ *  Variables with finite domains and constraints of any arity. Binary constraints are also
+ *  propagated by arc consistency, larger ones are checked once all but one of their variables
+ *  are assigned.
+ */
+
+pub struct Csp<V> {
+    domains: Vec<Vec<V>>,
+    constraints: Vec<Constraint<V>>,
+    // Constraints of every variable
+    involved: Vec<Vec<usize>>,
+}
+
+impl<V> Csp<V> {
+    pub fn new() -> Self {
+        Csp {
+            domains: Vec::new(),
+            constraints: Vec::new(),
+            involved: Vec::new(),
+        }
+    }
+
+    pub fn add_variable(&mut self, domain: Vec<V>) -> VarId {
+        self.domains.push(domain);
+        self.involved.push(Vec::new());
+        self.domains.len() - 1
+    }
+
+    pub fn add_constraint(&mut self, scope: Vec<VarId>, check: CheckFn<V>) {
+        for var in &scope {
+            if !self.involved[*var].contains(&self.constraints.len()) { self.involved[*var].push(self.constraints.len()); }
+        }
+        self.constraints.push(Constraint { scope, check });
+    }
+
+    // Removes the values of the domain that do not satisfy the predicate
+    pub fn add_unary(&mut self, var: VarId, check: impl Fn(&V) -> bool) {
+        self.domains[var].retain(check);
+    }
+
+    pub fn add_binary(&mut self, a: VarId, b: VarId, check: impl Fn(&V, &V) -> bool + 'static) {
+        self.add_constraint(vec![a, b], Box::new(move |values| check(values[0], values[1])));
+    }
+
+    // Pairwise different values, as binary constraints
+    pub fn add_all_different(&mut self, scope: &[VarId]) where V: PartialEq {
+        for (i, a) in scope.iter().enumerate() {
+            for b in &scope[i + 1..] {
+                self.add_binary(*a, *b, |x, y| x != y);
+            }
+        }
+    }
+
+    pub fn set_domain(&mut self, var: VarId, domain: Vec<V>) {
+        self.domains[var] = domain;
+    }
+
+    pub fn get_domain(&self, var: VarId) -> &[V] { &self.domains[var] }
+    pub fn get_constraint(&self, constraint: usize) -> &Constraint<V> { &self.constraints[constraint] }
+    // Indices of the constraints on the variable
+    pub fn get_involved(&self, var: VarId) -> &[usize] { &self.involved[var] }
+    pub fn len(&self) -> usize { self.domains.len() }
+    pub fn is_empty(&self) -> bool { self.domains.is_empty() }
+
+    // Every constraint whose variables are all assigned holds
+    pub fn is_consistent(&self, assignment: &[Option<V>]) -> bool {
+        self.constraints.iter().all(|constraint| self.holds(constraint, assignment) != Some(false))
+    }
+
+    // Values of `var` that keep the assignment consistent, to generate or prune successors
+    pub fn get_consistent_values(&self, assignment: &mut [Option<V>], var: VarId) -> Vec<V> where V: Clone {
+        let previous = assignment[var].take();
+        let values = self.domains[var].iter()
+            .filter(|value| {
+                assignment[var] = Some((*value).clone());
+                self.involved[var].iter().all(|c| self.holds(&self.constraints[*c], assignment) != Some(false))
+            })
+            .cloned()
+            .collect();
+        assignment[var] = previous;
+        values
+    }
+
+    // None if a variable of the scope is unassigned
+    fn holds(&self, constraint: &Constraint<V>, assignment: &[Option<V>]) -> Option<bool> {
+        let values = constraint.scope.iter().map(|var| assignment[*var].as_ref()).collect::<Option<Vec<_>>>()?;
+        Some((constraint.check)(&values))
+    }
+}
+
+impl<V> Default for Csp<V> {
+    fn default() -> Self { Self::new() }
+}
\ No newline at end of file
@@ -0,0 +1,519 @@
+use super::problem::{Csp, VarId};
+use std::cmp::Reverse;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VariableOrder {
+    // Lowest index first
+    Static,
+    // Fewest remaining values first
+    Mrv,
+    // MRV, ties broken by the most constraints on other unassigned variables
+    MrvDegree,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ValueOrder {
+    Static,
+    // Values ruling out the fewest values of other unassigned variables first
+    Lcv,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Inference {
+    // Constraints are checked once all their variables are assigned
+    None,
+    // Removes the values that violate a constraint whose other variables are assigned
+    ForwardChecking,
+    // Forward checking followed by AC-3 on the binary constraints between unassigned variables
+    Mac,
+}
+
+// Removes the values without support in the binary constraints, false if a domain becomes empty
+pub fn ac3<V: Clone>(csp: &mut Csp<V>) -> bool {
+    let mut solver = CspSolver::new();
+    let mut search = Search::new(csp, 0);
+    let arcs = (0..csp.len()).flat_map(|var| get_arcs(csp, &search, var, None)).collect();
+    let consistent = solver.revise_arcs(csp, &mut search, arcs);
+    for var in 0..csp.len() {
+        let domain = search.domains.values(var).map(|value| csp.get_domain(var)[value].clone()).collect();
+        csp.set_domain(var, domain);
+    }
+    consistent
+}
+
+// Arcs (other, var, constraint) of the binary constraints between `var` and unassigned variables
+fn get_arcs<V>(csp: &Csp<V>, search: &Search, var: VarId, skip: Option<VarId>) -> Vec<(VarId, VarId, usize)> {
+    csp.get_involved(var).iter()
+        .filter_map(|c| {
+            let scope = &csp.get_constraint(*c).scope;
+            if scope.len() != 2 { return None }
+            let other = if scope[0] == var { scope[1] } else { scope[0] };
+            let valid = other != var && Some(other) != skip && search.assignment[other].is_none();
+            if valid { Some((other, var, *c)) } else { None }
+        })
+        .collect()
+}
+
+// Live values of every variable, removals are undone from the trail
+struct Domains {
+    live: Vec<Vec<bool>>,
+    sizes: Vec<usize>,
+    trail: Vec<(VarId, usize)>,
+}
+
+impl Domains {
+    fn values(&self, var: VarId) -> impl Iterator<Item = usize> + '_ {
+        self.live[var].iter().enumerate().filter(|(_, live)| **live).map(|(value, _)| value)
+    }
+
+    fn remove(&mut self, var: VarId, value: usize) {
+        self.live[var][value] = false;
+        self.sizes[var] -= 1;
+        self.trail.push((var, value));
+    }
+}
+
+struct Search {
+    domains: Domains,
+    // Index of the value of every assigned variable
+    assignment: Vec<Option<usize>>,
+    // Assigned variables blamed for the values removed from every variable
+    pruned_by: Vec<Vec<bool>>,
+    blame_trail: Vec<(VarId, VarId)>,
+    solutions: Vec<Vec<usize>>,
+    limit: usize,
+}
+
+impl Search {
+    fn new<V>(csp: &Csp<V>, limit: usize) -> Self {
+        Search {
+            domains: Domains {
+                live: (0..csp.len()).map(|var| vec![true; csp.get_domain(var).len()]).collect(),
+                sizes: (0..csp.len()).map(|var| csp.get_domain(var).len()).collect(),
+                trail: Vec::new(),
+            },
+            assignment: vec![None; csp.len()],
+            pruned_by: vec![vec![false; csp.len()]; csp.len()],
+            blame_trail: Vec::new(),
+            solutions: Vec::new(),
+            limit,
+        }
+    }
+
+    fn blame(&mut self, var: VarId, other: VarId) {
+        if other == var || self.pruned_by[var][other] { return }
+        self.pruned_by[var][other] = true;
+        self.blame_trail.push((var, other));
+    }
+
+    fn get_assigned(&self) -> Vec<bool> {
+        self.assignment.iter().map(|value| value.is_some()).collect()
+    }
+
+    fn undo(&mut self, domains: usize, blames: usize) {
+        while self.domains.trail.len() > domains {
+            let (var, value) = self.domains.trail.pop().unwrap();
+            self.domains.live[var][value] = true;
+            self.domains.sizes[var] += 1;
+        }
+        while self.blame_trail.len() > blames {
+            let (var, other) = self.blame_trail.pop().unwrap();
+            self.pruned_by[var][other] = false;
+        }
+    }
+}
+
+enum Outcome {
+    Stop,
+    // Variables responsible for the failure
+    Conflict(Vec<bool>),
+}
+
+/*
+ *  Backtracking search with conflict-directed backjumping: every variable collects the assigned
+ *  variables that ruled out its values (by constraint checks or forward checking), and when it runs
+ *  out of values the search jumps back to the most recent of them instead of the previous variable.
+ *  Values removed by arc consistency are blamed on every assigned variable.
+ */
+
+pub struct CspSolver {
+    variable_order: VariableOrder,
+    value_order: ValueOrder,
+    inference: Inference,
+    backjumping: bool,
+    nodes: usize,
+    backtracks: usize,
+    checks: usize,
+}
+
+impl CspSolver {
+    pub fn new() -> Self {
+        CspSolver {
+            variable_order: VariableOrder::MrvDegree,
+            value_order: ValueOrder::Lcv,
+            inference: Inference::ForwardChecking,
+            backjumping: true,
+            nodes: 0,
+            backtracks: 0,
+            checks: 0,
+        }
+    }
+
+    pub fn set_variable_order(&mut self, order: VariableOrder) -> &mut Self {
+        self.variable_order = order;
+        self
+    }
+
+    pub fn set_value_order(&mut self, order: ValueOrder) -> &mut Self {
+        self.value_order = order;
+        self
+    }
+
+    pub fn set_inference(&mut self, inference: Inference) -> &mut Self {
+        self.inference = inference;
+        self
+    }
+
+    // Chronological backtracking when disabled
+    pub fn set_backjumping(&mut self, backjumping: bool) -> &mut Self {
+        self.backjumping = backjumping;
+        self
+    }
+
+    pub fn solve<V: Clone>(&mut self, csp: &Csp<V>) -> Option<Vec<V>> {
+        self.solve_all(csp, 1).pop()
+    }
+
+    // Completes the assignment, assigned values must belong to their domain
+    pub fn solve_partial<V: Clone + PartialEq>(&mut self, csp: &Csp<V>, assignment: &[Option<V>]) -> Option<Vec<V>> {
+        let mut search = Search::new(csp, 1);
+        for (var, value) in assignment.iter().enumerate() {
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+            let keep = csp.get_domain(var).iter().position(|other| other == value)?;
+            for other in 0..csp.get_domain(var).len() {
+                if other != keep { search.domains.remove(var, other); }
+            }
+        }
+        self.run(csp, search).pop()
+    }
+
+    // Up to `limit` solutions
+    pub fn solve_all<V: Clone>(&mut self, csp: &Csp<V>, limit: usize) -> Vec<Vec<V>> {
+        self.run(csp, Search::new(csp, limit))
+    }
+
+    // Assignments tried
+    pub fn get_nodes(&self) -> usize { self.nodes }
+    pub fn get_backtracks(&self) -> usize { self.backtracks }
+    // Constraint evaluations
+    pub fn get_checks(&self) -> usize { self.checks }
+
+    fn run<V: Clone>(&mut self, csp: &Csp<V>, mut search: Search) -> Vec<Vec<V>> {
+        if search.limit == 0 { return Vec::new() }
+        // Constraints on a single variable
+        for var in 0..csp.len() {
+            for c in csp.get_involved(var) {
+                if csp.get_constraint(*c).scope.iter().any(|other| *other != var) { continue; }
+                let values: Vec<usize> = search.domains.values(var).collect();
+                for value in values {
+                    if !self.check(csp, *c, |_| value) { search.domains.remove(var, value); }
+                }
+            }
+        }
+        if search.domains.sizes.contains(&0) { return Vec::new() }
+        if self.inference == Inference::Mac {
+            let arcs = (0..csp.len()).flat_map(|var| get_arcs(csp, &search, var, None)).collect();
+            if !self.revise_arcs(csp, &mut search, arcs) { return Vec::new() }
+        }
+
+        self.search(csp, &mut search);
+        search.solutions.iter()
+            .map(|values| values.iter().enumerate().map(|(var, value)| csp.get_domain(var)[*value].clone()).collect())
+            .collect()
+    }
+
+    fn search<V>(&mut self, csp: &Csp<V>, search: &mut Search) -> Outcome {
+        let var = match self.select_variable(csp, search) {
+            Some(var) => var,
+            None => {
+                search.solutions.push(search.assignment.iter().map(|value| value.unwrap()).collect());
+                if search.solutions.len() >= search.limit { return Outcome::Stop }
+                // The next solution may differ in any variable
+                return Outcome::Conflict(search.get_assigned())
+            }
+        };
+
+        let mut conflict = search.pruned_by[var].clone();
+        for value in self.order_values(csp, search, var) {
+            self.nodes += 1;
+            search.assignment[var] = Some(value);
+            let (domains, blames) = (search.domains.trail.len(), search.blame_trail.len());
+            let blame = match self.propagate(csp, search, var) {
+                Err(blame) => blame,
+                Ok(()) => match self.search(csp, search) {
+                    Outcome::Stop => return Outcome::Stop,
+                    Outcome::Conflict(blame) => blame,
+                },
+            };
+            search.undo(domains, blames);
+            if self.backjumping && !blame[var] {
+                search.assignment[var] = None;
+                return Outcome::Conflict(blame)
+            }
+            for (other, blamed) in blame.into_iter().enumerate() {
+                if blamed { conflict[other] = true; }
+            }
+        }
+        search.assignment[var] = None;
+        self.backtracks += 1;
+        conflict[var] = false;
+        Outcome::Conflict(conflict)
+    }
+
+    fn select_variable<V>(&mut self, csp: &Csp<V>, search: &Search) -> Option<VarId> {
+        let sizes = &search.domains.sizes;
+        let mut unassigned = (0..csp.len()).filter(|var| search.assignment[*var].is_none());
+        match self.variable_order {
+            VariableOrder::Static => unassigned.next(),
+            VariableOrder::Mrv => unassigned.min_by_key(|var| sizes[*var]),
+            VariableOrder::MrvDegree => unassigned.min_by_key(|var| (sizes[*var], Reverse(get_degree(csp, search, *var)))),
+        }
+    }
+
+    fn order_values<V>(&mut self, csp: &Csp<V>, search: &Search, var: VarId) -> Vec<usize> {
+        let values: Vec<usize> = search.domains.values(var).collect();
+        if self.value_order == ValueOrder::Static { return values }
+        let mut counted: Vec<(usize, usize)> = values.into_iter()
+            .map(|value| (self.count_ruled_out(csp, search, var, value), value))
+            .collect();
+        counted.sort_by_key(|(count, _)| *count);
+        counted.into_iter().map(|(_, value)| value).collect()
+    }
+
+    // Values of other variables removed by forward checking after assigning `value` to `var`
+    fn count_ruled_out<V>(&mut self, csp: &Csp<V>, search: &Search, var: VarId, value: usize) -> usize {
+        let mut count = 0;
+        for c in csp.get_involved(var) {
+            let future = match get_future(csp, search, *c, var) {
+                Some(future) => future,
+                None => continue,
+            };
+            for other in search.domains.values(future) {
+                let value_of = |x: VarId| if x == var { value } else if x == future { other } else { search.assignment[x].unwrap() };
+                if !self.check(csp, *c, value_of) { count += 1; }
+            }
+        }
+        count
+    }
+
+    // Checks or forward checks the constraints of the variable just assigned, Err holds the blamed variables
+    fn propagate<V>(&mut self, csp: &Csp<V>, search: &mut Search, var: VarId) -> Result<(), Vec<bool>> {
+        let mut changed = Vec::new();
+        for c in csp.get_involved(var) {
+            let scope = &csp.get_constraint(*c).scope;
+            let unassigned = scope.iter().any(|other| search.assignment[*other].is_none());
+            if !unassigned && self.inference == Inference::None {
+                let assignment = &search.assignment;
+                if !self.check(csp, *c, |x| assignment[x].unwrap()) {
+                    let mut blame = vec![false; csp.len()];
+                    for other in scope { blame[*other] = true; }
+                    return Err(blame)
+                }
+            }
+            if self.inference == Inference::None { continue; }
+
+            let future = match get_future(csp, search, *c, var) {
+                Some(future) => future,
+                None => continue,
+            };
+            let values: Vec<usize> = search.domains.values(future).collect();
+            let mut removed = false;
+            for value in values {
+                let assignment = &search.assignment;
+                if !self.check(csp, *c, |x| if x == future { value } else { assignment[x].unwrap() }) {
+                    search.domains.remove(future, value);
+                    removed = true;
+                }
+            }
+            if !removed { continue; }
+            for other in scope { search.blame(future, *other); }
+            if search.domains.sizes[future] == 0 { return Err(search.pruned_by[future].clone()) }
+            changed.push(future);
+        }
+
+        if self.inference == Inference::Mac {
+            let arcs = changed.into_iter().flat_map(|changed| get_arcs(csp, search, changed, None)).collect();
+            if !self.revise_arcs(csp, search, arcs) { return Err(search.get_assigned()) }
+        }
+        Ok(())
+    }
+
+    // AC-3, removals are blamed on every assigned variable
+    fn revise_arcs<V>(&mut self, csp: &Csp<V>, search: &mut Search, mut queue: Vec<(VarId, VarId, usize)>) -> bool {
+        while let Some((var, other, c)) = queue.pop() {
+            let mut removed = false;
+            let values: Vec<usize> = search.domains.values(var).collect();
+            for value in values {
+                let supported = search.domains.values(other).any(|support| {
+                    let value_of = |x: VarId| if x == var { value } else { support };
+                    self.check(csp, c, value_of)
+                });
+                if !supported {
+                    search.domains.remove(var, value);
+                    removed = true;
+                }
+            }
+            if !removed { continue; }
+            if search.domains.sizes[var] == 0 { return false }
+            for assigned in 0..csp.len() {
+                if search.assignment[assigned].is_some() { search.blame(var, assigned); }
+            }
+            queue.extend(get_arcs(csp, search, var, Some(other)));
+        }
+        true
+    }
+
+    fn check<V>(&mut self, csp: &Csp<V>, constraint: usize, value_of: impl Fn(VarId) -> usize) -> bool {
+        self.checks += 1;
+        let constraint = csp.get_constraint(constraint);
+        let values: Vec<&V> = constraint.scope.iter().map(|var| &csp.get_domain(*var)[value_of(*var)]).collect();
+        (constraint.check)(&values)
+    }
+}
+
+impl Default for CspSolver {
+    fn default() -> Self { Self::new() }
+}
+
+// Constraints on `var` and other unassigned variables
+fn get_degree<V>(csp: &Csp<V>, search: &Search, var: VarId) -> usize {
+    csp.get_involved(var).iter()
+        .filter(|c| csp.get_constraint(**c).scope.iter().any(|other| *other != var && search.assignment[*other].is_none()))
+        .count()
+}
+
+// The only unassigned variable of the constraint other than `var`, it may appear more than once in the scope
+fn get_future<V>(csp: &Csp<V>, search: &Search, constraint: usize, var: VarId) -> Option<VarId> {
+    let mut unassigned = csp.get_constraint(constraint).scope.iter()
+        .filter(|other| **other != var && search.assignment[**other].is_none());
+    let future = *unassigned.next()?;
+    unassigned.all(|other| *other == future).then_some(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rng::Rng;
+
+    fn get_solvers() -> Vec<CspSolver> {
+        let mut solvers = Vec::new();
+        for variable_order in [VariableOrder::Static, VariableOrder::Mrv, VariableOrder::MrvDegree] {
+            for value_order in [ValueOrder::Static, ValueOrder::Lcv] {
+                for inference in [Inference::None, Inference::ForwardChecking, Inference::Mac] {
+                    for backjumping in [false, true] {
+                        let mut solver = CspSolver::new();
+                        solver.set_variable_order(variable_order)
+                            .set_value_order(value_order)
+                            .set_inference(inference)
+                            .set_backjumping(backjumping);
+                        solvers.push(solver);
+                    }
+                }
+            }
+        }
+        solvers
+    }
+
+    fn queens(n: usize) -> Csp<usize> {
+        let mut csp = Csp::new();
+        for _ in 0..n { csp.add_variable((0..n).collect()); }
+        for a in 0..n {
+            for b in a + 1..n {
+                csp.add_binary(a, b, move |x, y| x != y && x.abs_diff(*y) != b - a);
+            }
+        }
+        csp
+    }
+
+    // Every assignment, counted by brute force
+    fn count_solutions(csp: &Csp<usize>, assignment: &mut Vec<Option<usize>>) -> usize {
+        if !csp.is_consistent(assignment) { return 0 }
+        let var = match assignment.iter().position(|value| value.is_none()) {
+            Some(var) => var,
+            None => return 1,
+        };
+        let mut count = 0;
+        for value in csp.get_domain(var).to_vec() {
+            assignment[var] = Some(value);
+            count += count_solutions(csp, assignment);
+        }
+        assignment[var] = None;
+        count
+    }
+
+    #[test]
+    fn counts_queens_solutions() {
+        for solver in &mut get_solvers() {
+            assert_eq!(solver.solve_all(&queens(6), usize::MAX).len(), 4);
+            let solution = solver.solve(&queens(8)).unwrap();
+            assert!(queens(8).is_consistent(&solution.into_iter().map(Some).collect::<Vec<_>>()));
+            assert!(solver.solve(&queens(3)).is_none());
+        }
+    }
+
+    // Backjumping must never skip over a solution
+    #[test]
+    fn matches_brute_force_on_random_problems() {
+        let mut rng = Rng::new(7);
+        for _ in 0..40 {
+            let mut csp = Csp::new();
+            let variables = 3 + rng.next_below(4);
+            for _ in 0..variables { csp.add_variable((0..1 + rng.next_below(3)).collect()); }
+            for _ in 0..variables + rng.next_below(6) {
+                let (a, b) = (rng.next_below(variables), rng.next_below(variables));
+                if a == b { continue; }
+                let forbidden: Vec<(usize, usize)> = (0..3).map(|_| (rng.next_below(3), rng.next_below(3))).collect();
+                csp.add_binary(a, b, move |x, y| !forbidden.contains(&(*x, *y)));
+            }
+            let (a, b, c) = (rng.next_below(variables), rng.next_below(variables), rng.next_below(variables));
+            csp.add_constraint(vec![a, b, c], Box::new(|values| (values[0] + values[1] + values[2]) % 3 != 1));
+
+            let expected = count_solutions(&csp, &mut vec![None; variables]);
+            for solver in &mut get_solvers() {
+                let solutions = solver.solve_all(&csp, usize::MAX);
+                assert_eq!(solutions.len(), expected);
+                for solution in solutions {
+                    assert!(csp.is_consistent(&solution.into_iter().map(Some).collect::<Vec<_>>()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn completes_partial_assignments() {
+        let csp = queens(6);
+        let mut assignment = vec![None; 6];
+        assignment[0] = Some(1);
+        let solution = CspSolver::new().solve_partial(&csp, &assignment).unwrap();
+        assert_eq!(solution[0], 1);
+        assignment[0] = Some(0);
+        assert!(CspSolver::new().solve_partial(&csp, &assignment).is_none());
+    }
+
+    #[test]
+    fn ac3_removes_unsupported_values() {
+        let mut csp = Csp::new();
+        let a = csp.add_variable(vec![1, 2, 3]);
+        let b = csp.add_variable(vec![1, 2, 3]);
+        let c = csp.add_variable(vec![1, 2, 3]);
+        csp.add_binary(a, b, |x, y| x < y);
+        csp.add_binary(b, c, |x, y| x < y);
+        assert!(ac3(&mut csp));
+        assert_eq!((csp.get_domain(a), csp.get_domain(b), csp.get_domain(c)), (&[1][..], &[2][..], &[3][..]));
+        csp.add_binary(c, a, |x, y| x < y);
+        assert!(!ac3(&mut csp));
+    }
+}
\ No newline at end of file
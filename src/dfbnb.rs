@@ -1,23 +1,54 @@
 use super::traits::node::Node;
-use super::traits::algorithm::Algorithm;
+use super::traits::algorithm::{Algorithm, AlgorithmExt};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::state_table::StateTable;
+use super::utils::validation::{check_descendant, get_parent, NodeValidator, NodeViolation};
+use std::mem::size_of;
 use std::rc::Rc;
-use std::collections::{HashMap};
 
 /*
  *  You can use this algorithm ONLY if the cost function is monotonic
  */
 
 pub struct DFBnB<T: Node> {
-    visited: HashMap<Rc<T::State>, isize>,
+    states: StateTable<Rc<T::State>>,
+    costs: Vec<isize>,
+    // Nodes on the current path and generated descendants not yet explored
+    depth: usize,
+    pending: usize,
+    memory: MemoryBudget<T::State>,
+    validator: NodeValidator<T>,
 }
 
 impl<T: Node> DFBnB<T> {
     pub fn new() -> Self {
-        DFBnB { visited: HashMap::new() }
+        DFBnB {
+            states: StateTable::new(),
+            costs: Vec::new(),
+            depth: 0,
+            pending: 0,
+            memory: MemoryBudget::new(),
+            validator: NodeValidator::<T>::new(),
+        }
+    }
+
+    // Checks the heuristic and that costs are monotonic on every generated edge, see `utils::validation`
+    pub fn set_validation(&mut self, enabled: bool) -> &mut Self {
+        self.validator.set_enabled(enabled);
+        self
     }
 
-    fn find_solution_recurse(&mut self, node: Box<T>, mut best: Option<Box<T>>) -> Option<Box<T>> {
-        for descendant in node.get_descendants() {
+    pub fn get_violations(&self) -> &[NodeViolation<T>] { self.validator.get_violations() }
+
+    fn find_solution_recurse(&mut self, node: T, mut best: Option<Box<T>>) -> Option<Box<T>> {
+        if self.memory.check(&self.get_memory_usage()) { return best }
+        let parent = get_parent(&self.validator, &node);
+        let mut descendants = node.get_descendants().into_iter();
+        self.pending += descendants.len();
+        self.depth += 1;
+        for descendant in descendants.by_ref() {
+            self.pending -= 1;
+            check_descendant(&mut self.validator, &parent, &*descendant);
             if descendant.is_solution() {
                 if let Some(best) = &best {
                     if descendant.get_cost() >= best.get_cost() { continue; }
@@ -30,25 +61,64 @@ impl<T: Node> DFBnB<T> {
                 if descendant.get_cost() >= best.get_cost() { continue; }
             }
 
-            if let Some(visited_cost) = self.visited.get(&descendant.get_state()) {
-                if visited_cost < &descendant.get_cost() { continue; }
+            let (id, inserted) = self.states.intern_with(descendant.get_state(), descendant.get_state_hash());
+            if inserted {
+                self.costs.push(isize::MAX);
+                self.memory.add_state(self.states.get(id));
             }
+            if self.costs[id] < descendant.get_cost() { continue; }
 
-            self.visited.insert(descendant.get_state(), descendant.get_cost());
-            best = self.find_solution_recurse(descendant, best);
+            self.costs[id] = descendant.get_cost();
+            best = self.find_solution_recurse(*descendant, best);
+            if self.memory.is_reached() { break; }
         }
+        self.pending -= descendants.len();
+        self.depth -= 1;
         best
     }
 }
 
+impl<T: Node> Default for DFBnB<T> {
+    fn default() -> Self { Self::new() }
+}
+
 impl<T: Node> Algorithm<T> for DFBnB<T> {
     fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        self.memory.reset();
+        self.states.clear();
+        self.costs.clear();
+        self.depth = 0;
+        self.pending = 0;
+        self.validator.clear();
+        if self.validator.is_enabled() {
+            self.validator.check_state(&root.get_state(), root.get_heuristic(), root.is_solution());
+        }
         if root.is_solution() { return Some(root); }
-        self.visited.insert(root.get_state(), root.get_cost());
-        self.find_solution_recurse(root, None)
+        self.states.intern_with(root.get_state(), root.get_state_hash());
+        self.costs.push(root.get_cost());
+        self.memory.add_state(&root.get_state());
+        self.find_solution_recurse(*root, None)
     }
 
     fn get_visited(&self) -> Vec<&Rc<T::State>> {
-        self.visited.keys().collect()
+        self.states.iter().collect()
     }
+}
+
+impl<T: Node> AlgorithmExt<T> for DFBnB<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.pending * size_of::<Box<T>>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.costs.capacity() * size_of::<isize>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.pending + self.depth) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
 }
\ No newline at end of file
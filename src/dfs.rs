@@ -1,37 +1,77 @@
 use super::traits::node::Node;
-use super::traits::algorithm::Algorithm;
-use std::collections::{HashSet};
+use super::traits::algorithm::{Algorithm, AlgorithmExt};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::state_table::StateTable;
+use std::mem::size_of;
 use std::rc::Rc;
 
 pub struct DFS<T: Node> {
-    visited: HashSet<Rc<T::State>>,
+    states: StateTable<Rc<T::State>>,
+    // Nodes on the current path and generated descendants not yet explored
+    depth: usize,
+    pending: usize,
+    memory: MemoryBudget<T::State>,
 }
 
 impl<T: Node> DFS<T> {
     pub fn new() -> Self {
-        DFS { visited: HashSet::new() }
+        DFS { states: StateTable::new(), depth: 0, pending: 0, memory: MemoryBudget::new() }
     }
 
     fn find_solution_recurse(&mut self, node: Box<T>) -> Option<Box<T>> {
         if node.is_solution() { return Some(node) }
-        for descendant in node.get_descendants() {
-            if self.visited.contains(&descendant.get_state()) { continue; }
-            self.visited.insert(descendant.get_state().clone());
-            if let Some(solution) = self.find_solution_recurse(descendant) {
-                return Some(solution)
-            }
+        if self.memory.check(&self.get_memory_usage()) { return None }
+        let mut descendants = node.get_descendants().into_iter();
+        let mut solution = None;
+        self.pending += descendants.len();
+        self.depth += 1;
+        for descendant in descendants.by_ref() {
+            self.pending -= 1;
+            let (id, inserted) = self.states.intern_with(descendant.get_state(), descendant.get_state_hash());
+            if !inserted { continue; }
+            self.memory.add_state(self.states.get(id));
+            solution = self.find_solution_recurse(descendant);
+            if solution.is_some() || self.memory.is_reached() { break; }
         }
-        None
+        self.pending -= descendants.len();
+        self.depth -= 1;
+        solution
     }
 }
 
+impl<T: Node> Default for DFS<T> {
+    fn default() -> Self { Self::new() }
+}
+
 impl<T: Node> Algorithm<T> for DFS<T> {
     fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
-        self.visited.insert(root.get_state());
+        self.memory.reset();
+        self.states.clear();
+        self.depth = 0;
+        self.pending = 0;
+        self.states.intern_with(root.get_state(), root.get_state_hash());
+        self.memory.add_state(&root.get_state());
         self.find_solution_recurse(root)
     }
 
     fn get_visited(&self) -> Vec<&Rc<T::State>> {
-        self.visited.iter().collect()
+        self.states.iter().collect()
+    }
+}
+
+impl<T: Node> AlgorithmExt<T> for DFS<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.pending * size_of::<Box<T>>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.pending + self.depth) * rc_size::<T>(),
+        }
     }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
 }
\ No newline at end of file
@@ -1,45 +1,91 @@
 use super::traits::{
     node::Node,
-    algorithm::Algorithm,
+    algorithm::{Algorithm, AlgorithmExt},
     p_tuple::PTuple
 };
-use std::collections::{BinaryHeap, HashMap};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::state_table::StateTable;
+use std::collections::BinaryHeap;
+use std::mem::size_of;
 use std::rc::Rc;
 
 pub struct Dijkstra<T: Node> {
     p_queue: BinaryHeap<PTuple<Box<T>>>,
-    visited: HashMap<Rc<T::State>, isize>,
+    states: StateTable<Rc<T::State>>,
+    costs: Vec<isize>,
+    expanded: usize,
+    memory: MemoryBudget<T::State>,
 }
 
 impl<T: Node> Dijkstra<T> {
     pub fn new() -> Self {
         Dijkstra {
             p_queue: BinaryHeap::new(),
-            visited: HashMap::new(),
+            states: StateTable::new(),
+            costs: Vec::new(),
+            expanded: 0,
+            memory: MemoryBudget::new(),
         }
     }
 
-    fn cost_fn(n: &Box<T>) -> isize { n.get_cost() }
+    fn cost_fn(n: &T) -> isize { n.get_cost() }
+}
+
+impl<T: Node> Default for Dijkstra<T> {
+    fn default() -> Self { Self::new() }
 }
 
 impl<T: Node> Algorithm<T> for Dijkstra<T> {
     fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
-        self.visited.insert(root.get_state(), root.get_cost());
-        self.p_queue.push(PTuple { priority: Dijkstra::cost_fn(&root), node: root });
+        self.memory.reset();
+        self.p_queue.clear();
+        self.states.clear();
+        self.costs.clear();
+        self.expanded = 0;
+        let (_, inserted) = self.states.intern_with(root.get_state(), root.get_state_hash());
+        if inserted {
+            self.memory.add_state(&root.get_state());
+            self.costs.push(root.get_cost());
+        }
+        self.p_queue.push(PTuple { priority: Self::cost_fn(&root), node: root });
 
         while let Some(PTuple {priority: _, node}) = self.p_queue.pop() {
             if node.is_solution() { return Some(node) }
+            if self.memory.check(&self.get_memory_usage()) { return None }
+            self.expanded += 1;
             for descendant in node.get_descendants() {
-                let visited_cost = *self.visited.get(&descendant.get_state()).unwrap_or(&isize::MAX);
-                if visited_cost <= Dijkstra::cost_fn(&descendant) { continue; }
-                self.visited.insert(descendant.get_state().clone(), descendant.get_cost());
-                self.p_queue.push(PTuple { priority: Dijkstra::cost_fn(&descendant), node: descendant });
+                let (id, inserted) = self.states.intern_with(descendant.get_state(), descendant.get_state_hash());
+                if inserted {
+                    self.costs.push(isize::MAX);
+                    self.memory.add_state(self.states.get(id));
+                }
+                if self.costs[id] <= Self::cost_fn(&descendant) { continue; }
+                self.costs[id] = descendant.get_cost();
+                self.p_queue.push(PTuple { priority: Self::cost_fn(&descendant), node: descendant });
             }
         }
         None
     }
 
     fn get_visited(&self) -> Vec<&Rc<T::State>> {
-        self.visited.keys().collect()
+        self.states.iter().collect()
     }
+}
+
+impl<T: Node> AlgorithmExt<T> for Dijkstra<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.p_queue.capacity() * size_of::<PTuple<Box<T>>>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.costs.capacity() * size_of::<isize>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.p_queue.len() + self.expanded) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
 }
\ No newline at end of file
@@ -0,0 +1,249 @@
+use crate::traits::game::GameState;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Bound { Exact, Lower, Upper }
+
+struct Entry<M> {
+    // Second hash of the state, entries of other states sharing the same key are ignored
+    lock: u64,
+    depth: usize,
+    value: isize,
+    bound: Bound,
+    best: Option<M>,
+}
+
+/*
+ *  Alpha-beta with a transposition table keyed by state hash and checked against a second hash of
+ *  the full state, so colliding states never share values. Moves are ordered with the table's
+ *  best move first, then by the evaluation of the resulting state.
+ *
+ *  With a time limit it deepens iteratively up to `depth` and returns the move of the
+ *  last iteration that completed in time, the first one always completes.
+ */
+
+pub struct AlphaBeta<G: GameState> {
+    depth: usize,
+    time_limit: Option<Duration>,
+    deadline: Option<Instant>,
+    table: HashMap<u64, Entry<G::Move>>,
+    nodes: usize,
+    completed_depth: usize,
+}
+
+impl<G: GameState> AlphaBeta<G> {
+    // Depth in plies, every agent move counts as one
+    pub fn new(depth: usize) -> Self {
+        AlphaBeta {
+            depth,
+            time_limit: None,
+            deadline: None,
+            table: HashMap::new(),
+            nodes: 0,
+            completed_depth: 0,
+        }
+    }
+
+    pub fn set_time_limit(&mut self, time_limit: Duration) -> &mut Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn find_move(&mut self, state: &G) -> Option<(G::Move, isize)> {
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        // The first iteration ignores the deadline, a state with legal moves always gets one
+        self.deadline = None;
+        self.completed_depth = 0;
+        let first_depth = if self.time_limit.is_some() { self.depth.min(1) } else { self.depth };
+        let mut best = None;
+        for depth in first_depth..=self.depth {
+            let result = self.search_root(state, depth);
+            self.deadline = deadline;
+            match result {
+                Some(result) => {
+                    best = result;
+                    self.completed_depth = depth;
+                }
+                None => break,
+            }
+        }
+        best
+    }
+
+    pub fn get_nodes(&self) -> usize { self.nodes }
+    // Deepest iteration finished before the time limit
+    pub fn get_completed_depth(&self) -> usize { self.completed_depth }
+    pub fn clear_table(&mut self) { self.table.clear(); }
+
+    fn is_timed_out(&self) -> bool {
+        self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false)
+    }
+
+    // None when the time ran out
+    fn search_root(&mut self, state: &G, depth: usize) -> Option<Option<(G::Move, isize)>> {
+        let maximizing = state.get_player() == 0;
+        let (mut alpha, mut beta) = (isize::MIN, isize::MAX);
+        let mut best: Option<(G::Move, isize)> = None;
+        for (action, child) in self.order_moves(state) {
+            let value = self.search(&child, depth.saturating_sub(1), alpha, beta)?;
+            let better = best.as_ref()
+                .map(|(_, best)| if maximizing { value > *best } else { value < *best })
+                .unwrap_or(true);
+            if better { best = Some((action, value)); }
+            if maximizing { alpha = alpha.max(value) } else { beta = beta.min(value) }
+        }
+        if let Some((action, value)) = &best {
+            self.table.insert(state.get_state_hash(), Entry {
+                lock: Self::get_lock(state),
+                depth,
+                value: *value,
+                bound: Bound::Exact,
+                best: Some(action.clone()),
+            });
+        }
+        Some(best)
+    }
+
+    fn search(&mut self, state: &G, depth: usize, mut alpha: isize, mut beta: isize) -> Option<isize> {
+        if self.is_timed_out() { return None }
+        self.nodes += 1;
+        if depth == 0 || state.is_terminal() { return Some(state.evaluate()) }
+
+        let hash = state.get_state_hash();
+        if let Some(entry) = self.probe(state, hash) {
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return Some(entry.value),
+                    Bound::Lower => alpha = alpha.max(entry.value),
+                    Bound::Upper => beta = beta.min(entry.value),
+                }
+                if alpha >= beta { return Some(entry.value) }
+            }
+        }
+
+        let children = self.order_moves(state);
+        if children.is_empty() { return Some(state.evaluate()) }
+
+        let maximizing = state.get_player() == 0;
+        let (alpha_start, beta_start) = (alpha, beta);
+        let mut best_value = if maximizing { isize::MIN } else { isize::MAX };
+        let mut best_move = None;
+        for (action, child) in children {
+            let value = self.search(&child, depth - 1, alpha, beta)?;
+            if maximizing {
+                if value > best_value || best_move.is_none() { best_value = value; best_move = Some(action); }
+                alpha = alpha.max(value);
+            } else {
+                if value < best_value || best_move.is_none() { best_value = value; best_move = Some(action); }
+                beta = beta.min(value);
+            }
+            if alpha >= beta { break; }
+        }
+
+        let bound = if best_value <= alpha_start {
+            Bound::Upper
+        } else if best_value >= beta_start {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(hash, Entry { lock: Self::get_lock(state), depth, value: best_value, bound, best: best_move });
+        Some(best_value)
+    }
+
+    fn probe(&self, state: &G, hash: u64) -> Option<&Entry<G::Move>> {
+        self.table.get(&hash).filter(|entry| entry.lock == Self::get_lock(state))
+    }
+
+    // Independent of `get_state_hash` even when it is not overridden
+    fn get_lock(state: &G) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(0x9e37_79b9_7f4a_7c15);
+        state.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn order_moves(&self, state: &G) -> Vec<(G::Move, G)> {
+        let table_move = self.probe(state, state.get_state_hash()).and_then(|entry| entry.best.clone());
+        let mut children: Vec<(G::Move, G, isize)> = state.get_moves().into_iter()
+            .map(|action| {
+                let child = state.apply(&action);
+                let value = child.evaluate();
+                (action, child, value)
+            })
+            .collect();
+        if state.get_player() == 0 {
+            children.sort_by_key(|(_, _, value)| std::cmp::Reverse(*value));
+        } else {
+            children.sort_by_key(|(_, _, value)| *value);
+        }
+        if let Some(i) = children.iter().position(|(action, _, _)| Some(action) == table_move.as_ref()) {
+            children[..=i].rotate_right(1);
+        }
+        children.into_iter().map(|(action, child, _)| (action, child)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::minimax::Minimax;
+    use crate::utils::fixtures::Stones;
+
+    #[test]
+    fn matches_minimax() {
+        for stones in 1..12 {
+            for player in 0..2 {
+                let state = Stones { stones, score: 0, player };
+                for depth in 1..7 {
+                    let expected = Minimax::new(depth).find_move(&state).map(|(_, value)| value);
+                    let value = AlphaBeta::new(depth).find_move(&state).map(|(_, value)| value);
+                    assert_eq!(value, expected, "{} stones, player {}, depth {}", stones, player, depth);
+                    // Iterative deepening, the table is filled by the shallower iterations
+                    let mut deepening = AlphaBeta::new(depth);
+                    deepening.set_time_limit(Duration::from_secs(60));
+                    assert_eq!(deepening.find_move(&state).map(|(_, value)| value), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn returns_a_move_without_time() {
+        let state = Stones { stones: 10, score: 0, player: 0 };
+        let mut search = AlphaBeta::new(6);
+        search.set_time_limit(Duration::ZERO);
+        let (action, _) = search.find_move(&state).unwrap();
+        assert!(state.get_moves().contains(&action));
+        assert!(search.get_completed_depth() >= 1);
+    }
+
+    // Every state has the same table key
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    struct Colliding(Stones);
+
+    impl GameState for Colliding {
+        type Move = usize;
+
+        fn get_player(&self) -> usize { self.0.get_player() }
+        fn get_players(&self) -> usize { 2 }
+        fn get_moves(&self) -> Vec<usize> { self.0.get_moves() }
+        fn apply(&self, take: &usize) -> Self { Colliding(self.0.apply(take)) }
+        fn is_terminal(&self) -> bool { self.0.is_terminal() }
+        fn evaluate(&self) -> isize { self.0.evaluate() }
+        fn get_state_hash(&self) -> u64 { 0 }
+    }
+
+    #[test]
+    fn ignores_entries_of_colliding_states() {
+        let mut search = AlphaBeta::new(5);
+        for stones in 1..12 {
+            let state = Colliding(Stones { stones, score: 0, player: 0 });
+            let expected = Minimax::new(5).find_move(&state.0).map(|(_, value)| value);
+            assert_eq!(search.find_move(&state).map(|(_, value)| value), expected, "{} stones", stones);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,248 @@
+use crate::traits::game::GameState;
+
+pub type EvaluationFn<G> = Box<dyn Fn(&G) -> isize>;
+
+/*
+ *  Chance nodes average their outcomes weighted by probability, player 0 maximizes and every
+ *  other player minimizes as in minimax.
+ *
+ *  When every evaluation is known to lie within bounds (`set_bounds`), chance nodes are cut off
+ *  with Star1 and probe the first move of each outcome before searching it (Star2).
+ *  Max and min nodes always prune as in alpha-beta.
+ */
+
+pub struct Expectimax<G: GameState> {
+    depth: usize,
+    bounds: Option<(f64, f64)>,
+    evaluation: Option<EvaluationFn<G>>,
+    nodes: usize,
+}
+
+impl<G: GameState> Expectimax<G> {
+    // Depth in plies, every agent move and chance outcome counts as one
+    pub fn new(depth: usize) -> Self {
+        Expectimax { depth, bounds: None, evaluation: None, nodes: 0 }
+    }
+
+    // Every evaluation, terminal states included, must lie in [lower, upper]
+    pub fn set_bounds(&mut self, lower: isize, upper: isize) -> &mut Self {
+        self.bounds = Some((lower as f64, upper as f64));
+        self
+    }
+
+    // Replaces `GameState::evaluate` at the leaves
+    pub fn set_evaluation<F: Fn(&G) -> isize + 'static>(&mut self, evaluation: F) -> &mut Self {
+        self.evaluation = Some(Box::new(evaluation));
+        self
+    }
+
+    // The state must be a decision node, chance nodes have no move to choose
+    pub fn find_move(&mut self, state: &G) -> Option<(G::Move, f64)> {
+        if state.is_chance() { return None }
+        let maximizing = state.get_player() == 0;
+        let (mut alpha, mut beta) = (f64::NEG_INFINITY, f64::INFINITY);
+        let mut best: Option<(G::Move, f64)> = None;
+        for action in state.get_moves() {
+            let value = self.search(&state.apply(&action), self.depth.saturating_sub(1), alpha, beta);
+            let better = best.as_ref()
+                .map(|(_, best)| if maximizing { value > *best } else { value < *best })
+                .unwrap_or(true);
+            if better { best = Some((action, value)); }
+            if maximizing { alpha = alpha.max(value) } else { beta = beta.min(value) }
+        }
+        best
+    }
+
+    pub fn get_nodes(&self) -> usize { self.nodes }
+
+    fn evaluate(&self, state: &G) -> f64 {
+        self.evaluation.as_ref().map(|evaluation| evaluation(state)).unwrap_or_else(|| state.evaluate()) as f64
+    }
+
+    // Fail-soft: a value <= alpha is an upper bound, a value >= beta a lower bound
+    fn search(&mut self, state: &G, depth: usize, alpha: f64, beta: f64) -> f64 {
+        self.search_with_first(state, depth, alpha, beta, None)
+    }
+
+    // `first` is the exact value of the first move when a probe already searched it
+    fn search_with_first(&mut self, state: &G, depth: usize, mut alpha: f64, mut beta: f64, first: Option<f64>) -> f64 {
+        self.nodes += 1;
+        if depth == 0 || state.is_terminal() { return self.evaluate(state) }
+        if state.is_chance() { return self.search_chance(state, depth, alpha, beta) }
+
+        let moves = state.get_moves();
+        if moves.is_empty() { return self.evaluate(state) }
+        let maximizing = state.get_player() == 0;
+        let mut best = if maximizing { f64::NEG_INFINITY } else { f64::INFINITY };
+        for (i, action) in moves.into_iter().enumerate() {
+            let value = match first {
+                Some(value) if i == 0 => value,
+                _ => self.search(&state.apply(&action), depth - 1, alpha, beta),
+            };
+            if maximizing {
+                best = best.max(value);
+                alpha = alpha.max(value);
+            } else {
+                best = best.min(value);
+                beta = beta.min(value);
+            }
+            if alpha >= beta { break; }
+        }
+        best
+    }
+
+    fn search_chance(&mut self, state: &G, depth: usize, alpha: f64, beta: f64) -> f64 {
+        let outcomes: Vec<(G, f64)> = state.get_outcomes().into_iter()
+            .filter(|(_, probability)| *probability > 0.0)
+            .map(|(action, probability)| (state.apply(&action), probability))
+            .collect();
+        if outcomes.is_empty() { return self.evaluate(state) }
+
+        let (lower, upper) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return outcomes.iter()
+                .map(|(child, p)| p * self.search(child, depth - 1, f64::NEG_INFINITY, f64::INFINITY))
+                .sum(),
+        };
+
+        // Bounds of every outcome, tightened by probing
+        let mut lb = vec![lower; outcomes.len()];
+        let mut ub = vec![upper; outcomes.len()];
+        let mut first = vec![None; outcomes.len()];
+        if depth >= 2 {
+            if let Some(value) = self.probe(&outcomes, depth, (alpha, beta), &mut lb, &mut ub, &mut first) {
+                return value
+            }
+        }
+
+        // Star1: outcomes not searched yet are replaced by their bounds
+        let mut sum = 0.0;
+        let mut rest_lb: f64 = outcomes.iter().zip(&lb).map(|((_, p), lb)| p * lb).sum();
+        let mut rest_ub: f64 = outcomes.iter().zip(&ub).map(|((_, p), ub)| p * ub).sum();
+        for (i, (child, p)) in outcomes.iter().enumerate() {
+            rest_lb -= p * lb[i];
+            rest_ub -= p * ub[i];
+            let a = (alpha - sum - rest_ub) / p;
+            let b = (beta - sum - rest_lb) / p;
+            if ub[i] <= a { return sum + p * ub[i] + rest_ub }
+            if lb[i] >= b { return sum + p * lb[i] + rest_lb }
+            if lb[i] >= ub[i] {
+                sum += p * lb[i];
+                continue;
+            }
+
+            let (low, high) = (a.max(lb[i]), b.min(ub[i]));
+            let value = self.search_with_first(child, depth - 1, low, high, first[i]);
+            if value <= low && low > lb[i] { return sum + p * value + rest_ub }
+            if value >= high && high < ub[i] { return sum + p * value + rest_lb }
+            sum += p * value;
+        }
+        sum
+    }
+
+    // Star2: the first move of a max node bounds it from below, the one of a min node from above.
+    // Values found strictly inside the probe window are exact and kept in `first` for the full search
+    fn probe(&mut self, outcomes: &[(G, f64)], depth: usize, (alpha, beta): (f64, f64),
+             lb: &mut [f64], ub: &mut [f64], first: &mut [Option<f64>]) -> Option<f64> {
+        for (i, (child, p)) in outcomes.iter().enumerate() {
+            if child.is_terminal() || child.is_chance() { continue; }
+            let action = match child.get_moves().into_iter().next() {
+                Some(action) => action,
+                None => continue,
+            };
+            let weighted = |bounds: &[f64], range: std::ops::Range<usize>| -> f64 {
+                range.map(|j| outcomes[j].1 * bounds[j]).sum()
+            };
+
+            if child.get_player() == 0 {
+                // The cut off is only reachable if the probe raises the lower bound above `low`
+                let low = ((beta - weighted(lb, 0..i) - weighted(ub, i + 1..outcomes.len())) / p).max(lb[i]);
+                if low >= ub[i] { continue; }
+                self.nodes += 1;
+                let value = self.search(&child.apply(&action), depth - 2, low, ub[i]);
+                if value > low { lb[i] = value.min(ub[i]); }
+                if value > low && value < ub[i] { first[i] = Some(value); }
+                let lower = weighted(lb, 0..outcomes.len());
+                if lower >= beta { return Some(lower) }
+            } else {
+                let high = ((alpha - weighted(ub, 0..i) - weighted(lb, i + 1..outcomes.len())) / p).min(ub[i]);
+                if high <= lb[i] { continue; }
+                self.nodes += 1;
+                let value = self.search(&child.apply(&action), depth - 2, lb[i], high);
+                if value < high { ub[i] = value.max(lb[i]); }
+                if value < high && value > lb[i] { first[i] = Some(value); }
+                let upper = weighted(ub, 0..outcomes.len());
+                if upper <= alpha { return Some(upper) }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Max adds 1 to 3, a die adds 0 to 2, min adds 1 to 3, the die again. Leaves are scrambled into [-5, 5]
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    struct Dice { value: isize, phase: usize }
+
+    impl GameState for Dice {
+        type Move = isize;
+
+        fn get_player(&self) -> usize { if self.phase == 2 { 1 } else { 0 } }
+        fn get_players(&self) -> usize { 2 }
+        fn get_moves(&self) -> Vec<isize> { if self.is_chance() { Vec::new() } else { vec![1, 2, 3] } }
+        fn apply(&self, step: &isize) -> Self { Dice { value: self.value + step, phase: (self.phase + 1) % 4 } }
+        fn is_terminal(&self) -> bool { false }
+        fn evaluate(&self) -> isize { (self.value * self.value * 3 + self.value * 5).rem_euclid(11) - 5 }
+        fn is_chance(&self) -> bool { self.phase % 2 == 1 }
+        fn get_outcomes(&self) -> Vec<(isize, f64)> { vec![(0, 0.5), (1, 0.25), (2, 0.25)] }
+    }
+
+    // Without any pruning
+    fn expectimax(state: &Dice, depth: usize, nodes: &mut usize) -> f64 {
+        *nodes += 1;
+        if depth == 0 { return state.evaluate() as f64 }
+        if state.is_chance() {
+            return state.get_outcomes().iter().map(|(roll, p)| p * expectimax(&state.apply(roll), depth - 1, nodes)).sum()
+        }
+        let values = state.get_moves().into_iter().map(|step| expectimax(&state.apply(&step), depth - 1, nodes));
+        if state.get_player() == 0 { values.fold(f64::NEG_INFINITY, f64::max) } else { values.fold(f64::INFINITY, f64::min) }
+    }
+
+    #[test]
+    fn pruning_keeps_the_values() {
+        let (mut plain_total, mut unbounded_total, mut bounded_total) = (0, 0, 0);
+        for value in 0..6 {
+            let state = Dice { value, phase: 0 };
+            for depth in 1..9 {
+                let mut plain_nodes = 0;
+                let expected = state.get_moves().into_iter()
+                    .map(|step| expectimax(&state.apply(&step), depth - 1, &mut plain_nodes))
+                    .fold(f64::NEG_INFINITY, f64::max);
+                let mut unbounded = Expectimax::new(depth);
+                let mut bounded = Expectimax::new(depth);
+                bounded.set_bounds(-5, 5);
+                for search in [&mut unbounded, &mut bounded] {
+                    let (_, found) = search.find_move(&state).unwrap();
+                    assert!((found - expected).abs() < 1e-9, "value {}, depth {}: {} != {}", value, depth, found, expected);
+                }
+                plain_total += plain_nodes;
+                unbounded_total += unbounded.get_nodes();
+                bounded_total += bounded.get_nodes();
+            }
+        }
+        // Star2 probes may cost more than they save on a single search, not over all of them
+        assert!(unbounded_total <= plain_total);
+        assert!(bounded_total * 10 < unbounded_total * 9, "{} nodes with bounds, {} without", bounded_total, unbounded_total);
+    }
+
+    #[test]
+    fn evaluation_replaces_the_state_evaluation() {
+        let state = Dice { value: 0, phase: 0 };
+        let mut search = Expectimax::new(1);
+        search.set_evaluation(|state: &Dice| -state.value);
+        assert_eq!(search.find_move(&state), Some((1, -1.0)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,276 @@
+use crate::mcts::{get_exploration_bonus, select_best};
+use crate::traits::game::GameState;
+use crate::utils::rng::Rng;
+use std::time::{Duration, Instant};
+
+// Picks the index of the move to play during a rollout
+pub type GameRolloutFn<G> = Box<dyn FnMut(&G, &[<G as GameState>::Move], &mut Rng) -> usize>;
+
+struct TreeNode<G: GameState> {
+    state: G,
+    action: Option<G::Move>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // Moves without a child yet, chance nodes add children as their outcomes are drawn
+    untried: Vec<G::Move>,
+    visits: usize,
+    // Sum of the evaluations from the point of view of player 0
+    value: f64,
+}
+
+/*
+ *  UCT for games: player 0 picks the child with the highest normalized mean, every other player the
+ *  lowest, chance nodes draw an outcome with its probability. One child is added per iteration and
+ *  rollouts are evaluated with `GameState::evaluate` when they end or reach the rollout depth.
+ *
+ *  The chosen move is the most visited child of the root. The single agent counterpart is `mcts::MCTS`,
+ *  both share the UCT selection.
+ */
+
+pub struct GameMCTS<G: GameState + Clone> {
+    tree: Vec<TreeNode<G>>,
+    exploration: f64,
+    iterations: usize,
+    time_limit: Option<Duration>,
+    rollout_depth: usize,
+    rollout: Option<GameRolloutFn<G>>,
+    rng: Rng,
+    // Lowest and highest evaluation seen
+    bounds: Option<(f64, f64)>,
+    completed: usize,
+}
+
+impl<G: GameState + Clone> GameMCTS<G> {
+    pub fn new() -> Self {
+        GameMCTS {
+            tree: Vec::new(),
+            exploration: std::f64::consts::SQRT_2,
+            iterations: 10_000,
+            time_limit: None,
+            rollout_depth: 100,
+            rollout: None,
+            rng: Rng::new(0),
+            bounds: None,
+            completed: 0,
+        }
+    }
+
+    pub fn set_exploration(&mut self, exploration: f64) -> &mut Self {
+        self.exploration = exploration;
+        self
+    }
+
+    // Both budgets apply, the search stops at the first one reached
+    pub fn set_iterations(&mut self, iterations: usize) -> &mut Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn set_time_limit(&mut self, time_limit: Duration) -> &mut Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    // Rollouts are evaluated after `depth` plies even if the game did not end
+    pub fn set_rollout_depth(&mut self, depth: usize) -> &mut Self {
+        self.rollout_depth = depth;
+        self
+    }
+
+    // Replaces the uniformly random rollout policy
+    pub fn set_rollout(&mut self, rollout: GameRolloutFn<G>) -> &mut Self {
+        self.rollout = Some(rollout);
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    // The state must be a decision node, returns the move and its mean evaluation
+    pub fn find_move(&mut self, state: &G) -> Option<(G::Move, f64)> {
+        if state.is_chance() || state.is_terminal() { return None }
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        self.tree.clear();
+        self.bounds = None;
+        self.completed = 0;
+        self.add_node(state.clone(), None, None);
+        if self.tree[0].untried.is_empty() { return None }
+
+        while self.completed < self.iterations {
+            if deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false) { break; }
+            self.iterate();
+            self.completed += 1;
+        }
+
+        self.tree[0].children.iter()
+            .max_by_key(|child| self.tree[**child].visits)
+            .map(|child| {
+                let node = &self.tree[*child];
+                (node.action.clone().unwrap(), node.value / node.visits.max(1) as f64)
+            })
+    }
+
+    pub fn get_iterations(&self) -> usize { self.completed }
+    pub fn get_tree_size(&self) -> usize { self.tree.len() }
+
+    fn add_node(&mut self, state: G, action: Option<G::Move>, parent: Option<usize>) -> usize {
+        let untried = if state.is_terminal() || state.is_chance() { Vec::new() } else { state.get_moves() };
+        self.tree.push(TreeNode { state, action, parent, children: Vec::new(), untried, visits: 0, value: 0.0 });
+        let id = self.tree.len() - 1;
+        if let Some(parent) = parent { self.tree[parent].children.push(id); }
+        id
+    }
+
+    fn draw_outcome(&mut self, state: &G) -> Option<G::Move> {
+        let outcomes = state.get_outcomes();
+        let mut left = self.rng.next_f64();
+        for (action, probability) in &outcomes {
+            if left < *probability { return Some(action.clone()) }
+            left -= probability;
+        }
+        outcomes.last().map(|(action, _)| action.clone())
+    }
+
+    fn get_mean(&self, index: usize) -> f64 {
+        let node = &self.tree[index];
+        let mean = node.value / node.visits as f64;
+        match self.bounds {
+            Some((low, high)) if high > low => (mean - low) / (high - low),
+            _ => 0.5,
+        }
+    }
+
+    fn select_child(&self, index: usize) -> usize {
+        let maximizing = self.tree[index].state.get_player() == 0;
+        let visits = self.tree[index].visits;
+        let score = |child: usize| -> f64 {
+            let mean = if maximizing { self.get_mean(child) } else { 1.0 - self.get_mean(child) };
+            mean + get_exploration_bonus(self.exploration, visits, self.tree[child].visits)
+        };
+        select_best(self.tree[index].children.iter().copied(), score).unwrap()
+    }
+
+    fn iterate(&mut self) {
+        // Selection and expansion
+        let mut current = 0;
+        loop {
+            let node = &self.tree[current];
+            if node.state.is_terminal() { break; }
+            if node.state.is_chance() {
+                let state = node.state.clone();
+                let outcome = match self.draw_outcome(&state) {
+                    Some(outcome) => outcome,
+                    None => break,
+                };
+                let child = self.tree[current].children.iter().copied()
+                    .find(|child| self.tree[*child].action.as_ref() == Some(&outcome));
+                match child {
+                    Some(child) => current = child,
+                    None => {
+                        current = self.add_node(state.apply(&outcome), Some(outcome), Some(current));
+                        break;
+                    }
+                }
+            } else if !node.untried.is_empty() {
+                let i = self.rng.next_below(node.untried.len());
+                let action = self.tree[current].untried.swap_remove(i);
+                let state = self.tree[current].state.apply(&action);
+                current = self.add_node(state, Some(action), Some(current));
+                break;
+            } else if node.children.is_empty() {
+                break;
+            } else {
+                current = self.select_child(current);
+            }
+        }
+
+        let value = self.simulate(self.tree[current].state.clone());
+        self.bounds = Some(self.bounds.map_or((value, value), |(low, high)| (low.min(value), high.max(value))));
+        let mut next = Some(current);
+        while let Some(index) = next {
+            self.tree[index].visits += 1;
+            self.tree[index].value += value;
+            next = self.tree[index].parent;
+        }
+    }
+
+    fn simulate(&mut self, mut state: G) -> f64 {
+        for _ in 0..self.rollout_depth {
+            if state.is_terminal() { break; }
+            let action = if state.is_chance() {
+                self.draw_outcome(&state)
+            } else {
+                let moves = state.get_moves();
+                if moves.is_empty() { break; }
+                let i = match &mut self.rollout {
+                    Some(rollout) => rollout(&state, &moves, &mut self.rng),
+                    None => self.rng.next_below(moves.len()),
+                };
+                moves.into_iter().nth(i)
+            };
+            match action {
+                Some(action) => state = state.apply(&action),
+                None => break,
+            }
+        }
+        state.evaluate() as f64
+    }
+}
+
+impl<G: GameState + Clone> Default for GameMCTS<G> {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::minimax::Minimax;
+    use crate::utils::fixtures::Stones;
+
+    #[test]
+    fn same_seed_same_move() {
+        let state = Stones { stones: 9, score: 0, player: 0 };
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let mut search = GameMCTS::new();
+            search.set_seed(7).set_iterations(500);
+            let (action, value) = search.find_move(&state).unwrap();
+            runs.push((action, value.to_bits(), search.get_tree_size()));
+        }
+        assert_eq!(runs[0], runs[1]);
+    }
+
+    #[test]
+    fn stops_at_the_budgets() {
+        let state = Stones { stones: 30, score: 0, player: 0 };
+        let mut search = GameMCTS::new();
+        search.set_iterations(40);
+        assert!(search.find_move(&state).is_some());
+        assert_eq!(search.get_iterations(), 40);
+        assert!(search.get_tree_size() <= 41);
+
+        let time_start = Instant::now();
+        search.set_iterations(usize::MAX).set_time_limit(Duration::from_millis(20));
+        assert!(search.find_move(&state).is_some());
+        assert!(time_start.elapsed() < Duration::from_secs(2));
+        assert!(search.get_iterations() > 0);
+    }
+
+    #[test]
+    fn plays_the_minimax_move() {
+        for stones in 2..9 {
+            let state = Stones { stones, score: 0, player: 0 };
+            let value = |action: &usize| {
+                let child = state.apply(action);
+                if child.is_terminal() { child.evaluate() } else { Minimax::new(stones).find_move(&child).unwrap().1 }
+            };
+            let best = state.get_moves().iter().map(value).max();
+            let mut search = GameMCTS::new();
+            search.set_seed(1).set_iterations(3000);
+            let (action, _) = search.find_move(&state).unwrap();
+            assert_eq!(Some(value(&action)), best, "{} stones", stones);
+        }
+    }
+}
\ No newline at end of file
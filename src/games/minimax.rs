@@ -0,0 +1,36 @@
+use crate::traits::game::GameState;
+
+pub struct Minimax {
+    depth: usize,
+    nodes: usize,
+}
+
+impl Minimax {
+    // Depth in plies, every agent move counts as one
+    pub fn new(depth: usize) -> Self {
+        Minimax { depth, nodes: 0 }
+    }
+
+    pub fn find_move<G: GameState>(&mut self, state: &G) -> Option<(G::Move, isize)> {
+        let maximizing = state.get_player() == 0;
+        let mut best: Option<(G::Move, isize)> = None;
+        for action in state.get_moves() {
+            let value = self.minimax(&state.apply(&action), self.depth.saturating_sub(1));
+            let better = best.as_ref()
+                .map(|(_, best)| if maximizing { value > *best } else { value < *best })
+                .unwrap_or(true);
+            if better { best = Some((action, value)); }
+        }
+        best
+    }
+
+    pub fn get_nodes(&self) -> usize { self.nodes }
+
+    fn minimax<G: GameState>(&mut self, state: &G, depth: usize) -> isize {
+        self.nodes += 1;
+        if depth == 0 || state.is_terminal() { return state.evaluate() }
+        let values = state.get_moves().into_iter().map(|action| self.minimax(&state.apply(&action), depth - 1));
+        let value = if state.get_player() == 0 { values.max() } else { values.min() };
+        value.unwrap_or_else(|| state.evaluate())
+    }
+}
\ No newline at end of file
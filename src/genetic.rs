@@ -0,0 +1,412 @@
+use super::traits::node::Node;
+use super::traits::algorithm::{Algorithm, AlgorithmExt};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::rng::Rng;
+use super::utils::state_table::StateTable;
+use std::mem::size_of;
+use std::rc::Rc;
+
+// Picks a parent from the population sorted best first, given its size
+pub type SelectionFn = Box<dyn FnMut(usize, &mut Rng) -> usize>;
+pub type CrossoverFn<A> = Box<dyn FnMut(&[A], &[A], &mut Rng) -> Vec<A>>;
+pub type MutationFn<A> = Box<dyn FnMut(&mut Vec<A>, &mut Rng)>;
+// Picks the descendant replacing an invalid or missing action by its index, indices past the end
+// wrap around so a repair may return any number
+pub type RepairFn<T> = Box<dyn FnMut(&[Box<T>], &mut Rng) -> usize>;
+
+// Best of `size` random individuals
+pub fn tournament(size: usize) -> SelectionFn {
+    Box::new(move |population, rng| (0..size.max(1)).map(|_| rng.next_below(population)).min().unwrap())
+}
+
+// Linear ranking, the best individual is `population` times more likely than the worst
+pub fn rank() -> SelectionFn {
+    Box::new(|population, rng| {
+        let mut ticket = rng.next_below(population * (population + 1) / 2);
+        for i in 0..population {
+            let weight = population - i;
+            if ticket < weight { return i }
+            ticket -= weight;
+        }
+        population - 1
+    })
+}
+
+// Uniform among the best `fraction` of the population
+pub fn truncation(fraction: f64) -> SelectionFn {
+    Box::new(move |population, rng| rng.next_below(((population as f64 * fraction) as usize).clamp(1, population)))
+}
+
+// Head of the first parent and tail of the second, cut at the same position
+pub fn one_point<A: Clone>() -> CrossoverFn<A> {
+    Box::new(|a, b, rng| {
+        let cut = rng.next_below(a.len() + 1);
+        a[..cut].iter().chain(b.iter().skip(cut)).cloned().collect()
+    })
+}
+
+// Segment of the second parent between two cuts, the first parent elsewhere
+pub fn two_point<A: Clone>() -> CrossoverFn<A> {
+    Box::new(|a, b, rng| {
+        let (x, y) = (rng.next_below(a.len() + 1), rng.next_below(a.len() + 1));
+        let (start, end) = (x.min(y), x.max(y).min(b.len()));
+        let mut child = a.to_vec();
+        if start < end { child[start..end].clone_from_slice(&b[start..end]); }
+        child
+    })
+}
+
+// Every position from either parent, the length is taken from one of them
+pub fn uniform<A: Clone>() -> CrossoverFn<A> {
+    Box::new(|a, b, rng| {
+        let length = if rng.next_below(2) == 0 { a.len() } else { b.len() };
+        (0..length).map(|i| match (a.get(i), b.get(i)) {
+            (Some(x), Some(y)) => if rng.next_below(2) == 0 { x.clone() } else { y.clone() },
+            (Some(x), None) => x.clone(),
+            (None, Some(y)) => y.clone(),
+            (None, None) => unreachable!(),
+        }).collect()
+    })
+}
+
+// Exchanges two actions
+pub fn swap<A>() -> MutationFn<A> {
+    Box::new(|genome, rng| {
+        if genome.len() < 2 { return }
+        let (i, j) = (rng.next_below(genome.len()), rng.next_below(genome.len()));
+        genome.swap(i, j);
+    })
+}
+
+// Drops one action
+pub fn remove<A>() -> MutationFn<A> {
+    Box::new(|genome, rng| {
+        if genome.is_empty() { return }
+        genome.remove(rng.next_below(genome.len()));
+    })
+}
+
+// Cuts the tail, repair grows a new one
+pub fn truncate<A>() -> MutationFn<A> {
+    Box::new(|genome, rng| {
+        let length = rng.next_below(genome.len() + 1);
+        genome.truncate(length);
+    })
+}
+
+// Solutions first by cost, then the other plans by the estimated total cost of their last node
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Fitness {
+    Solved(isize),
+    Unsolved(isize),
+}
+
+/*
+ *  Genomes are action sequences replayed from the root. Invalid actions and the missing tail of a
+ *  sequence that ends before a solution are replaced with descendants chosen by the repair function
+ *  (random by default), and the repaired sequence is written back to the genome.
+ *
+ *  Returns the cheapest solution reached by any individual.
+ */
+
+pub struct Genetic<T: Node + Clone> where T::Action: Clone + PartialEq {
+    population: usize,
+    generations: usize,
+    elitism: usize,
+    crossover_rate: f64,
+    mutation_rate: f64,
+    max_length: usize,
+    selection: SelectionFn,
+    crossover: CrossoverFn<T::Action>,
+    mutation: MutationFn<T::Action>,
+    repair: Option<RepairFn<T>>,
+    rng: Rng,
+    states: StateTable<Rc<T::State>>,
+    genes: usize,
+    depth: usize,
+    history: Vec<Option<isize>>,
+    memory: MemoryBudget<T::State>,
+}
+
+impl<T: Node + Clone> Genetic<T> where T::Action: Clone + PartialEq {
+    pub fn new() -> Self {
+        Genetic {
+            population: 50,
+            generations: 100,
+            elitism: 2,
+            crossover_rate: 0.9,
+            mutation_rate: 0.2,
+            max_length: 1_000,
+            selection: tournament(3),
+            crossover: one_point(),
+            mutation: truncate(),
+            repair: None,
+            rng: Rng::new(0),
+            states: StateTable::new(),
+            genes: 0,
+            depth: 0,
+            history: Vec::new(),
+            memory: MemoryBudget::new(),
+        }
+    }
+
+    pub fn set_population(&mut self, population: usize) -> &mut Self {
+        self.population = population.max(1);
+        self
+    }
+
+    pub fn set_generations(&mut self, generations: usize) -> &mut Self {
+        self.generations = generations;
+        self
+    }
+
+    // Best individuals copied unchanged to the next generation
+    pub fn set_elitism(&mut self, elitism: usize) -> &mut Self {
+        self.elitism = elitism;
+        self
+    }
+
+    pub fn set_rates(&mut self, crossover: f64, mutation: f64) -> &mut Self {
+        self.crossover_rate = crossover;
+        self.mutation_rate = mutation;
+        self
+    }
+
+    // Replays stop after `length` actions
+    pub fn set_max_length(&mut self, length: usize) -> &mut Self {
+        self.max_length = length;
+        self
+    }
+
+    pub fn set_selection(&mut self, selection: SelectionFn) -> &mut Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn set_crossover(&mut self, crossover: CrossoverFn<T::Action>) -> &mut Self {
+        self.crossover = crossover;
+        self
+    }
+
+    pub fn set_mutation(&mut self, mutation: MutationFn<T::Action>) -> &mut Self {
+        self.mutation = mutation;
+        self
+    }
+
+    pub fn set_repair(&mut self, repair: RepairFn<T>) -> &mut Self {
+        self.repair = Some(repair);
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    // Cost of the best solution after every generation, the first entry is the initial population
+    pub fn get_history(&self) -> &[Option<isize>] { &self.history }
+
+    // Repairs the genome in place, returns the solution reached or the estimated total cost of the last node
+    fn replay(&mut self, root: &T, genome: &mut Vec<T::Action>) -> Result<Box<T>, isize> {
+        let mut node = Box::new(root.clone());
+        self.depth = 0;
+        let result = loop {
+            let (id, inserted) = self.states.intern_with(node.get_state(), node.get_state_hash());
+            if inserted { self.memory.add_state(self.states.get(id)); }
+            if node.is_solution() { break Ok(node) }
+            let estimate = node.get_cost() + node.get_heuristic();
+            if self.depth == self.max_length { break Err(estimate) }
+
+            let mut descendants = node.get_descendants();
+            if descendants.is_empty() { break Err(estimate) }
+            let valid = genome.get(self.depth)
+                .and_then(|action| descendants.iter().position(|descendant| descendant.get_action().as_ref() == Some(action)));
+            let i = match (valid, &mut self.repair) {
+                (Some(i), _) => i,
+                (None, Some(repair)) => repair(&descendants, &mut self.rng) % descendants.len(),
+                (None, None) => self.rng.next_below(descendants.len()),
+            };
+            node = descendants.swap_remove(i);
+            if let Some(action) = node.get_action() {
+                match genome.get_mut(self.depth) {
+                    Some(gene) => *gene = action,
+                    None => genome.push(action),
+                }
+            }
+            self.depth += 1;
+        };
+        genome.truncate(self.depth);
+        result
+    }
+
+    fn evaluate(&mut self, root: &T, genome: &mut Vec<T::Action>, best: &mut Option<Box<T>>) -> Fitness {
+        let node = match self.replay(root, genome) {
+            Ok(node) => node,
+            Err(estimate) => return Fitness::Unsolved(estimate),
+        };
+        let fitness = Fitness::Solved(node.get_cost());
+        if best.as_ref().map(|best| node.get_cost() < best.get_cost()).unwrap_or(true) { *best = Some(node); }
+        fitness
+    }
+}
+
+impl<T: Node + Clone> Default for Genetic<T> where T::Action: Clone + PartialEq {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Node + Clone> Algorithm<T> for Genetic<T> where T::Action: Clone + PartialEq {
+    fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        self.memory.reset();
+        self.states.clear();
+        self.history.clear();
+        self.genes = 0;
+        self.depth = 0;
+        let mut best: Option<Box<T>> = None;
+        let mut population: Vec<(Vec<T::Action>, Fitness)> = Vec::new();
+        for _ in 0..self.population {
+            let mut genome = Vec::new();
+            let fitness = self.evaluate(&root, &mut genome, &mut best);
+            self.genes += genome.len();
+            population.push((genome, fitness));
+        }
+
+        for generation in 0..=self.generations {
+            population.sort_by_key(|(_, fitness)| *fitness);
+            self.history.push(best.as_ref().map(|best| best.get_cost()));
+            if generation == self.generations || self.memory.check(&self.get_memory_usage()) { break; }
+
+            let mut next: Vec<(Vec<T::Action>, Fitness)> = population.iter().take(self.elitism).cloned().collect();
+            while next.len() < self.population {
+                let a = (self.selection)(population.len(), &mut self.rng);
+                let mut child = if self.rng.next_f64() < self.crossover_rate {
+                    let b = (self.selection)(population.len(), &mut self.rng);
+                    (self.crossover)(&population[a].0, &population[b].0, &mut self.rng)
+                } else {
+                    population[a].0.clone()
+                };
+                if self.rng.next_f64() < self.mutation_rate { (self.mutation)(&mut child, &mut self.rng); }
+                let fitness = self.evaluate(&root, &mut child, &mut best);
+                next.push((child, fitness));
+            }
+            self.genes = next.iter().map(|(genome, _)| genome.len()).sum();
+            population = next;
+        }
+        best
+    }
+
+    fn get_visited(&self) -> Vec<&Rc<T::State>> {
+        self.states.iter().collect()
+    }
+}
+
+impl<T: Node + Clone> AlgorithmExt<T> for Genetic<T> where T::Action: Clone + PartialEq {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.genes * size_of::<T::Action>()
+                + self.population * size_of::<(Vec<T::Action>, Fitness)>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.depth + 1) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a_star::AStar;
+    use crate::utils::fixtures::{EdgeNode, GridNode};
+
+    fn children(crossover: &mut CrossoverFn<usize>, a: &[usize], b: &[usize]) -> Vec<Vec<usize>> {
+        let mut rng = Rng::new(0);
+        (0..50).map(|_| crossover(a, b, &mut rng)).collect()
+    }
+
+    // Whether every position of the child comes from the same position of a parent, true for the second
+    fn sources(child: &[usize], a: &[usize], b: &[usize]) -> Vec<bool> {
+        child.iter().enumerate().map(|(i, gene)| match (a.get(i), b.get(i)) {
+            (Some(x), _) if x == gene => false,
+            (_, Some(y)) if y == gene => true,
+            _ => panic!("{:?} does not come from {:?} and {:?}", child, a, b),
+        }).collect()
+    }
+
+    const SHORT: [usize; 3] = [1, 2, 3];
+    const LONG: [usize; 5] = [10, 20, 30, 40, 50];
+
+    #[test]
+    fn one_point_takes_a_head_and_a_tail() {
+        for (a, b) in [(&SHORT[..], &LONG[..]), (&LONG[..], &SHORT[..]), (&SHORT[..], &LONG[..3])] {
+            for child in children(&mut one_point(), a, b) {
+                assert!(child.len() >= b.len() && child.len() <= a.len().max(b.len()));
+                assert!(sources(&child, a, b).windows(2).all(|pair| pair[0] <= pair[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn two_point_replaces_one_segment() {
+        for (a, b) in [(&SHORT[..], &LONG[..]), (&LONG[..], &SHORT[..]), (&SHORT[..], &LONG[..3])] {
+            for child in children(&mut two_point(), a, b) {
+                assert_eq!(child.len(), a.len());
+                let sources = sources(&child, a, b);
+                assert!(sources.windows(2).filter(|pair| pair[0] != pair[1]).count() <= 2);
+                assert!(sources.iter().enumerate().all(|(i, from_b)| !from_b || i < b.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_mixes_positions() {
+        for (a, b) in [(&SHORT[..], &LONG[..]), (&LONG[..], &SHORT[..]), (&SHORT[..], &LONG[..3])] {
+            let children = children(&mut uniform(), a, b);
+            assert!(children.iter().all(|child| child.len() == a.len() || child.len() == b.len()));
+            for child in &children { sources(child, a, b); }
+            assert!(children.iter().any(|child| sources(child, a, b)[..3].windows(2).any(|pair| pair[0] != pair[1])));
+        }
+    }
+
+    #[test]
+    fn only_children_below_the_elites_are_bred() {
+        use std::cell::Cell;
+        let mutations = Rc::new(Cell::new(0));
+        let mut genetic = Genetic::new();
+        let counter = mutations.clone();
+        genetic.set_population(10).set_generations(20).set_rates(0.0, 1.0).set_seed(2)
+            .set_mutation(Box::new(move |_: &mut Vec<char>, _: &mut Rng| counter.set(counter.get() + 1)));
+
+        for (elitism, children) in [(10, 0), (4, 6 * 20), (0, 10 * 20)] {
+            mutations.set(0);
+            genetic.set_elitism(elitism);
+            genetic.find_solution(GridNode::root());
+            assert_eq!(mutations.get(), children);
+        }
+    }
+
+    #[test]
+    fn finds_the_optimal_plan() {
+        let optimum = AStar::new().find_solution(Box::new(EdgeNode::root())).unwrap().get_cost();
+        for seed in 0..10 {
+            let mut genetic = Genetic::new();
+            genetic.set_population(10).set_generations(10).set_seed(seed);
+            let solution = genetic.find_solution(Box::new(EdgeNode::root()));
+            assert_eq!(solution.map(|solution| solution.get_cost()), Some(optimum));
+            let history: Vec<isize> = genetic.get_history().iter().flatten().copied().collect();
+            assert!(history.windows(2).all(|pair| pair[1] <= pair[0]));
+        }
+    }
+
+    #[test]
+    fn repair_indices_wrap_around() {
+        let mut genetic = Genetic::new();
+        genetic.set_population(5).set_generations(5).set_max_length(30)
+            .set_repair(Box::new(|_: &[Box<GridNode>], rng: &mut Rng| 100 + rng.next_below(100)));
+        assert!(genetic.find_solution(GridNode::root()).is_some());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,105 @@
+use crate::traits::heuristic::Heuristic;
+use crate::traits::problem::Problem;
+use crate::utils::graph::Graph;
+
+pub enum Violation<S, A> {
+    // Estimate over the true distance to the nearest solution
+    Overestimate { state: S, heuristic: isize, distance: isize },
+    // h(state) > cost + h(successor)
+    Inconsistent { state: S, action: A, successor: S, cost: isize, heuristic: isize, successor_heuristic: isize },
+}
+
+pub struct HeuristicReport<S, A> {
+    pub states: usize,
+    pub solutions: usize,
+    pub violations: Vec<Violation<S, A>>,
+}
+
+impl<S, A> HeuristicReport<S, A> {
+    pub fn is_admissible(&self) -> bool {
+        !self.violations.iter().any(|violation| matches!(violation, Violation::Overestimate { .. }))
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        !self.violations.iter().any(|violation| matches!(violation, Violation::Inconsistent { .. }))
+    }
+}
+
+/*
+ *  Exhaustive check for small problems: every reachable state is compared with its true distance to
+ *  the nearest solution (Dijkstra backward from every solution), and every edge leaving it with
+ *  h(state) <= cost + h(successor). States that cannot reach a solution may have any estimate.
+ */
+
+pub fn check_heuristic<P, H>(problem: &P, heuristic: &H) -> HeuristicReport<P::State, P::Action>
+where
+    P: Problem,
+    P::State: Clone,
+    H: Heuristic<P::State> + ?Sized,
+{
+    let graph = Graph::from_problem(problem);
+    let solutions: Vec<usize> = (0..graph.len()).filter(|id| problem.is_solution(graph.get_vertex(*id))).collect();
+    let distances = graph.get_distances_from(&solutions, true);
+
+    let mut violations = Vec::new();
+    for (id, distance) in distances.iter().enumerate() {
+        let Some(distance) = *distance else { continue };
+        let state = graph.get_vertex(id);
+        let estimate = heuristic.get_heuristic(state);
+        if estimate > distance {
+            violations.push(Violation::Overestimate { state: state.clone(), heuristic: estimate, distance });
+        }
+        for (action, successor, cost) in problem.get_successors(state) {
+            let successor_heuristic = heuristic.get_heuristic(&successor);
+            if estimate <= cost + successor_heuristic { continue; }
+            violations.push(Violation::Inconsistent {
+                state: state.clone(), action, successor, cost, heuristic: estimate, successor_heuristic,
+            });
+        }
+    }
+    HeuristicReport { states: graph.len(), solutions: solutions.len(), violations }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // States 0..=4 on a line, steps of 1 cost 1, 4 is the solution
+    struct Line;
+
+    impl Problem for Line {
+        type State = isize;
+        type Action = ();
+
+        fn get_root(&self) -> isize { 0 }
+
+        fn get_successors(&self, state: &isize) -> Vec<((), isize, isize)> {
+            if *state < 4 { vec![((), state + 1, 1)] } else { Vec::new() }
+        }
+
+        fn get_heuristic(&self, _state: &isize) -> isize { 0 }
+        fn is_solution(&self, state: &isize) -> bool { *state == 4 }
+    }
+
+    #[test]
+    fn exact_distance_has_no_violations() {
+        let report = check_heuristic(&Line, &|state: &isize| 4 - state);
+        assert_eq!((report.states, report.solutions), (5, 1));
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn reports_overestimates() {
+        let report = check_heuristic(&Line, &|state: &isize| if *state == 2 { 3 } else { 4 - state });
+        assert!(!report.is_admissible());
+        assert!(matches!(report.violations[..], [Violation::Overestimate { state: 2, heuristic: 3, distance: 2 }, ..]));
+    }
+
+    #[test]
+    fn admissible_may_be_inconsistent() {
+        let report = check_heuristic(&Line, &|state: &isize| if *state == 1 { 0 } else { 4 - state });
+        assert!(report.is_admissible());
+        assert!(!report.is_consistent());
+        assert!(matches!(report.violations[..], [Violation::Inconsistent { state: 0, successor: 1, .. }]));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,187 @@
+use crate::traits::heuristic::{BoxedHeuristic, Heuristic};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// Admissible or consistent if every member is, and at least as informed as any of them
+pub struct Max<S> {
+    heuristics: Vec<BoxedHeuristic<S>>,
+}
+
+impl<S> Max<S> {
+    pub fn new() -> Self {
+        Max { heuristics: Vec::new() }
+    }
+
+    pub fn add<H: Heuristic<S> + Send + Sync + 'static>(&mut self, heuristic: H) -> &mut Self {
+        self.heuristics.push(Box::new(heuristic));
+        self
+    }
+}
+
+impl<S> Default for Max<S> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<S> Heuristic<S> for Max<S> {
+    fn get_heuristic(&self, state: &S) -> isize {
+        self.heuristics.iter().map(|heuristic| heuristic.get_heuristic(state)).max().unwrap_or(0)
+    }
+}
+
+// Rounded down, admissible if every member is and the weights add up to at most 1
+pub struct WeightedSum<S> {
+    terms: Vec<(f64, BoxedHeuristic<S>)>,
+}
+
+impl<S> WeightedSum<S> {
+    pub fn new() -> Self {
+        WeightedSum { terms: Vec::new() }
+    }
+
+    pub fn add<H: Heuristic<S> + Send + Sync + 'static>(&mut self, weight: f64, heuristic: H) -> &mut Self {
+        self.terms.push((weight, Box::new(heuristic)));
+        self
+    }
+}
+
+impl<S> Default for WeightedSum<S> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<S> Heuristic<S> for WeightedSum<S> {
+    fn get_heuristic(&self, state: &S) -> isize {
+        let sum: f64 = self.terms.iter().map(|(weight, heuristic)| weight * heuristic.get_heuristic(state) as f64).sum();
+        sum.floor() as isize
+    }
+}
+
+// Remembers the estimate of every state, the cache starts over once it holds `capacity` states
+pub struct Cached<S> {
+    heuristic: BoxedHeuristic<S>,
+    cache: Mutex<HashMap<S, isize>>,
+    capacity: usize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl<S> Cached<S> {
+    pub fn new<H: Heuristic<S> + Send + Sync + 'static>(heuristic: H) -> Self {
+        Cached {
+            heuristic: Box::new(heuristic),
+            cache: Mutex::new(HashMap::new()),
+            capacity: 1 << 20,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn get_hits(&self) -> usize { self.hits.load(Ordering::Relaxed) }
+    pub fn get_misses(&self) -> usize { self.misses.load(Ordering::Relaxed) }
+}
+
+impl<S: Eq + Hash + Clone> Heuristic<S> for Cached<S> {
+    fn get_heuristic(&self, state: &S) -> isize {
+        if let Some(value) = self.cache.lock().unwrap().get(state) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return *value;
+        }
+        // Computed without the lock, other threads keep reading the cache
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let value = self.heuristic.get_heuristic(state);
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= self.capacity { cache.clear(); }
+        cache.insert(state.clone(), value);
+        value
+    }
+}
+
+// One of several named heuristics, the first one added until another is selected
+pub struct Selector<S> {
+    heuristics: Vec<(String, BoxedHeuristic<S>)>,
+    selected: usize,
+}
+
+impl<S> Selector<S> {
+    pub fn new() -> Self {
+        Selector { heuristics: Vec::new(), selected: 0 }
+    }
+
+    pub fn add<H: Heuristic<S> + Send + Sync + 'static>(&mut self, name: &str, heuristic: H) -> &mut Self {
+        self.heuristics.push((name.to_owned(), Box::new(heuristic)));
+        self
+    }
+
+    // False and the selection is unchanged if there is no heuristic with that name
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.heuristics.iter().position(|(other, _)| other == name) {
+            Some(i) => { self.selected = i; true },
+            None => false,
+        }
+    }
+
+    pub fn get_selected(&self) -> Option<&str> {
+        self.heuristics.get(self.selected).map(|(name, _)| name.as_str())
+    }
+
+    pub fn get_names(&self) -> Vec<&str> {
+        self.heuristics.iter().map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+impl<S> Default for Selector<S> {
+    fn default() -> Self { Self::new() }
+}
+
+// 0 while empty
+impl<S> Heuristic<S> for Selector<S> {
+    fn get_heuristic(&self, state: &S) -> isize {
+        self.heuristics.get(self.selected).map(|(_, heuristic)| heuristic.get_heuristic(state)).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_and_weighted_sum() {
+        let mut max = Max::new();
+        assert_eq!(max.get_heuristic(&3), 0);
+        max.add(|state: &isize| *state).add(|state: &isize| 10 - state);
+        assert_eq!((max.get_heuristic(&3), max.get_heuristic(&8)), (7, 8));
+
+        let mut sum = WeightedSum::new();
+        sum.add(0.5, |state: &isize| *state).add(0.5, |_: &isize| 2);
+        assert_eq!((sum.get_heuristic(&3), sum.get_heuristic(&4)), (2, 3));
+    }
+
+    #[test]
+    fn cached_counts_hits_and_starts_over_when_full() {
+        let mut cached = Cached::new(|state: &isize| state * 2);
+        cached.set_capacity(2);
+        for state in [1, 1, 2, 1, 3, 1] {
+            assert_eq!(cached.get_heuristic(&state), state * 2);
+        }
+        // 3 clears the cache holding 1 and 2, so the last 1 is computed again
+        assert_eq!((cached.get_hits(), cached.get_misses()), (2, 4));
+    }
+
+    #[test]
+    fn selector_keeps_selection_on_unknown_names() {
+        let mut selector = Selector::new();
+        assert_eq!(selector.get_selected(), None);
+        selector.add("zero", |_: &isize| 0).add("identity", |state: &isize| *state);
+        assert_eq!(selector.get_selected(), Some("zero"));
+        assert!(selector.select("identity"));
+        assert!(!selector.select("missing"));
+        assert_eq!(selector.get_selected(), Some("identity"));
+        assert_eq!(selector.get_names(), ["zero", "identity"]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,251 @@
+use crate::traits::problem::Problem;
+use crate::utils::graph::{Graph, VertexId};
+use crate::utils::rng::Rng;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"ALT1";
+
+pub enum Selection {
+    Random,
+    // Every landmark is the vertex farthest from the previous ones
+    Farthest,
+    // Leaf of the shortest path tree from a random vertex below which the current bound is worst
+    Avoid,
+}
+
+/*
+ *  ALT: distances from and to a few landmarks give lower bounds through the triangle inequality,
+ *  d(v, w) >= d(L, w) - d(L, v) and d(v, w) >= d(v, L) - d(w, L). Tables are tied to the vertex ids
+ *  of the graph they were computed on.
+ */
+
+pub struct Landmarks {
+    landmarks: Vec<VertexId>,
+    // Distances from and to every landmark, indexed by vertex id
+    from: Vec<Vec<Option<isize>>>,
+    to: Vec<Vec<Option<isize>>>,
+}
+
+impl Landmarks {
+    pub fn new<V: Eq + Hash>(graph: &Graph<V>, count: usize, selection: Selection, seed: u64) -> Self {
+        let mut landmarks = Landmarks { landmarks: Vec::new(), from: Vec::new(), to: Vec::new() };
+        let mut rng = Rng::new(seed);
+        while landmarks.landmarks.len() < count.min(graph.len()) {
+            let landmark = match selection {
+                Selection::Random => rng.next_below(graph.len()),
+                Selection::Farthest => landmarks.get_farthest(graph, &mut rng),
+                Selection::Avoid => {
+                    let landmark = landmarks.get_avoid(graph, &mut rng);
+                    // Every subtree already has a landmark
+                    if landmarks.landmarks.contains(&landmark) { landmarks.get_farthest(graph, &mut rng) } else { landmark }
+                },
+            };
+            if landmarks.landmarks.contains(&landmark) { continue; }
+            landmarks.landmarks.push(landmark);
+            landmarks.from.push(graph.get_distances(landmark, false));
+            landmarks.to.push(graph.get_distances(landmark, true));
+        }
+        landmarks
+    }
+
+    // Unreachable vertices come first, they are not covered yet
+    fn get_farthest<V: Eq + Hash>(&self, graph: &Graph<V>, rng: &mut Rng) -> VertexId {
+        if self.landmarks.is_empty() {
+            let start = rng.next_below(graph.len());
+            let distances = graph.get_distances(start, false);
+            return (0..graph.len()).max_by_key(|v| distances[*v].map(|d| d as usize).unwrap_or(usize::MAX)).unwrap()
+        }
+        (0..graph.len())
+            .filter(|v| !self.landmarks.contains(v))
+            .max_by_key(|v| self.from.iter().map(|from| from[*v].map(|d| d as usize).unwrap_or(usize::MAX)).min().unwrap())
+            .unwrap_or(0)
+    }
+
+    fn get_avoid<V: Eq + Hash>(&self, graph: &Graph<V>, rng: &mut Rng) -> VertexId {
+        let root = rng.next_below(graph.len());
+        let distances = graph.get_distances(root, false);
+        // Shortest path tree, children listed after their parent in `order`
+        let mut order: Vec<VertexId> = (0..graph.len()).filter(|v| distances[*v].is_some()).collect();
+        order.sort_by_key(|v| distances[*v]);
+        let mut parent: Vec<Option<VertexId>> = vec![None; graph.len()];
+        for v in &order {
+            for (next, cost) in graph.get_edges(*v) {
+                if parent[*next].is_none() && *next != root && distances[*next] == Some(distances[*v].unwrap() + cost) {
+                    parent[*next] = Some(*v);
+                }
+            }
+        }
+
+        // Error of the current bound summed over every subtree, zero for subtrees with a landmark
+        let mut size: Vec<isize> = order.iter().map(|v| distances[*v].unwrap() - self.get_heuristic(root, *v)).collect();
+        let position: Vec<usize> = {
+            let mut position = vec![0; graph.len()];
+            for (i, v) in order.iter().enumerate() { position[*v] = i; }
+            position
+        };
+        let mut blocked = vec![false; order.len()];
+        for i in (0..order.len()).rev() {
+            if self.landmarks.contains(&order[i]) { blocked[i] = true; }
+            if let Some(p) = parent[order[i]] {
+                size[position[p]] += size[i];
+                blocked[position[p]] |= blocked[i];
+            }
+        }
+        let mut current = root;
+        loop {
+            let child = order.iter()
+                .filter(|v| parent[**v] == Some(current) && !blocked[position[**v]])
+                .max_by_key(|v| size[position[**v]]);
+            match child {
+                Some(child) => current = *child,
+                None => return current,
+            }
+        }
+    }
+
+    pub fn get_landmarks(&self) -> &[VertexId] { &self.landmarks }
+
+    // Lower bound of the distance from `from` to `to`
+    pub fn get_heuristic(&self, from: VertexId, to: VertexId) -> isize {
+        let mut best = 0;
+        for (from_landmark, to_landmark) in self.from.iter().zip(&self.to) {
+            if let (Some(a), Some(b)) = (from_landmark[from], from_landmark[to]) { best = best.max(b - a); }
+            if let (Some(a), Some(b)) = (to_landmark[from], to_landmark[to]) { best = best.max(a - b); }
+        }
+        best
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let vertices = self.from.first().map(|from| from.len()).unwrap_or(0);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.landmarks.len() as u64).to_le_bytes())?;
+        writer.write_all(&(vertices as u64).to_le_bytes())?;
+        for landmark in &self.landmarks {
+            writer.write_all(&(*landmark as u64).to_le_bytes())?;
+        }
+        for distance in self.from.iter().chain(&self.to).flatten() {
+            writer.write_all(&(distance.unwrap_or(-1) as i64).to_le_bytes())?;
+        }
+        writer.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC { return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a landmarks file")) }
+        let mut read = || -> io::Result<i64> {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            Ok(i64::from_le_bytes(bytes))
+        };
+        let (count, vertices) = (read()? as usize, read()? as usize);
+        let landmarks = (0..count).map(|_| read().map(|id| id as VertexId)).collect::<io::Result<Vec<_>>>()?;
+        let mut tables = (0..2 * count)
+            .map(|_| (0..vertices).map(|_| read().map(|d| if d < 0 { None } else { Some(d as isize) })).collect())
+            .collect::<io::Result<Vec<Vec<_>>>>()?;
+        let to = tables.split_off(count);
+        Ok(Landmarks { landmarks, from: tables, to })
+    }
+}
+
+// Shortest path query on the graph with the landmark bound as heuristic
+pub struct LandmarkQuery<'a, V> {
+    graph: &'a Graph<V>,
+    landmarks: &'a Landmarks,
+    start: VertexId,
+    goal: VertexId,
+}
+
+impl<'a, V: Eq + Hash> LandmarkQuery<'a, V> {
+    pub fn new(graph: &'a Graph<V>, landmarks: &'a Landmarks, start: VertexId, goal: VertexId) -> Self {
+        LandmarkQuery { graph, landmarks, start, goal }
+    }
+}
+
+impl<'a, V: Eq + Hash> Problem for LandmarkQuery<'a, V> {
+    type State = VertexId;
+    // Vertex moved to
+    type Action = VertexId;
+
+    fn get_root(&self) -> VertexId { self.start }
+
+    fn get_successors(&self, state: &VertexId) -> Vec<(VertexId, VertexId, isize)> {
+        self.graph.get_edges(*state).iter().map(|(next, cost)| (*next, *next, *cost)).collect()
+    }
+
+    fn get_heuristic(&self, state: &VertexId) -> isize { self.landmarks.get_heuristic(*state, self.goal) }
+    fn is_solution(&self, state: &VertexId) -> bool { *state == self.goal }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::a_star::AStar;
+    use crate::traits::problem::ProblemAlgorithm;
+
+    fn random_graph(rng: &mut Rng, vertices: usize, edges: usize) -> Graph<usize> {
+        let mut graph = Graph::new();
+        for v in 0..vertices { graph.add_vertex(v); }
+        for _ in 0..edges {
+            let (from, to) = (rng.next_below(vertices), rng.next_below(vertices));
+            graph.add_edge(from, to, 1 + rng.next_below(9) as isize);
+        }
+        graph
+    }
+
+    #[test]
+    fn bounds_are_admissible() {
+        let mut rng = Rng::new(11);
+        let graph = random_graph(&mut rng, 40, 120);
+        for selection in [Selection::Random, Selection::Farthest, Selection::Avoid] {
+            let landmarks = Landmarks::new(&graph, 4, selection, 1);
+            assert_eq!(landmarks.get_landmarks().len(), 4);
+            for to in 0..graph.len() {
+                let distances = graph.get_distances(to, true);
+                for (from, distance) in distances.into_iter().enumerate() {
+                    if let Some(distance) = distance { assert!(landmarks.get_heuristic(from, to) <= distance); }
+                }
+            }
+
+            let mut a_star = AStar::new();
+            for (start, goal) in [(0, 39), (5, 17), (30, 2)] {
+                let query = LandmarkQuery::new(&graph, &landmarks, start, goal);
+                let cost = a_star.find_solution(&query).map(|node| a_star.get_arena().get(node).cost);
+                assert_eq!(cost, graph.get_distances(start, false)[goal]);
+            }
+        }
+    }
+
+    #[test]
+    fn farthest_starts_with_an_unreachable_vertex() {
+        // 0, 1 and 2 both ways, 3 only leads to 0
+        let mut graph = Graph::new();
+        for v in 0..4 { graph.add_vertex(v); }
+        for (from, to) in [(0, 1), (1, 0), (1, 2), (2, 1), (3, 0)] { graph.add_edge(from, to, 1); }
+        for seed in 0..20 {
+            let landmark = Landmarks::new(&graph, 1, Selection::Farthest, seed).get_landmarks()[0];
+            let start = Rng::new(seed).next_below(4);
+            assert_eq!(landmark, if start == 3 { 2 } else { 3 });
+        }
+    }
+
+    #[test]
+    fn loads_what_it_saves() {
+        let mut rng = Rng::new(2);
+        let graph = random_graph(&mut rng, 10, 30);
+        let landmarks = Landmarks::new(&graph, 2, Selection::Farthest, 0);
+        let path = std::env::temp_dir().join(format!("search-landmarks-test-{}.alt", std::process::id()));
+        landmarks.save(&path).unwrap();
+        let loaded = Landmarks::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get_landmarks(), landmarks.get_landmarks());
+        for (from, to) in (0..10).flat_map(|from| (0..10).map(move |to| (from, to))) {
+            assert_eq!(loaded.get_heuristic(from, to), landmarks.get_heuristic(from, to));
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,232 @@
+use crate::traits::{
+    problem::Problem,
+    p_tuple::PTuple
+};
+use std::collections::{BinaryHeap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// Rank of the abstract state of a concrete one
+pub type AbstractionFn<S> = Box<dyn Fn(&S) -> usize + Send + Sync>;
+
+const UNREACHABLE: u16 = u16::MAX;
+const MAGIC: &[u8; 4] = b"PDB1";
+
+/*
+ *  Distances to the goal in an abstract space, indexed by the rank of every abstract state
+ *  (a perfect hash in 0..size given by the user). Distances over u16::MAX - 1 are stored as
+ *  that, which keeps them admissible.
+ */
+
+pub struct PatternDatabase {
+    distances: Vec<u16>,
+}
+
+impl PatternDatabase {
+    // Forward sweep from the root of the abstract problem, then uniform-cost search back from its solutions
+    pub fn build<P: Problem, R: Fn(&P::State) -> usize>(problem: &P, size: usize, rank: R) -> Self {
+        let mut reached = vec![false; size];
+        // (successor, state, step cost) of every transition, sorted by successor
+        let mut edges: Vec<(usize, usize, isize)> = Vec::new();
+        let mut goals = Vec::new();
+        let root = problem.get_root();
+        reached[rank(&root)] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(state) = queue.pop_front() {
+            let id = rank(&state);
+            if problem.is_solution(&state) {
+                goals.push(id);
+                continue;
+            }
+            for (_, successor, step_cost) in problem.get_successors(&state) {
+                let successor_id = rank(&successor);
+                edges.push((successor_id, id, step_cost));
+                if !reached[successor_id] {
+                    reached[successor_id] = true;
+                    queue.push_back(successor);
+                }
+            }
+        }
+        edges.sort_unstable();
+
+        let mut costs = vec![isize::MAX; size];
+        let mut p_queue: BinaryHeap<PTuple<usize>> = BinaryHeap::new();
+        for id in goals {
+            costs[id] = 0;
+            p_queue.push(PTuple { priority: 0, node: id });
+        }
+        while let Some(PTuple { priority, node }) = p_queue.pop() {
+            if priority > costs[node] { continue; }
+            let start = edges.partition_point(|(successor, _, _)| *successor < node);
+            for &(_, id, step_cost) in edges[start..].iter().take_while(|(successor, _, _)| *successor == node) {
+                if costs[id] <= priority + step_cost { continue; }
+                costs[id] = priority + step_cost;
+                p_queue.push(PTuple { priority: costs[id], node: id });
+            }
+        }
+        Self::from_costs(costs)
+    }
+
+    // Uniform-cost search from the goals, successors of the abstract problem must also be its predecessors at the same cost
+    pub fn build_backward<P: Problem, R: Fn(&P::State) -> usize>(problem: &P, goals: Vec<P::State>, size: usize, rank: R) -> Self {
+        let mut costs = vec![isize::MAX; size];
+        let mut p_queue: BinaryHeap<PTuple<P::State>> = BinaryHeap::new();
+        for goal in goals {
+            costs[rank(&goal)] = 0;
+            p_queue.push(PTuple { priority: 0, node: goal });
+        }
+        while let Some(PTuple { priority, node }) = p_queue.pop() {
+            if priority > costs[rank(&node)] { continue; }
+            for (_, predecessor, step_cost) in problem.get_successors(&node) {
+                let id = rank(&predecessor);
+                if costs[id] <= priority + step_cost { continue; }
+                costs[id] = priority + step_cost;
+                p_queue.push(PTuple { priority: costs[id], node: predecessor });
+            }
+        }
+        Self::from_costs(costs)
+    }
+
+    fn from_costs(costs: Vec<isize>) -> Self {
+        let distances = costs.into_iter()
+            .map(|cost| if cost == isize::MAX { UNREACHABLE } else { cost.min(UNREACHABLE as isize - 1) as u16 })
+            .collect();
+        PatternDatabase { distances }
+    }
+
+    // None if the abstract state was not reached or cannot reach the goal
+    pub fn get_distance(&self, rank: usize) -> Option<isize> {
+        match self.distances[rank] {
+            UNREACHABLE => None,
+            distance => Some(distance as isize),
+        }
+    }
+
+    pub fn len(&self) -> usize { self.distances.len() }
+    pub fn is_empty(&self) -> bool { self.distances.is_empty() }
+    // Abstract states with a distance
+    pub fn get_reached(&self) -> usize { self.distances.iter().filter(|distance| **distance != UNREACHABLE).count() }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.distances.len() as u64).to_le_bytes())?;
+        for distance in &self.distances {
+            writer.write_all(&distance.to_le_bytes())?;
+        }
+        writer.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC { return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a pattern database")) }
+        let mut len = [0; 8];
+        reader.read_exact(&mut len)?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        // Compared in u128, a corrupted length can't overflow
+        if bytes.len() as u128 != 2 * u64::from_le_bytes(len) as u128 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Pattern database length mismatch"))
+        }
+        let distances = bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+        Ok(PatternDatabase { distances })
+    }
+}
+
+/*
+ *  Maximum over groups of pattern databases of the sum of every group. The sum is only admissible
+ *  if the abstract costs of a group are disjoint (ex: every tile move counted in a single pattern).
+ */
+
+pub struct PdbHeuristic<S> {
+    groups: Vec<Vec<(PatternDatabase, AbstractionFn<S>)>>,
+}
+
+impl<S> PdbHeuristic<S> {
+    pub fn new() -> Self {
+        PdbHeuristic { groups: Vec::new() }
+    }
+
+    pub fn add(&mut self, pdb: PatternDatabase, abstraction: AbstractionFn<S>) -> &mut Self {
+        self.groups.push(vec![(pdb, abstraction)]);
+        self
+    }
+
+    pub fn add_additive(&mut self, group: Vec<(PatternDatabase, AbstractionFn<S>)>) -> &mut Self {
+        self.groups.push(group);
+        self
+    }
+
+    // None if some abstract state cannot reach the goal, the state is a dead end
+    pub fn get_heuristic(&self, state: &S) -> Option<isize> {
+        let mut best = 0;
+        for group in &self.groups {
+            let mut sum = 0;
+            for (pdb, abstraction) in group {
+                sum += pdb.get_distance(abstraction(state))?;
+            }
+            best = best.max(sum);
+        }
+        Some(best)
+    }
+}
+
+impl<S> Default for PdbHeuristic<S> {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Line of 6 cells, steps of one cell both ways, the goal is cell 4
+    struct Line;
+
+    impl Problem for Line {
+        type State = usize;
+        type Action = ();
+
+        fn get_root(&self) -> usize { 0 }
+        fn get_successors(&self, state: &usize) -> Vec<((), usize, isize)> {
+            [state.wrapping_sub(1), state + 1].into_iter().filter(|next| *next < 6).map(|next| ((), next, 1)).collect()
+        }
+        fn get_heuristic(&self, _state: &usize) -> isize { 0 }
+        fn is_solution(&self, state: &usize) -> bool { *state == 4 }
+    }
+
+    fn get_distances(pdb: &PatternDatabase) -> Vec<Option<isize>> {
+        (0..pdb.len()).map(|rank| pdb.get_distance(rank)).collect()
+    }
+
+    #[test]
+    fn builds_both_ways() {
+        let expected = vec![Some(4), Some(3), Some(2), Some(1), Some(0), Some(1), None];
+        // Solutions are not expanded, cell 5 is only reached from the goal
+        let forward = PatternDatabase::build(&Line, 7, |state| *state);
+        assert_eq!(get_distances(&forward)[..5], expected[..5]);
+        assert_eq!(forward.get_reached(), 5);
+        let backward = PatternDatabase::build_backward(&Line, vec![4], 7, |state| *state);
+        assert_eq!(get_distances(&backward), expected);
+    }
+
+    #[test]
+    fn loads_what_it_saves_and_rejects_bad_lengths() {
+        let path = std::env::temp_dir().join(format!("search-pdb-test-{}.pdb", std::process::id()));
+        let pdb = PatternDatabase::build_backward(&Line, vec![4], 7, |state| *state);
+        pdb.save(&path).unwrap();
+        assert_eq!(get_distances(&PatternDatabase::load(&path).unwrap()), get_distances(&pdb));
+
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(PatternDatabase::load(&path).err().map(|error| error.kind()), Some(io::ErrorKind::InvalidData));
+        let mut huge = bytes[..4].to_vec();
+        huge.extend(u64::MAX.to_le_bytes());
+        fs::write(&path, &huge).unwrap();
+        assert_eq!(PatternDatabase::load(&path).err().map(|error| error.kind()), Some(io::ErrorKind::InvalidData));
+        fs::remove_file(&path).unwrap();
+    }
+}
\ No newline at end of file
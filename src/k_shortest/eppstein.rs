@@ -0,0 +1,314 @@
+use crate::traits::{
+    problem::Problem,
+    p_tuple::PTuple
+};
+use crate::utils::path::Path;
+use crate::utils::state_table::{StateId, StateTable};
+use super::yen::get_overlap;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+
+// Path from the root as the sidetracks taken off the shortest path tree, the last one links to the previous ones
+struct Sidetrack {
+    parent: Option<usize>,
+    // (state, index of the edge)
+    edge: Option<(StateId, usize)>,
+    cost: isize,
+}
+
+/*
+ *  Eppstein's algorithm over the explicit graph reachable from the root, which must be finite.
+ *  Every path is the shortest path tree towards the solutions (reverse Dijkstra) with a sequence
+ *  of sidetracks, each off the tree path the previous one leads to, and costs the tree path plus
+ *  the extra cost of its sidetracks. Paths are popped in cost order from a heap of sequences, the
+ *  children of a sequence add one more sidetrack.
+ *
+ *  Only loopless paths are returned, the ones revisiting a state are skipped but still extended.
+ *  Solutions are not expanded, paths end at the first one. With a maximum overlap, paths sharing
+ *  more than that fraction of their intermediate states with a returned one are skipped too. Every
+ *  skipped path counts against the number of paths enumerated.
+ */
+
+pub struct Eppstein<P: Problem> {
+    k: usize,
+    max_overlap: Option<f64>,
+    max_paths: usize,
+    states: StateTable<P::State>,
+    // (action, successor, step cost) of every state
+    edges: Vec<Vec<(P::Action, StateId, isize)>>,
+    // Cost to the closest solution and the edge towards it
+    distances: Vec<Option<isize>>,
+    tree: Vec<Option<usize>>,
+    enumerated: usize,
+}
+
+impl<P: Problem> Eppstein<P> where P::State: Clone {
+    pub fn new(k: usize) -> Self {
+        Eppstein {
+            k,
+            max_overlap: None,
+            max_paths: 1_000,
+            states: StateTable::new(),
+            edges: Vec::new(),
+            distances: Vec::new(),
+            tree: Vec::new(),
+            enumerated: 0,
+        }
+    }
+
+    pub fn set_max_overlap(&mut self, max_overlap: f64) -> &mut Self {
+        self.max_overlap = Some(max_overlap);
+        self
+    }
+
+    // Paths enumerated before giving up on finding k diverse ones
+    pub fn set_max_paths(&mut self, max_paths: usize) -> &mut Self {
+        self.max_paths = max_paths;
+        self
+    }
+
+    // Paths from the root to a solution in cost order
+    pub fn find_paths(&mut self, problem: &P) -> Vec<Path<P::State, P::Action>> {
+        self.explore(problem);
+        let mut paths: Vec<Path<P::State, P::Action>> = Vec::new();
+        let Some(cost) = self.distances[0] else { return paths };
+        let mut sidetracks = vec![Sidetrack { parent: None, edge: None, cost }];
+        let mut p_queue: BinaryHeap<PTuple<usize>> = BinaryHeap::new();
+        p_queue.push(PTuple { priority: cost, node: 0 });
+
+        while paths.len() < self.k && self.enumerated < self.max_paths {
+            let Some(PTuple { node, .. }) = p_queue.pop() else { break };
+            self.enumerated += 1;
+            let path = self.get_path(&sidetracks, node);
+            let diverse = path.as_ref().map(|path| {
+                self.max_overlap
+                    .map(|max_overlap| paths.iter().all(|other| get_overlap(&path.states, &other.states) <= max_overlap))
+                    .unwrap_or(true)
+            }).unwrap_or(false);
+
+            // One more sidetrack off the tree path from the head of the last one
+            let mut current = sidetracks[node].edge.map(|(state, edge)| self.edges[state][edge].1).unwrap_or(0);
+            loop {
+                let distance = self.distances[current].unwrap();
+                for (edge, (_, successor, step_cost)) in self.edges[current].iter().enumerate() {
+                    if self.tree[current] == Some(edge) { continue; }
+                    let Some(successor_distance) = self.distances[*successor] else { continue };
+                    let cost = sidetracks[node].cost + step_cost + successor_distance - distance;
+                    p_queue.push(PTuple { priority: cost, node: sidetracks.len() });
+                    sidetracks.push(Sidetrack { parent: Some(node), edge: Some((current, edge)), cost });
+                }
+                match self.tree[current] {
+                    Some(edge) => current = self.edges[current][edge].1,
+                    None => break,
+                }
+            }
+            if let (Some(path), true) = (path, diverse) { paths.push(path); }
+        }
+        paths
+    }
+
+    pub fn get_states(&self) -> &StateTable<P::State> { &self.states }
+    pub fn get_enumerated(&self) -> usize { self.enumerated }
+
+    // Reachable graph and the shortest path tree towards the solutions
+    fn explore(&mut self, problem: &P) {
+        self.states.clear();
+        self.edges.clear();
+        self.enumerated = 0;
+        let root = problem.get_root();
+        let hash = problem.get_state_hash(&root);
+        self.states.intern_with(root, hash);
+        self.edges.push(Vec::new());
+        let mut queue = VecDeque::from([0]);
+        let mut solutions = Vec::new();
+        while let Some(state) = queue.pop_front() {
+            if problem.is_solution(self.states.get(state)) {
+                solutions.push(state);
+                continue;
+            }
+            for (action, successor, step_cost) in problem.get_successors(self.states.get(state)) {
+                let hash = problem.get_state_hash(&successor);
+                let (successor, inserted) = self.states.intern_with(successor, hash);
+                if inserted {
+                    self.edges.push(Vec::new());
+                    queue.push_back(successor);
+                }
+                self.edges[state].push((action, successor, step_cost));
+            }
+        }
+
+        let mut reverse: Vec<Vec<(StateId, usize)>> = vec![Vec::new(); self.states.len()];
+        for (state, edges) in self.edges.iter().enumerate() {
+            for (edge, (_, successor, _)) in edges.iter().enumerate() {
+                reverse[*successor].push((state, edge));
+            }
+        }
+        self.distances = vec![None; self.states.len()];
+        self.tree = vec![None; self.states.len()];
+        let mut p_queue: BinaryHeap<PTuple<StateId>> = BinaryHeap::new();
+        for state in solutions {
+            self.distances[state] = Some(0);
+            p_queue.push(PTuple { priority: 0, node: state });
+        }
+        while let Some(PTuple { priority, node }) = p_queue.pop() {
+            if self.distances[node] != Some(priority) { continue; }
+            for &(state, edge) in &reverse[node] {
+                let distance = priority + self.edges[state][edge].2;
+                if self.distances[state].map(|current| current <= distance).unwrap_or(false) { continue; }
+                self.distances[state] = Some(distance);
+                self.tree[state] = Some(edge);
+                p_queue.push(PTuple { priority: distance, node: state });
+            }
+        }
+    }
+
+    // None if the path revisits a state
+    fn get_path(&self, sidetracks: &[Sidetrack], mut index: usize) -> Option<Path<P::State, P::Action>> {
+        let mut edges = Vec::new();
+        while let Some(parent) = sidetracks[index].parent {
+            edges.push(sidetracks[index].edge.unwrap());
+            index = parent;
+        }
+        edges.reverse();
+
+        let mut path = Path { states: vec![self.states.get(0).clone()], actions: Vec::new(), cost: 0 };
+        let mut visited = HashSet::from([0]);
+        let mut current = 0;
+        let mut take = |current: StateId, edge: usize| {
+            let (action, successor, step_cost) = &self.edges[current][edge];
+            path.states.push(self.states.get(*successor).clone());
+            path.actions.push(action.clone());
+            path.cost += step_cost;
+            visited.insert(*successor).then_some(*successor)
+        };
+        for (state, edge) in edges {
+            while current != state { current = take(current, self.tree[current].unwrap())?; }
+            current = take(current, edge)?;
+        }
+        while let Some(edge) = self.tree[current] { current = take(current, edge)?; }
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::k_shortest::yen::Yen;
+    use crate::traits::node::Node;
+    use std::rc::Rc;
+
+    // (from, to, cost), with cycles, from 0 to 5
+    const EDGES: [(usize, usize, isize); 13] = [
+        (0, 1, 2), (0, 2, 4), (1, 2, 1), (2, 1, 1), (1, 3, 5), (2, 3, 2), (2, 4, 6),
+        (3, 4, 1), (3, 5, 7), (4, 5, 2), (4, 3, 1), (1, 5, 12), (3, 0, 1),
+    ];
+
+    fn get_edges(from: usize) -> impl Iterator<Item = (usize, isize)> {
+        EDGES.iter().filter(move |edge| edge.0 == from).map(|(_, to, cost)| (*to, *cost))
+    }
+
+    struct Graph;
+
+    impl Problem for Graph {
+        type State = usize;
+        type Action = usize;
+
+        fn get_root(&self) -> usize { 0 }
+        fn get_successors(&self, state: &usize) -> Vec<(usize, usize, isize)> {
+            get_edges(*state).map(|(to, cost)| (to, to, cost)).collect()
+        }
+        fn get_heuristic(&self, _state: &usize) -> isize { 0 }
+        fn is_solution(&self, state: &usize) -> bool { *state == 5 }
+    }
+
+    #[derive(Clone)]
+    struct GraphNode { state: Rc<usize>, parent: Option<Rc<GraphNode>>, cost: isize }
+
+    impl Node for GraphNode {
+        type State = usize;
+        type Action = usize;
+
+        fn get_descendants(self) -> Vec<Box<Self>> {
+            let (state, cost) = (*self.state, self.cost);
+            let parent = Rc::new(self);
+            get_edges(state)
+                .map(|(to, step_cost)| Box::new(GraphNode { state: Rc::new(to), parent: Some(parent.clone()), cost: cost + step_cost }))
+                .collect()
+        }
+
+        fn get_state(&self) -> Rc<usize> { self.state.clone() }
+        fn get_action(&self) -> Option<usize> { self.parent.as_ref().map(|_| *self.state) }
+        fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+        fn get_cost(&self) -> isize { self.cost }
+        fn get_heuristic(&self) -> isize { 0 }
+        fn is_solution(&self) -> bool { *self.state == 5 }
+    }
+
+    // Costs of every loopless path ending at its first solution, cheapest first
+    fn get_all_costs() -> Vec<isize> {
+        fn visit(path: &mut Vec<usize>, cost: isize, costs: &mut Vec<isize>) {
+            let state = path[path.len() - 1];
+            if state == 5 { return costs.push(cost) }
+            for (to, step_cost) in get_edges(state) {
+                if path.contains(&to) { continue; }
+                path.push(to);
+                visit(path, cost + step_cost, costs);
+                path.pop();
+            }
+        }
+        let mut costs = Vec::new();
+        visit(&mut vec![0], 0, &mut costs);
+        costs.sort();
+        costs
+    }
+
+    fn assert_valid(states: &[usize], cost: isize) {
+        assert_eq!((states[0], states[states.len() - 1]), (0, 5));
+        let mut sorted = states.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), states.len(), "{:?} revisits a state", states);
+        let edges: isize = states.windows(2)
+            .map(|step| get_edges(step[0]).find(|(to, _)| *to == step[1]).unwrap().1)
+            .sum();
+        assert_eq!(edges, cost);
+    }
+
+    #[test]
+    fn eppstein_enumerates_loopless_paths_in_cost_order() {
+        let expected = get_all_costs();
+        let mut eppstein = Eppstein::new(expected.len() + 5);
+        for _ in 0..2 {
+            let paths = eppstein.find_paths(&Graph);
+            assert_eq!(paths.iter().map(|path| path.cost).collect::<Vec<_>>(), expected);
+            for path in &paths { assert_valid(&path.states, path.cost); }
+        }
+    }
+
+    #[test]
+    fn yen_matches_eppstein() {
+        let expected = get_all_costs();
+        let solutions = Yen::new(expected.len() + 5).find_solutions(GraphNode { state: Rc::new(0), parent: None, cost: 0 });
+        assert_eq!(solutions.iter().map(|solution| solution.get_cost()).collect::<Vec<_>>(), expected);
+        for solution in solutions {
+            let mut states = vec![*solution.state];
+            let mut parent = solution.get_parent();
+            while let Some(node) = parent {
+                states.push(*node.state);
+                parent = node.get_parent();
+            }
+            states.reverse();
+            assert_valid(&states, solution.get_cost());
+        }
+    }
+
+    #[test]
+    fn skips_overlapping_paths() {
+        let paths = Eppstein::new(3).set_max_overlap(0.0).find_paths(&Graph);
+        for (i, path) in paths.iter().enumerate() {
+            for other in &paths[..i] {
+                assert_eq!(get_overlap(&path.states, &other.states), 0.0);
+            }
+        }
+        assert_eq!(paths[0].cost, get_all_costs()[0]);
+    }
+}
\ No newline at end of file
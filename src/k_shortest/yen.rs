@@ -0,0 +1,153 @@
+use crate::traits::node::Node;
+use crate::traits::p_tuple::PTuple;
+use crate::utils::state_table::{StateId, StateTable};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+use std::rc::Rc;
+
+// Fraction of the intermediate states of `path` (all but the first and the last) that are also in `other`
+pub(crate) fn get_overlap<S: Eq + Hash>(path: &[S], other: &[S]) -> f64 {
+    if path.len() <= 2 { return 0.0 }
+    let other: HashSet<&S> = other.iter().collect();
+    let shared = path[1..path.len() - 1].iter().filter(|state| other.contains(state)).count();
+    shared as f64 / (path.len() - 2) as f64
+}
+
+// Nodes from the root to the solution
+fn get_path<T: Node>(solution: Rc<T>) -> Vec<Rc<T>> {
+    let mut path = vec![solution];
+    while let Some(parent) = path[path.len() - 1].get_parent() {
+        path.push(parent);
+    }
+    path.reverse();
+    path
+}
+
+/*
+ *  Yen's algorithm: the k cheapest loopless solutions in cost order. Every solution found branches
+ *  at each of its nodes into a spur search (A*) that may not revisit the states before the node nor
+ *  take the actions other solutions with the same beginning took there.
+ *
+ *  With a maximum overlap, solutions sharing more than that fraction of their intermediate states with
+ *  a returned one are skipped, up to a number of solutions enumerated.
+ */
+
+pub struct Yen<T: Node + Clone> where T::Action: PartialEq {
+    k: usize,
+    max_overlap: Option<f64>,
+    max_paths: usize,
+    states: StateTable<Rc<T::State>>,
+    searches: usize,
+    expanded: usize,
+}
+
+impl<T: Node + Clone> Yen<T> where T::Action: PartialEq {
+    pub fn new(k: usize) -> Self {
+        Yen {
+            k,
+            max_overlap: None,
+            max_paths: 1_000,
+            states: StateTable::new(),
+            searches: 0,
+            expanded: 0,
+        }
+    }
+
+    pub fn set_max_overlap(&mut self, max_overlap: f64) -> &mut Self {
+        self.max_overlap = Some(max_overlap);
+        self
+    }
+
+    // Solutions enumerated before giving up on finding k diverse ones
+    pub fn set_max_paths(&mut self, max_paths: usize) -> &mut Self {
+        self.max_paths = max_paths;
+        self
+    }
+
+    // Solution nodes in cost order, their parents give the full paths
+    pub fn find_solutions(&mut self, root: T) -> Vec<Box<T>> {
+        let mut solutions: Vec<Box<T>> = Vec::new();
+        let mut returned: Vec<Vec<Rc<T::State>>> = Vec::new();
+        let mut paths: Vec<Vec<Rc<T>>> = Vec::new();
+        let mut candidates: BinaryHeap<PTuple<Box<T>>> = BinaryHeap::new();
+        let mut seen: Vec<Vec<T::Action>> = Vec::new();
+
+        if let Some(solution) = self.search(root, &HashSet::new(), &[]) {
+            seen.push(get_actions(&get_path(Rc::new((*solution).clone()))));
+            candidates.push(PTuple { priority: solution.get_cost(), node: solution });
+        }
+        while solutions.len() < self.k && paths.len() < self.max_paths {
+            let solution = match candidates.pop() {
+                Some(PTuple { node, .. }) => node,
+                None => break,
+            };
+            let path = get_path(Rc::new((*solution).clone()));
+            let states: Vec<Rc<T::State>> = path.iter().map(|node| node.get_state()).collect();
+            let diverse = self.max_overlap
+                .map(|max_overlap| returned.iter().all(|other| get_overlap(&states, other) <= max_overlap))
+                .unwrap_or(true);
+            if diverse {
+                returned.push(states.clone());
+                solutions.push(solution);
+            }
+
+            // Spur searches from every node but the solution
+            let actions = get_actions(&path);
+            for i in 0..path.len() - 1 {
+                let excluded: HashSet<StateId> = path[..=i].iter()
+                    .map(|node| self.states.intern_with(node.get_state(), node.get_state_hash()).0)
+                    .collect();
+                let taken: Vec<T::Action> = paths.iter().chain([&path])
+                    .filter(|other| other.len() > i + 1 && get_actions(&other[..=i]) == actions[..i])
+                    .filter_map(|other| other[i + 1].get_action())
+                    .collect();
+                let spur = (*path[i]).clone();
+                if let Some(candidate) = self.search(spur, &excluded, &taken) {
+                    let candidate_actions = get_actions(&get_path(Rc::new((*candidate).clone())));
+                    if seen.contains(&candidate_actions) { continue; }
+                    seen.push(candidate_actions);
+                    candidates.push(PTuple { priority: candidate.get_cost(), node: candidate });
+                }
+            }
+            paths.push(path);
+        }
+        solutions
+    }
+
+    pub fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+    // Spur searches run and nodes they expanded
+    pub fn get_searches(&self) -> usize { self.searches }
+    pub fn get_expanded(&self) -> usize { self.expanded }
+
+    // A* from the spur node avoiding the excluded states, and the taken actions from the spur node
+    fn search(&mut self, spur: T, excluded: &HashSet<StateId>, taken: &[T::Action]) -> Option<Box<T>> {
+        self.searches += 1;
+        let mut costs: HashMap<StateId, isize> = HashMap::new();
+        let mut p_queue: BinaryHeap<PTuple<Box<T>>> = BinaryHeap::new();
+        let spur_state = self.states.intern_with(spur.get_state(), spur.get_state_hash()).0;
+        costs.insert(spur_state, spur.get_cost());
+        p_queue.push(PTuple { priority: spur.get_cost() + spur.get_heuristic(), node: Box::new(spur) });
+
+        let mut first = true;
+        while let Some(PTuple { node, .. }) = p_queue.pop() {
+            if node.is_solution() { return Some(node) }
+            let id = self.states.intern_with(node.get_state(), node.get_state_hash()).0;
+            if costs.get(&id).map(|cost| *cost < node.get_cost()).unwrap_or(false) { continue; }
+            self.expanded += 1;
+            for descendant in node.get_descendants() {
+                if first && descendant.get_action().map(|action| taken.contains(&action)).unwrap_or(false) { continue; }
+                let (id, _) = self.states.intern_with(descendant.get_state(), descendant.get_state_hash());
+                if excluded.contains(&id) { continue; }
+                if costs.get(&id).map(|cost| *cost <= descendant.get_cost()).unwrap_or(false) { continue; }
+                costs.insert(id, descendant.get_cost());
+                p_queue.push(PTuple { priority: descendant.get_cost() + descendant.get_heuristic(), node: descendant });
+            }
+            first = false;
+        }
+        None
+    }
+}
+
+fn get_actions<T: Node>(path: &[Rc<T>]) -> Vec<T::Action> {
+    path.iter().filter_map(|node| node.get_action()).collect()
+}
\ No newline at end of file
@@ -2,10 +2,91 @@ pub mod traits {
     pub mod node;
     pub mod algorithm;
     pub mod p_tuple;
+    pub mod problem;
+    pub mod game;
+    pub mod and_or;
+    pub mod mapf;
+    pub mod multi_objective;
+    pub mod resources;
+    pub mod heuristic;
+}
+
+pub mod utils {
+    pub mod state_table;
+    pub mod arena;
+    pub mod rng;
+    pub mod zobrist;
+    pub mod memory;
+    pub mod path;
+    pub mod policy;
+    pub mod focal;
+    pub mod reservation;
+    pub mod graph;
+    pub mod validation;
+    pub mod search_tree;
+    #[cfg(test)]
+    pub mod fixtures;
 }
 
 pub mod bfs;
 pub mod dfs;
 pub mod dijkstra;
 pub mod a_star;
-pub mod dfbnb;
\ No newline at end of file
+pub mod dfbnb;
+pub mod mcts;
+pub mod genetic;
+pub mod portfolio;
+pub mod space_time;
+pub mod multi_objective;
+pub mod resource_constrained;
+pub mod contraction;
+
+pub mod arena {
+    pub mod bfs;
+    pub mod dijkstra;
+    pub mod a_star;
+    pub mod dfbnb;
+}
+
+pub mod parallel {
+    pub mod hda_star;
+    pub mod bfs;
+}
+
+pub mod local {
+    pub mod hill_climbing;
+    pub mod simulated_annealing;
+    pub mod tabu;
+}
+
+pub mod games {
+    pub mod minimax;
+    pub mod alpha_beta;
+    pub mod expectimax;
+    pub mod mcts;
+}
+pub mod and_or {
+    pub mod search;
+    pub mod ao_star;
+}
+pub mod csp {
+    pub mod problem;
+    pub mod solver;
+}
+
+pub mod mapf {
+    pub mod agents;
+    pub mod cbs;
+}
+
+pub mod k_shortest {
+    pub mod yen;
+    pub mod eppstein;
+}
+
+pub mod heuristics {
+    pub mod pdb;
+    pub mod landmarks;
+    pub mod combinators;
+    pub mod checker;
+}
\ No newline at end of file
@@ -0,0 +1,227 @@
+use crate::traits::node::Node;
+use crate::traits::algorithm::{Algorithm, AlgorithmExt};
+use crate::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use crate::utils::rng::Rng;
+use crate::utils::state_table::StateTable;
+use std::mem::size_of;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HillClimbingMode {
+    // Moves to the descendant with the lowest heuristic, ties broken at random
+    Steepest,
+    // Moves to the first improving descendant in random order
+    FirstChoice,
+}
+
+/*
+ *  Descends on `get_heuristic()` from the root until a solution is reached or no descendant improves,
+ *  then restarts from a random state: a walk of up to `restart_walk` random descendants from the root.
+ *  Every climb may end in a different solution, the cheapest is returned and climbs that can no longer
+ *  beat it are abandoned.
+ */
+
+pub struct HillClimbing<T: Node + Clone> {
+    mode: HillClimbingMode,
+    restarts: usize,
+    // Longest random walk from the root before a restart climbs
+    restart_walk: usize,
+    // Consecutive moves to a descendant with the same heuristic
+    sideways: usize,
+    rng: Rng,
+    states: StateTable<Rc<T::State>>,
+    // Nodes on the current climb and descendants of the last step
+    depth: usize,
+    pending: usize,
+    steps: usize,
+    climbs: usize,
+    memory: MemoryBudget<T::State>,
+}
+
+impl<T: Node + Clone> HillClimbing<T> {
+    pub fn new(mode: HillClimbingMode) -> Self {
+        HillClimbing {
+            mode,
+            restarts: 10,
+            restart_walk: 20,
+            sideways: 100,
+            rng: Rng::new(0),
+            states: StateTable::new(),
+            depth: 0,
+            pending: 0,
+            steps: 0,
+            climbs: 0,
+            memory: MemoryBudget::new(),
+        }
+    }
+
+    pub fn set_restarts(&mut self, restarts: usize) -> &mut Self {
+        self.restarts = restarts;
+        self
+    }
+
+    // 0 restarts every climb from the root
+    pub fn set_restart_walk(&mut self, steps: usize) -> &mut Self {
+        self.restart_walk = steps;
+        self
+    }
+
+    pub fn set_sideways(&mut self, sideways: usize) -> &mut Self {
+        self.sideways = sideways;
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    pub fn get_steps(&self) -> usize { self.steps }
+    pub fn get_climbs(&self) -> usize { self.climbs }
+
+    // Random descendants from the root, stops early at a solution or a node without descendants
+    fn walk(&mut self, root: &T) -> Box<T> {
+        let mut node = Box::new(root.clone());
+        for _ in 0..self.rng.next_below(self.restart_walk + 1) {
+            if node.is_solution() { break; }
+            let mut descendants = node.clone().get_descendants();
+            if descendants.is_empty() { break; }
+            node = descendants.swap_remove(self.rng.next_below(descendants.len()));
+        }
+        node
+    }
+
+    fn climb(&mut self, mut node: Box<T>, mut best: Option<Box<T>>) -> Option<Box<T>> {
+        let mut sideways = 0;
+        self.depth = 0;
+        loop {
+            let (id, inserted) = self.states.intern_with(node.get_state(), node.get_state_hash());
+            if inserted { self.memory.add_state(self.states.get(id)); }
+            if node.is_solution() {
+                if best.as_ref().map(|best| node.get_cost() < best.get_cost()).unwrap_or(true) { best = Some(node); }
+                return best
+            }
+            if self.memory.check(&self.get_memory_usage()) { return best }
+
+            let heuristic = node.get_heuristic();
+            let mut descendants = node.get_descendants();
+            if let Some(best) = &best {
+                descendants.retain(|descendant| descendant.get_cost() < best.get_cost());
+            }
+            self.rng.shuffle(&mut descendants);
+            self.steps += 1;
+            self.depth += 1;
+            self.pending = descendants.len();
+
+            let next = match self.mode {
+                HillClimbingMode::Steepest => descendants.iter()
+                    .enumerate()
+                    .min_by_key(|(_, descendant)| descendant.get_heuristic())
+                    .map(|(i, _)| i),
+                HillClimbingMode::FirstChoice => descendants.iter()
+                    .position(|descendant| descendant.get_heuristic() < heuristic)
+                    .or_else(|| descendants.iter().position(|descendant| descendant.get_heuristic() == heuristic)),
+            };
+            let next = match next {
+                Some(i) => descendants.swap_remove(i),
+                None => return best,
+            };
+
+            if next.get_heuristic() > heuristic { return best }
+            if next.get_heuristic() == heuristic {
+                if sideways == self.sideways { return best }
+                sideways += 1;
+            } else {
+                sideways = 0;
+            }
+            node = next;
+        }
+    }
+}
+
+impl<T: Node + Clone> Algorithm<T> for HillClimbing<T> {
+    fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        self.memory.reset();
+        self.states.clear();
+        self.depth = 0;
+        self.pending = 0;
+        self.steps = 0;
+        self.climbs = 0;
+        let mut best = None;
+        for restart in 0..=self.restarts {
+            self.climbs += 1;
+            let start = if restart == 0 { root.clone() } else { self.walk(&root) };
+            best = self.climb(start, best);
+            if self.memory.is_reached() { break; }
+        }
+        best
+    }
+
+    fn get_visited(&self) -> Vec<&Rc<T::State>> {
+        self.states.iter().collect()
+    }
+}
+
+impl<T: Node + Clone> AlgorithmExt<T> for HillClimbing<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.pending * size_of::<Box<T>>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.pending + self.depth + 1) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The root has 5 improving descendants without descendants of their own, only the last is a solution
+    #[derive(Clone)]
+    struct FanNode { state: Rc<usize>, parent: Option<Rc<FanNode>> }
+
+    impl Node for FanNode {
+        type State = usize;
+        type Action = usize;
+
+        fn get_descendants(self) -> Vec<Box<Self>> {
+            if *self.state != 0 { return Vec::new() }
+            let parent = Rc::new(self);
+            (1..=5).map(|state| Box::new(FanNode { state: Rc::new(state), parent: Some(parent.clone()) })).collect()
+        }
+
+        fn get_state(&self) -> Rc<usize> { self.state.clone() }
+        fn get_action(&self) -> Option<usize> { self.parent.as_ref().map(|_| *self.state) }
+        fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+        fn get_cost(&self) -> isize { *self.state as isize }
+        fn get_heuristic(&self) -> isize { [10, 9, 8, 7, 6, 0][*self.state] }
+        fn is_solution(&self) -> bool { *self.state == 5 }
+    }
+
+    fn climb(mode: HillClimbingMode, seed: u64) -> Option<isize> {
+        let mut climbing = HillClimbing::new(mode);
+        climbing.set_restarts(0).set_seed(seed);
+        let solution = climbing.find_solution(Box::new(FanNode { state: Rc::new(0), parent: None }));
+        assert_eq!(climbing.get_climbs(), 1);
+        solution.map(|solution| solution.get_cost())
+    }
+
+    #[test]
+    fn steepest_takes_the_best_descendant() {
+        assert!((0..20).all(|seed| climb(HillClimbingMode::Steepest, seed) == Some(5)));
+    }
+
+    #[test]
+    fn first_choice_takes_any_improving_descendant() {
+        let solutions: Vec<Option<isize>> = (0..20).map(|seed| climb(HillClimbingMode::FirstChoice, seed)).collect();
+        assert!(solutions.contains(&Some(5)));
+        assert!(solutions.contains(&None));
+    }
+}
\ No newline at end of file
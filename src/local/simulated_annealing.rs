@@ -0,0 +1,199 @@
+use crate::traits::node::Node;
+use crate::traits::algorithm::{Algorithm, AlgorithmExt};
+use crate::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use crate::utils::rng::Rng;
+use crate::utils::state_table::StateTable;
+use std::mem::size_of;
+use std::rc::Rc;
+
+// Temperature at the given step, the search stops once it is not positive
+pub type ScheduleFn = Box<dyn Fn(usize) -> f64>;
+
+// initial * alpha ^ step
+pub fn exponential(initial: f64, alpha: f64) -> ScheduleFn {
+    Box::new(move |step| initial * alpha.powf(step as f64))
+}
+
+// Reaches 0 after `steps` and stays there, 0 steps is 0 from the start
+pub fn linear(initial: f64, steps: usize) -> ScheduleFn {
+    Box::new(move |step| if step >= steps { 0.0 } else { initial * (1.0 - step as f64 / steps as f64) })
+}
+
+// initial / ln(step + 2)
+pub fn logarithmic(initial: f64) -> ScheduleFn {
+    Box::new(move |step| initial / ((step + 2) as f64).ln())
+}
+
+// Descendants of the node followed by its parent, flagged as true
+pub(crate) fn get_neighbours<T: Node + Clone>(node: &T) -> Vec<(Box<T>, bool)> {
+    let mut neighbours: Vec<(Box<T>, bool)> = node.clone().get_descendants().into_iter()
+        .map(|descendant| (descendant, false))
+        .collect();
+    if let Some(parent) = node.get_parent() {
+        neighbours.push((Box::new((*parent).clone()), true));
+    }
+    neighbours
+}
+
+// Weights over 1 make moves toward a solution downhill, solutions are compared by cost
+pub(crate) fn get_energy<T: Node>(node: &T, weight: f64) -> f64 {
+    node.get_cost() as f64 + weight * node.get_heuristic() as f64
+}
+
+/*
+ *  Random walk over partial paths minimizing `get_cost() + weight * get_heuristic()`: a random descendant or
+ *  the parent is taken if it is not worse, or with probability exp(-delta / temperature) otherwise.
+ *  Going back to the parent lets the walk leave dead ends and try cheaper paths after a solution.
+ */
+
+pub struct SimulatedAnnealing<T: Node + Clone> {
+    schedule: ScheduleFn,
+    weight: f64,
+    steps: usize,
+    rng: Rng,
+    states: StateTable<Rc<T::State>>,
+    // Nodes on the current path below the start and its neighbours, moving above the start counts as 0
+    depth: usize,
+    pending: usize,
+    completed: usize,
+    memory: MemoryBudget<T::State>,
+}
+
+impl<T: Node + Clone> SimulatedAnnealing<T> {
+    pub fn new() -> Self {
+        SimulatedAnnealing {
+            schedule: exponential(100.0, 0.9999),
+            weight: 2.0,
+            steps: 100_000,
+            rng: Rng::new(0),
+            states: StateTable::new(),
+            depth: 0,
+            pending: 0,
+            completed: 0,
+            memory: MemoryBudget::new(),
+        }
+    }
+
+    pub fn set_schedule(&mut self, schedule: ScheduleFn) -> &mut Self {
+        self.schedule = schedule;
+        self
+    }
+
+    pub fn set_weight(&mut self, weight: f64) -> &mut Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn set_steps(&mut self, steps: usize) -> &mut Self {
+        self.steps = steps;
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    pub fn get_steps(&self) -> usize { self.completed }
+
+    fn visit(&mut self, node: &T) {
+        let (id, inserted) = self.states.intern_with(node.get_state(), node.get_state_hash());
+        if inserted { self.memory.add_state(self.states.get(id)); }
+    }
+}
+
+impl<T: Node + Clone> Default for SimulatedAnnealing<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Node + Clone> Algorithm<T> for SimulatedAnnealing<T> {
+    fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        self.memory.reset();
+        self.states.clear();
+        self.depth = 0;
+        self.pending = 0;
+        self.completed = 0;
+        self.visit(&root);
+        let mut best = if root.is_solution() { Some(root.clone()) } else { None };
+        let mut energy = get_energy(&*root, self.weight);
+        let mut neighbours = get_neighbours(&*root);
+
+        for step in 0..self.steps {
+            let temperature = (self.schedule)(step);
+            if temperature <= 0.0 || neighbours.is_empty() { break; }
+            self.pending = neighbours.len();
+            if self.memory.check(&self.get_memory_usage()) { break; }
+            self.completed += 1;
+
+            let i = self.rng.next_below(neighbours.len());
+            let delta = get_energy(&*neighbours[i].0, self.weight) - energy;
+            if delta > 0.0 && self.rng.next_f64() >= (-delta / temperature).exp() { continue; }
+
+            let (current, is_parent) = neighbours.swap_remove(i);
+            if is_parent { self.depth = self.depth.saturating_sub(1) } else { self.depth += 1 }
+            self.visit(&current);
+            if current.is_solution() && best.as_ref().map(|best| current.get_cost() < best.get_cost()).unwrap_or(true) {
+                best = Some(current.clone());
+            }
+            energy = get_energy(&*current, self.weight);
+            neighbours = get_neighbours(&*current);
+        }
+        best
+    }
+
+    fn get_visited(&self) -> Vec<&Rc<T::State>> {
+        self.states.iter().collect()
+    }
+}
+
+impl<T: Node + Clone> AlgorithmExt<T> for SimulatedAnnealing<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.pending * size_of::<(Box<T>, bool)>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.pending + self.depth + 1) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::GridNode;
+
+    #[test]
+    fn schedules_cool_down_monotonically() {
+        for schedule in [exponential(100.0, 0.99), linear(100.0, 50), logarithmic(100.0)] {
+            let temperatures: Vec<f64> = (0..100).map(&schedule).collect();
+            assert!(temperatures.iter().all(|temperature| !temperature.is_nan() && *temperature >= 0.0));
+            assert!(temperatures.windows(2).all(|pair| pair[1] <= pair[0]));
+        }
+        let schedule = linear(100.0, 50);
+        assert_eq!(schedule(0), 100.0);
+        assert_eq!(schedule(25), 50.0);
+        assert!((50..100).all(|step| schedule(step) == 0.0));
+        assert_eq!(linear(100.0, 0)(0), 0.0);
+    }
+
+    #[test]
+    fn every_schedule_reaches_the_goal() {
+        for schedule in [exponential(100.0, 0.999), linear(100.0, 5_000), logarithmic(100.0)] {
+            let mut annealing = SimulatedAnnealing::new();
+            annealing.set_schedule(schedule).set_steps(5_000).set_seed(3);
+            assert!(annealing.find_solution(GridNode::root()).is_some());
+            assert!(annealing.get_steps() > 0);
+        }
+
+        let mut annealing = SimulatedAnnealing::new();
+        annealing.set_schedule(linear(100.0, 0));
+        assert!(annealing.find_solution(GridNode::root()).is_none());
+        assert_eq!(annealing.get_steps(), 0);
+    }
+}
\ No newline at end of file
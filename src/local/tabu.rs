@@ -0,0 +1,265 @@
+use crate::traits::node::Node;
+use crate::traits::algorithm::{Algorithm, AlgorithmExt};
+use crate::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use crate::utils::rng::Rng;
+use crate::utils::state_table::{StateId, StateTable};
+use super::simulated_annealing::{get_energy, get_neighbours};
+use std::mem::size_of;
+use std::rc::Rc;
+
+/*
+ *  Always moves to the best neighbour (descendants and parent) by `get_cost() + weight * get_heuristic()`,
+ *  even if it is worse, unless its state was visited in the last `tenure` moves. A tabu neighbour is
+ *  still taken if it is a solution cheaper than the best one (aspiration). When every neighbour is tabu
+ *  the one visited longest ago is taken, which backtracks along the current path.
+ */
+
+pub struct TabuSearch<T: Node + Clone> {
+    tenure: usize,
+    weight: f64,
+    steps: usize,
+    rng: Rng,
+    states: StateTable<Rc<T::State>>,
+    // Step at which every state stops being tabu, indexed by state id
+    tabu_until: Vec<usize>,
+    depth: usize,
+    pending: usize,
+    completed: usize,
+    memory: MemoryBudget<T::State>,
+}
+
+impl<T: Node + Clone> TabuSearch<T> {
+    pub fn new() -> Self {
+        TabuSearch {
+            tenure: 100,
+            weight: 2.0,
+            steps: 10_000,
+            rng: Rng::new(0),
+            states: StateTable::new(),
+            tabu_until: Vec::new(),
+            depth: 0,
+            pending: 0,
+            completed: 0,
+            memory: MemoryBudget::new(),
+        }
+    }
+
+    pub fn set_tenure(&mut self, tenure: usize) -> &mut Self {
+        self.tenure = tenure;
+        self
+    }
+
+    pub fn set_weight(&mut self, weight: f64) -> &mut Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn set_steps(&mut self, steps: usize) -> &mut Self {
+        self.steps = steps;
+        self
+    }
+
+    // Breaks ties between neighbours
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    pub fn get_steps(&self) -> usize { self.completed }
+
+    fn intern(&mut self, node: &T) -> StateId {
+        let (id, inserted) = self.states.intern_with(node.get_state(), node.get_state_hash());
+        if inserted {
+            self.tabu_until.push(0);
+            self.memory.add_state(self.states.get(id));
+        }
+        id
+    }
+
+    fn make_tabu(&mut self, id: StateId) {
+        self.tabu_until[id] = self.completed + self.tenure;
+    }
+}
+
+impl<T: Node + Clone> Default for TabuSearch<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Node + Clone> Algorithm<T> for TabuSearch<T> {
+    fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        self.memory.reset();
+        self.states.clear();
+        self.tabu_until.clear();
+        self.depth = 0;
+        self.pending = 0;
+        self.completed = 0;
+        let id = self.intern(&root);
+        self.make_tabu(id);
+        let mut best = if root.is_solution() { Some(root.clone()) } else { None };
+        let mut current = root;
+
+        for _ in 0..self.steps {
+            let mut neighbours = get_neighbours(&*current);
+            self.rng.shuffle(&mut neighbours);
+            self.pending = neighbours.len();
+            if self.memory.check(&self.get_memory_usage()) { break; }
+            self.completed += 1;
+
+            // Best allowed neighbour, and the tabu neighbour visited longest ago
+            let mut chosen: Option<(usize, StateId, f64)> = None;
+            let mut oldest: Option<(usize, StateId, usize)> = None;
+            for (i, (neighbour, _)) in neighbours.iter().enumerate() {
+                let id = self.intern(neighbour);
+                let aspiration = neighbour.is_solution()
+                    && best.as_ref().map(|best| neighbour.get_cost() < best.get_cost()).unwrap_or(true);
+                if self.tabu_until[id] > self.completed && !aspiration {
+                    if oldest.map(|(_, _, until)| self.tabu_until[id] < until).unwrap_or(true) {
+                        oldest = Some((i, id, self.tabu_until[id]));
+                    }
+                    continue;
+                }
+                let energy = get_energy(&**neighbour, self.weight);
+                if chosen.map(|(_, _, best)| energy < best).unwrap_or(true) { chosen = Some((i, id, energy)); }
+            }
+            let (i, id) = match (chosen, oldest) {
+                (Some((i, id, _)), _) | (None, Some((i, id, _))) => (i, id),
+                (None, None) => break,
+            };
+
+            let (next, is_parent) = neighbours.swap_remove(i);
+            if is_parent { self.depth = self.depth.saturating_sub(1) } else { self.depth += 1 }
+            self.make_tabu(id);
+            if next.is_solution() && best.as_ref().map(|best| next.get_cost() < best.get_cost()).unwrap_or(true) {
+                best = Some(next.clone());
+            }
+            current = next;
+        }
+        best
+    }
+
+    fn get_visited(&self) -> Vec<&Rc<T::State>> {
+        self.states.iter().collect()
+    }
+}
+
+impl<T: Node + Clone> AlgorithmExt<T> for TabuSearch<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: self.pending * size_of::<(Box<T>, bool)>(),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.tabu_until.capacity() * size_of::<usize>()
+                + self.memory.get_state_bytes(),
+            nodes: (self.pending + self.depth + 1) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::hill_climbing::{HillClimbing, HillClimbingMode};
+    use crate::local::simulated_annealing::SimulatedAnnealing;
+
+    // Cells 0 to 9 on a line, moving costs 1 either way and the goal is 9
+    #[derive(Clone)]
+    struct LineNode { state: Rc<usize>, parent: Option<Rc<LineNode>>, cost: isize }
+
+    impl LineNode {
+        fn root() -> Box<Self> { Box::new(LineNode { state: Rc::new(0), parent: None, cost: 0 }) }
+    }
+
+    impl Node for LineNode {
+        type State = usize;
+        type Action = usize;
+
+        fn get_descendants(self) -> Vec<Box<Self>> {
+            let (state, cost) = (*self.state, self.cost);
+            let parent = Rc::new(self);
+            [state.wrapping_sub(1), state + 1].into_iter()
+                .filter(|next| *next < 10)
+                .map(|next| Box::new(LineNode { state: Rc::new(next), parent: Some(parent.clone()), cost: cost + 1 }))
+                .collect()
+        }
+
+        fn get_state(&self) -> Rc<usize> { self.state.clone() }
+        fn get_action(&self) -> Option<usize> { self.parent.as_ref().map(|_| *self.state) }
+        fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+        fn get_cost(&self) -> isize { self.cost }
+        fn get_heuristic(&self) -> isize { 9 - *self.state as isize }
+        fn is_solution(&self) -> bool { *self.state == 9 }
+    }
+
+    #[test]
+    fn counters_start_over_on_every_search() {
+        let mut tabu = TabuSearch::new();
+        tabu.set_steps(50).set_tenure(5);
+        for _ in 0..2 {
+            tabu.set_seed(1);
+            assert_eq!(tabu.find_solution(LineNode::root()).map(|solution| solution.get_cost()), Some(9));
+            assert_eq!(tabu.get_steps(), 50);
+            assert_eq!(tabu.get_states().len(), 10);
+        }
+
+        let mut annealing = SimulatedAnnealing::new();
+        annealing.set_steps(200);
+        let mut steps = Vec::new();
+        for _ in 0..2 {
+            annealing.set_seed(1);
+            annealing.find_solution(LineNode::root());
+            steps.push(annealing.get_steps());
+        }
+        assert_eq!(steps[0], steps[1]);
+
+        let mut climbing = HillClimbing::new(HillClimbingMode::Steepest);
+        climbing.set_restarts(2);
+        let mut counts = Vec::new();
+        for _ in 0..2 {
+            climbing.set_seed(1);
+            assert_eq!(climbing.find_solution(LineNode::root()).map(|solution| solution.get_cost()), Some(9));
+            counts.push((climbing.get_climbs(), climbing.get_steps()));
+        }
+        assert_eq!(counts[0].0, 3);
+        assert_eq!(counts[0], counts[1]);
+    }
+    // 0 reaches the goal 2 directly for 10 or through 1 for 2, 3 is a dead end next to 1
+    const SHORTCUT: [(usize, usize, isize); 4] = [(0, 1, 1), (0, 2, 10), (1, 2, 1), (1, 3, 1)];
+
+    #[derive(Clone)]
+    struct ShortcutNode { state: Rc<usize>, parent: Option<Rc<ShortcutNode>>, cost: isize }
+
+    impl Node for ShortcutNode {
+        type State = usize;
+        type Action = usize;
+
+        fn get_descendants(self) -> Vec<Box<Self>> {
+            let (state, cost) = (*self.state, self.cost);
+            let parent = Rc::new(self);
+            SHORTCUT.iter().filter(|edge| edge.0 == state)
+                .map(|(_, to, step)| Box::new(ShortcutNode { state: Rc::new(*to), parent: Some(parent.clone()), cost: cost + step }))
+                .collect()
+        }
+
+        fn get_state(&self) -> Rc<usize> { self.state.clone() }
+        fn get_action(&self) -> Option<usize> { self.parent.as_ref().map(|_| *self.state) }
+        fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+        fn get_cost(&self) -> isize { self.cost }
+        fn get_heuristic(&self) -> isize { [10, 10, 0, 5][*self.state] }
+        fn is_solution(&self) -> bool { *self.state == 2 }
+    }
+
+    #[test]
+    fn aspiration_takes_a_tabu_solution_when_it_is_cheaper() {
+        // 2 for 10, back to 0, 1, then 2 again for 2 although it is tabu and 3 is not
+        let mut tabu = TabuSearch::new();
+        tabu.set_steps(4).set_tenure(100);
+        let root = Box::new(ShortcutNode { state: Rc::new(0), parent: None, cost: 0 });
+        assert_eq!(tabu.find_solution(root).map(|solution| solution.get_cost()), Some(2));
+        assert_eq!(tabu.get_steps(), 4);
+    }
+}
\ No newline at end of file
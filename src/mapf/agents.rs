@@ -0,0 +1,24 @@
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Agent<V> {
+    pub start: V,
+    pub goal: V,
+}
+
+// One agent per line as `start_x start_y goal_x goal_y`, empty lines and lines starting with `#` are skipped
+pub fn parse_agents(text: &str) -> Result<Vec<Agent<(usize, usize)>>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            let values = line.split_whitespace()
+                .map(|value| value.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| format!("Line {}: {}", number, error))?;
+            match values[..] {
+                [start_x, start_y, goal_x, goal_y] => Ok(Agent { start: (start_x, start_y), goal: (goal_x, goal_y) }),
+                _ => Err(format!("Line {}: expected 4 coordinates, found {}", number, values.len())),
+            }
+        })
+        .collect()
+}
\ No newline at end of file
@@ -0,0 +1,454 @@
+use crate::traits::mapf::MapfGraph;
+use crate::utils::focal::FocalList;
+use crate::utils::state_table::StateTable;
+use super::agents::Agent;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy)]
+enum Constraint {
+    // The agent cannot be at the vertex at that time
+    Vertex { agent: usize, vertex: usize, time: usize },
+    // The agent cannot arrive at `to` from `from` at that time
+    Edge { agent: usize, from: usize, to: usize, time: usize },
+}
+
+impl Constraint {
+    fn get_agent(&self) -> usize {
+        match self {
+            Constraint::Vertex { agent, .. } | Constraint::Edge { agent, .. } => *agent,
+        }
+    }
+}
+
+enum Conflict {
+    // Both agents at the vertex at the same time
+    Vertex { agents: (usize, usize), vertex: usize, time: usize },
+    // The first agent moves from `from` to `to` while the second one swaps with it
+    Edge { agents: (usize, usize), from: usize, to: usize, time: usize },
+}
+
+// Node of the constraint tree, its constraints are the ones on the way to the root
+struct TreeNode {
+    parent: Option<usize>,
+    constraint: Option<Constraint>,
+    paths: Vec<Rc<Vec<usize>>>,
+    // Lower bound on the cost of every agent
+    bounds: Vec<isize>,
+    cost: isize,
+}
+
+// Other agents' paths, to count the conflicts of a path being planned
+struct Reservations {
+    vertices: HashMap<(usize, usize), isize>,
+    moves: HashMap<(usize, usize, usize), isize>,
+    // Agents that stay at their goal from the given time on
+    parked: Vec<(usize, usize)>,
+}
+
+impl Reservations {
+    fn new(paths: &[Rc<Vec<usize>>], agent: usize) -> Self {
+        let mut reservations = Reservations { vertices: HashMap::new(), moves: HashMap::new(), parked: Vec::new() };
+        for (other, path) in paths.iter().enumerate() {
+            if other == agent || path.is_empty() { continue; }
+            for (time, vertex) in path.iter().enumerate() {
+                *reservations.vertices.entry((*vertex, time)).or_insert(0) += 1;
+                if time > 0 { *reservations.moves.entry((path[time - 1], *vertex, time)).or_insert(0) += 1; }
+            }
+            reservations.parked.push((path[path.len() - 1], path.len()));
+        }
+        reservations
+    }
+
+    // Conflicts caused by moving from `from` to `to` arriving at `time`
+    fn count(&self, from: usize, to: usize, time: usize) -> isize {
+        let parked = self.parked.iter().filter(|(vertex, since)| *vertex == to && time >= *since).count() as isize;
+        let swaps = if from == to { 0 } else { self.moves.get(&(to, from, time)).copied().unwrap_or(0) };
+        self.vertices.get(&(to, time)).copied().unwrap_or(0) + parked + swaps
+    }
+}
+
+/*
+ *  Conflict-Based Search: the high level searches a tree of constraints (an agent may not be at a
+ *  vertex, or take an edge, at some time), planning every agent on its own with space-time A* and
+ *  splitting on the first conflict between two paths. Returns paths of minimal sum of costs, the
+ *  cost of an agent being the time it reaches its goal for the last time.
+ *
+ *  With a suboptimality weight over 1 it becomes Enhanced CBS: both levels are focal searches that
+ *  prefer fewer conflicts among nodes within the weight of the lower bound, and the sum of costs is
+ *  at most the weight times the optimum.
+ */
+
+pub struct CBS<G: MapfGraph> {
+    weight: f64,
+    time_limit: Option<Duration>,
+    vertices: StateTable<G::Vertex>,
+    neighbours: Vec<Vec<usize>>,
+    // Distance of every vertex to the goal of every agent
+    distances: Vec<Vec<isize>>,
+    tree: Vec<TreeNode>,
+    cost: isize,
+    lower_bound: isize,
+    expanded: usize,
+    low_level_expanded: usize,
+    limit_reached: bool,
+}
+
+impl<G: MapfGraph> CBS<G> {
+    pub fn new() -> Self {
+        CBS {
+            weight: 1.0,
+            time_limit: None,
+            vertices: StateTable::new(),
+            neighbours: Vec::new(),
+            distances: Vec::new(),
+            tree: Vec::new(),
+            cost: 0,
+            lower_bound: 0,
+            expanded: 0,
+            low_level_expanded: 0,
+            limit_reached: false,
+        }
+    }
+
+    // Enables ECBS when over 1
+    pub fn set_suboptimality(&mut self, weight: f64) -> &mut Self {
+        self.weight = weight.max(1.0);
+        self
+    }
+
+    pub fn set_time_limit(&mut self, time_limit: Duration) -> &mut Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    // Path of every agent, one vertex per timestep
+    pub fn find_paths(&mut self, graph: &G, agents: &[Agent<G::Vertex>]) -> Option<Vec<Vec<G::Vertex>>> {
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        self.tree.clear();
+        self.distances.clear();
+        // The graph may differ between calls
+        self.vertices.clear();
+        self.neighbours.clear();
+        self.limit_reached = false;
+        let starts: Vec<usize> = agents.iter().map(|agent| self.vertices.intern(agent.start.clone()).0).collect();
+        let goals: Vec<usize> = agents.iter().map(|agent| self.vertices.intern(agent.goal.clone()).0).collect();
+        for goal in &goals {
+            let distances = self.compute_distances(graph, *goal);
+            self.distances.push(distances);
+        }
+
+        let mut paths: Vec<Rc<Vec<usize>>> = agents.iter().map(|_| Rc::new(Vec::new())).collect();
+        let mut bounds = Vec::new();
+        for agent in 0..agents.len() {
+            let (path, bound) = self.plan(agent, starts[agent], goals[agent], &[], &paths)?;
+            paths[agent] = Rc::new(path);
+            bounds.push(bound);
+        }
+        let mut open = FocalList::new(self.weight);
+        self.push(&mut open, TreeNode { parent: None, constraint: None, cost: get_cost(&paths), paths, bounds });
+
+        while let Some(bound) = open.get_min_bound() {
+            if deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false) {
+                self.limit_reached = true;
+                return None
+            }
+            let index = open.pop().unwrap();
+            self.expanded += 1;
+            let conflict = match find_conflicts(&self.tree[index].paths).1 {
+                Some(conflict) => conflict,
+                None => {
+                    self.cost = self.tree[index].cost;
+                    self.lower_bound = bound;
+                    let paths = self.tree[index].paths.iter()
+                        .map(|path| path.iter().map(|vertex| self.vertices.get(*vertex).clone()).collect())
+                        .collect();
+                    return Some(paths)
+                }
+            };
+
+            let children = match conflict {
+                Conflict::Vertex { agents: (a, b), vertex, time } => [
+                    Constraint::Vertex { agent: a, vertex, time },
+                    Constraint::Vertex { agent: b, vertex, time },
+                ],
+                Conflict::Edge { agents: (a, b), from, to, time } => [
+                    Constraint::Edge { agent: a, from, to, time },
+                    Constraint::Edge { agent: b, from: to, to: from, time },
+                ],
+            };
+            for constraint in children {
+                let agent = constraint.get_agent();
+                let mut constraints = self.get_constraints(index, agent);
+                constraints.push(constraint);
+                let mut paths = self.tree[index].paths.clone();
+                let (path, bound) = match self.plan(agent, starts[agent], goals[agent], &constraints, &paths) {
+                    Some(planned) => planned,
+                    None => continue,
+                };
+                let mut bounds = self.tree[index].bounds.clone();
+                paths[agent] = Rc::new(path);
+                // Constraints only make paths longer
+                bounds[agent] = bounds[agent].max(bound);
+                self.push(&mut open, TreeNode { parent: Some(index), constraint: Some(constraint), cost: get_cost(&paths), paths, bounds });
+            }
+        }
+        None
+    }
+
+    // Sum of costs of the last paths found
+    pub fn get_cost(&self) -> isize { self.cost }
+    // Lower bound on the optimal sum of costs when the paths were found
+    pub fn get_lower_bound(&self) -> isize { self.lower_bound }
+    // Constraint tree nodes expanded
+    pub fn get_expanded(&self) -> usize { self.expanded }
+    pub fn get_low_level_expanded(&self) -> usize { self.low_level_expanded }
+    pub fn is_limit_reached(&self) -> bool { self.limit_reached }
+
+    fn push(&mut self, open: &mut FocalList, node: TreeNode) {
+        let conflicts = find_conflicts(&node.paths).0 as isize;
+        open.push(self.tree.len(), node.bounds.iter().sum(), node.cost, conflicts);
+        self.tree.push(node);
+    }
+
+    fn get_constraints(&self, mut index: usize, agent: usize) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+        loop {
+            let node = &self.tree[index];
+            if let Some(constraint) = node.constraint.filter(|constraint| constraint.get_agent() == agent) {
+                constraints.push(constraint);
+            }
+            match node.parent {
+                Some(parent) => index = parent,
+                None => return constraints,
+            }
+        }
+    }
+
+    // Breadth-first search from the goal, caching the neighbours of every vertex found
+    fn compute_distances(&mut self, graph: &G, goal: usize) -> Vec<isize> {
+        let mut distances = vec![isize::MAX; self.vertices.len()];
+        let mut queue = VecDeque::from([goal]);
+        distances[goal] = 0;
+        while let Some(vertex) = queue.pop_front() {
+            if vertex >= self.neighbours.len() { self.neighbours.resize(vertex + 1, Vec::new()); }
+            if self.neighbours[vertex].is_empty() {
+                let neighbours = graph.get_neighbours(self.vertices.get(vertex)).into_iter()
+                    .map(|neighbour| self.vertices.intern(neighbour).0)
+                    .collect();
+                self.neighbours[vertex] = neighbours;
+            }
+            for neighbour in self.neighbours[vertex].clone() {
+                if neighbour >= distances.len() { distances.resize(neighbour + 1, isize::MAX); }
+                if distances[neighbour] != isize::MAX { continue; }
+                distances[neighbour] = distances[vertex] + 1;
+                queue.push_back(neighbour);
+            }
+        }
+        distances
+    }
+
+    // Focal space-time A*, returns the path and a lower bound on its optimal cost
+    fn plan(&mut self, agent: usize, start: usize, goal: usize, constraints: &[Constraint], paths: &[Rc<Vec<usize>>]) -> Option<(Vec<usize>, isize)> {
+        let mut vertex_constraints = HashSet::new();
+        let mut edge_constraints = HashSet::new();
+        let mut last_goal_constraint = None;
+        let mut horizon = paths.iter().map(|path| path.len()).max().unwrap_or(0);
+        for constraint in constraints {
+            match *constraint {
+                Constraint::Vertex { vertex, time, .. } => {
+                    vertex_constraints.insert((vertex, time));
+                    if vertex == goal { last_goal_constraint = last_goal_constraint.max(Some(time)); }
+                    horizon = horizon.max(time);
+                }
+                Constraint::Edge { from, to, time, .. } => {
+                    edge_constraints.insert((from, to, time));
+                    horizon = horizon.max(time);
+                }
+            }
+        }
+        if vertex_constraints.contains(&(start, 0)) { return None }
+        let reservations = Reservations::new(paths, agent);
+        let distances = &self.distances[agent];
+        let get_distance = |vertex: usize| distances.get(vertex).copied().unwrap_or(isize::MAX);
+        if get_distance(start) == isize::MAX { return None }
+
+        // (vertex, time, parent, conflicts)
+        let mut nodes: Vec<(usize, usize, Option<usize>, isize)> = vec![(start, 0, None, 0)];
+        // After the horizon nothing changes with time
+        let mut closed = HashSet::new();
+        let mut open = FocalList::new(self.weight);
+        open.push(0, get_distance(start), get_distance(start), 0);
+
+        while let Some(bound) = open.get_min_bound() {
+            let index = open.pop().unwrap();
+            let (vertex, time, _, conflicts) = nodes[index];
+            if !closed.insert((vertex, time.min(horizon + 1))) { continue; }
+            if vertex == goal && last_goal_constraint.map(|last| time > last).unwrap_or(true) {
+                let mut path = Vec::new();
+                let mut current = Some(index);
+                while let Some(index) = current {
+                    path.push(nodes[index].0);
+                    current = nodes[index].2;
+                }
+                path.reverse();
+                return Some((path, bound))
+            }
+            self.low_level_expanded += 1;
+
+            for next in self.neighbours[vertex].iter().copied().chain([vertex]) {
+                let distance = get_distance(next);
+                let next_time = time + 1;
+                if distance == isize::MAX
+                    || vertex_constraints.contains(&(next, next_time))
+                    || edge_constraints.contains(&(vertex, next, next_time))
+                    || closed.contains(&(next, next_time.min(horizon + 1))) { continue; }
+                let cost = next_time as isize + distance;
+                nodes.push((next, next_time, Some(index), conflicts + reservations.count(vertex, next, next_time)));
+                open.push(nodes.len() - 1, cost, cost, nodes[nodes.len() - 1].3);
+            }
+        }
+        None
+    }
+}
+
+impl<G: MapfGraph> Default for CBS<G> {
+    fn default() -> Self { Self::new() }
+}
+
+fn get_cost(paths: &[Rc<Vec<usize>>]) -> isize {
+    paths.iter().map(|path| path.len() as isize - 1).sum()
+}
+
+// Agents stay at their goal after their path ends
+fn get_position(path: &[usize], time: usize) -> usize {
+    path[time.min(path.len() - 1)]
+}
+
+// Number of conflicting pairs and the earliest conflict
+fn find_conflicts(paths: &[Rc<Vec<usize>>]) -> (usize, Option<Conflict>) {
+    let horizon = paths.iter().map(|path| path.len()).max().unwrap_or(0);
+    let mut count = 0;
+    let mut first = None;
+    let mut previous: HashMap<usize, usize> = HashMap::new();
+    for time in 0..horizon {
+        let mut occupied: HashMap<usize, usize> = HashMap::new();
+        for (agent, path) in paths.iter().enumerate() {
+            let vertex = get_position(path, time);
+            if let Some(other) = occupied.insert(vertex, agent) {
+                count += 1;
+                first.get_or_insert(Conflict::Vertex { agents: (other, agent), vertex, time });
+            }
+            if time == 0 { continue; }
+            let from = get_position(path, time - 1);
+            let other = match previous.get(&vertex) {
+                Some(other) if from != vertex && *other < agent => *other,
+                _ => continue,
+            };
+            if get_position(&paths[other], time) == from {
+                count += 1;
+                first.get_or_insert(Conflict::Edge { agents: (agent, other), from, to: vertex, time });
+            }
+        }
+        previous = occupied;
+    }
+    (count, first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Open cells of a grid, 4-connected
+    struct Grid { cells: Vec<(usize, usize)> }
+
+    impl Grid {
+        fn parse(rows: &[&str]) -> Self {
+            let cells = rows.iter().enumerate()
+                .flat_map(|(y, row)| row.chars().enumerate().filter(|(_, cell)| *cell == '.').map(move |(x, _)| (x, y)))
+                .collect();
+            Grid { cells }
+        }
+    }
+
+    impl MapfGraph for Grid {
+        type Vertex = (usize, usize);
+
+        fn get_neighbours(&self, &(x, y): &(usize, usize)) -> Vec<(usize, usize)> {
+            [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))].into_iter()
+                .filter(|cell| self.cells.contains(cell))
+                .collect()
+        }
+    }
+
+    fn agent(start: (usize, usize), goal: (usize, usize)) -> Agent<(usize, usize)> { Agent { start, goal } }
+
+    // Checked independently of `find_conflicts`
+    fn assert_valid(grid: &Grid, agents: &[Agent<(usize, usize)>], paths: &[Vec<(usize, usize)>]) {
+        for (agent, path) in agents.iter().zip(paths) {
+            assert_eq!((path[0], path[path.len() - 1]), (agent.start, agent.goal));
+            for step in path.windows(2) {
+                assert!(step[0] == step[1] || grid.get_neighbours(&step[0]).contains(&step[1]));
+            }
+        }
+        let at = |path: &Vec<(usize, usize)>, time: usize| path[time.min(path.len() - 1)];
+        let horizon = paths.iter().map(|path| path.len()).max().unwrap();
+        for time in 0..horizon {
+            for (a, first) in paths.iter().enumerate() {
+                for second in &paths[a + 1..] {
+                    assert_ne!(at(first, time), at(second, time), "vertex conflict at {}", time);
+                    if time == 0 { continue; }
+                    let swapped = at(first, time) == at(second, time - 1) && at(second, time) == at(first, time - 1);
+                    assert!(!swapped, "edge conflict at {}", time);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn avoids_swapping_along_an_edge() {
+        let grid = Grid::parse(&["..", ".."]);
+        let agents = [agent((0, 0), (1, 0)), agent((1, 0), (0, 0))];
+        let mut cbs = CBS::new();
+        let paths = cbs.find_paths(&grid, &agents).unwrap();
+        assert_valid(&grid, &agents, &paths);
+        // One agent goes around the square
+        assert_eq!(cbs.get_cost(), 4);
+
+        // The same instance on another graph
+        let corridor = Grid::parse(&["...."]);
+        let agents = [agent((0, 0), (3, 0))];
+        assert_eq!(cbs.find_paths(&corridor, &agents), Some(vec![vec![(0, 0), (1, 0), (2, 0), (3, 0)]]));
+    }
+
+    #[test]
+    fn waits_in_a_side_pocket() {
+        let grid = Grid::parse(&["...", "#.#"]);
+        let agents = [agent((0, 0), (2, 0)), agent((2, 0), (0, 0))];
+        let mut cbs = CBS::new();
+        let paths = cbs.find_paths(&grid, &agents).unwrap();
+        assert_valid(&grid, &agents, &paths);
+        assert_eq!(cbs.get_cost(), 7);
+        assert_eq!(cbs.get_lower_bound(), 7);
+    }
+
+    #[test]
+    fn suboptimal_cost_is_bounded() {
+        let grid = Grid::parse(&["....", "..#.", "....", ".#.."]);
+        let agents = [
+            agent((0, 0), (3, 3)), agent((3, 3), (0, 0)), agent((3, 0), (0, 2)), agent((0, 2), (3, 0)),
+        ];
+        let mut cbs = CBS::new();
+        let paths = cbs.find_paths(&grid, &agents).unwrap();
+        assert_valid(&grid, &agents, &paths);
+        let optimum = cbs.get_cost();
+
+        let mut ecbs = CBS::new();
+        ecbs.set_suboptimality(1.5);
+        let paths = ecbs.find_paths(&grid, &agents).unwrap();
+        assert_valid(&grid, &agents, &paths);
+        assert!(ecbs.get_lower_bound() <= optimum);
+        assert!(optimum <= ecbs.get_cost() && ecbs.get_cost() as f64 <= 1.5 * optimum as f64);
+    }
+}
\ No newline at end of file
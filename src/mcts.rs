@@ -0,0 +1,318 @@
+use super::traits::node::Node;
+use super::traits::algorithm::{Algorithm, AlgorithmExt};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::rng::Rng;
+use super::utils::state_table::{StateId, StateTable};
+use std::mem::size_of;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// Picks the index of the descendant to follow during a rollout
+pub type RolloutFn<T> = Box<dyn FnMut(&[Box<T>], &mut Rng) -> usize>;
+
+// UCT exploration term of a child, unvisited children come first
+pub(crate) fn get_exploration_bonus(exploration: f64, parent_visits: usize, visits: usize) -> f64 {
+    if visits == 0 { return f64::INFINITY }
+    exploration * ((parent_visits as f64).ln() / visits as f64).sqrt()
+}
+
+// Child with the highest score, the first one on ties
+pub(crate) fn select_best<I: Iterator<Item = usize>, F: Fn(usize) -> f64>(mut children: I, score: F) -> Option<usize> {
+    let first = children.next()?;
+    Some(children.fold((first, score(first)), |best, child| {
+        let value = score(child);
+        if value > best.1 { (child, value) } else { best }
+    }).0)
+}
+
+struct TreeNode<T> {
+    // Taken when the node is expanded
+    node: Option<Box<T>>,
+    state: StateId,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    visits: usize,
+    // Rollouts that reached a solution and the sum of their rewards
+    successes: usize,
+    reward: f64,
+    // Every rollout below this node is known, it is never selected again
+    exhausted: bool,
+}
+
+/*
+ *  Monte Carlo tree search for single agent problems, the reward of a rollout is `-get_cost()` of the
+ *  solution it reaches and rollouts that fail count as the worst reward. Rewards are normalized to
+ *  [0, 1] with the extremes seen so far before applying UCT.
+ *
+ *  Returns the cheapest solution found within the budget, states already on the path from the root
+ *  are not added to the tree.
+ */
+
+pub struct MCTS<T: Node + Clone> {
+    tree: Vec<TreeNode<T>>,
+    states: StateTable<Rc<T::State>>,
+    exploration: f64,
+    iterations: usize,
+    time_limit: Option<Duration>,
+    rollout_depth: usize,
+    rollout: Option<RolloutFn<T>>,
+    rng: Rng,
+    best: Option<Box<T>>,
+    // Lowest and highest reward seen
+    bounds: Option<(f64, f64)>,
+    completed: usize,
+    memory: MemoryBudget<T::State>,
+}
+
+impl<T: Node + Clone> MCTS<T> {
+    pub fn new() -> Self {
+        MCTS {
+            tree: Vec::new(),
+            states: StateTable::new(),
+            exploration: std::f64::consts::SQRT_2,
+            iterations: 10_000,
+            time_limit: None,
+            rollout_depth: 1_000,
+            rollout: None,
+            rng: Rng::new(0),
+            best: None,
+            bounds: None,
+            completed: 0,
+            memory: MemoryBudget::new(),
+        }
+    }
+
+    pub fn set_exploration(&mut self, exploration: f64) -> &mut Self {
+        self.exploration = exploration;
+        self
+    }
+
+    // Both budgets apply, the search stops at the first one reached
+    pub fn set_iterations(&mut self, iterations: usize) -> &mut Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn set_time_limit(&mut self, time_limit: Duration) -> &mut Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    // Rollouts give up after `depth` steps without reaching a solution
+    pub fn set_rollout_depth(&mut self, depth: usize) -> &mut Self {
+        self.rollout_depth = depth;
+        self
+    }
+
+    // Replaces the uniformly random rollout policy
+    pub fn set_rollout(&mut self, rollout: RolloutFn<T>) -> &mut Self {
+        self.rollout = Some(rollout);
+        self
+    }
+
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    pub fn get_iterations(&self) -> usize { self.completed }
+    pub fn get_tree_size(&self) -> usize { self.tree.len() }
+
+    fn intern(&mut self, node: &T) -> StateId {
+        let (state, inserted) = self.states.intern_with(node.get_state(), node.get_state_hash());
+        if inserted { self.memory.add_state(self.states.get(state)); }
+        state
+    }
+
+    fn add_node(&mut self, node: Box<T>, state: StateId, parent: Option<usize>) -> usize {
+        self.tree.push(TreeNode {
+            node: Some(node),
+            state,
+            parent,
+            children: Vec::new(),
+            visits: 0,
+            successes: 0,
+            reward: 0.0,
+            exhausted: false,
+        });
+        self.tree.len() - 1
+    }
+
+    fn is_on_path(&self, state: StateId, mut current: Option<usize>) -> bool {
+        while let Some(index) = current {
+            if self.tree[index].state == state { return true }
+            current = self.tree[index].parent;
+        }
+        false
+    }
+
+    fn get_value(&self, index: usize) -> f64 {
+        let node = &self.tree[index];
+        match self.bounds {
+            Some((low, high)) if high > low =>
+                (node.reward - node.successes as f64 * low) / (high - low) / node.visits as f64,
+            _ => node.successes as f64 / node.visits as f64,
+        }
+    }
+
+    fn select_child(&self, index: usize) -> usize {
+        let visits = self.tree[index].visits;
+        let score = |child: usize| -> f64 {
+            let bonus = get_exploration_bonus(self.exploration, visits, self.tree[child].visits);
+            if bonus.is_infinite() { bonus } else { self.get_value(child) + bonus }
+        };
+        let children = self.tree[index].children.iter().copied().filter(|child| !self.tree[*child].exhausted);
+        select_best(children, score).unwrap()
+    }
+
+    // Records the solution, returns its reward
+    fn reach_solution(&mut self, node: &T) -> f64 {
+        let better = self.best.as_ref().map(|best| node.get_cost() < best.get_cost()).unwrap_or(true);
+        if better { self.best = Some(Box::new(node.clone())); }
+        -node.get_cost() as f64
+    }
+
+    fn simulate(&mut self, mut node: Box<T>) -> Option<f64> {
+        for _ in 0..self.rollout_depth {
+            if node.is_solution() { return Some(self.reach_solution(&node)) }
+            let mut descendants = node.get_descendants();
+            if descendants.is_empty() { return None }
+            let i = match &mut self.rollout {
+                Some(rollout) => rollout(&descendants, &mut self.rng),
+                None => self.rng.next_below(descendants.len()),
+            };
+            node = descendants.swap_remove(i);
+        }
+        if node.is_solution() { Some(self.reach_solution(&node)) } else { None }
+    }
+
+    fn iterate(&mut self) {
+        let mut current = 0;
+        while !self.tree[current].children.is_empty() {
+            current = self.select_child(current);
+        }
+
+        let node = self.tree[current].node.take().unwrap();
+        let reward = if node.is_solution() {
+            self.tree[current].exhausted = true;
+            Some(self.reach_solution(&node))
+        } else {
+            for descendant in node.get_descendants() {
+                let state = self.intern(&descendant);
+                if self.is_on_path(state, Some(current)) { continue; }
+                let child = self.add_node(descendant, state, Some(current));
+                self.tree[current].children.push(child);
+            }
+            if self.tree[current].children.is_empty() {
+                self.tree[current].exhausted = true;
+                None
+            } else {
+                let i = self.rng.next_below(self.tree[current].children.len());
+                current = self.tree[current].children[i];
+                let node = self.tree[current].node.clone().unwrap();
+                self.simulate(node)
+            }
+        };
+
+        if let Some(reward) = reward {
+            self.bounds = Some(self.bounds.map_or((reward, reward), |(low, high)| (low.min(reward), high.max(reward))));
+        }
+        let mut next = Some(current);
+        while let Some(index) = next {
+            let node = &mut self.tree[index];
+            node.visits += 1;
+            if let Some(reward) = reward {
+                node.successes += 1;
+                node.reward += reward;
+            }
+            let node = &self.tree[index];
+            if !node.children.is_empty() && node.children.iter().all(|child| self.tree[*child].exhausted) {
+                self.tree[index].exhausted = true;
+            }
+            next = self.tree[index].parent;
+        }
+    }
+}
+
+impl<T: Node + Clone> Default for MCTS<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Node + Clone> Algorithm<T> for MCTS<T> {
+    fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        // The root must be at index 0, nothing is kept from a previous search
+        self.tree.clear();
+        self.states.clear();
+        self.memory.reset();
+        self.best = None;
+        self.bounds = None;
+        self.completed = 0;
+        let state = self.intern(&root);
+        self.add_node(root, state, None);
+        while self.completed < self.iterations && !self.tree[0].exhausted {
+            if deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false) { break; }
+            if self.memory.check(&self.get_memory_usage()) { break; }
+            self.iterate();
+            self.completed += 1;
+        }
+        self.best.take()
+    }
+
+    fn get_visited(&self) -> Vec<&Rc<T::State>> {
+        self.states.iter().collect()
+    }
+}
+
+impl<T: Node + Clone> AlgorithmExt<T> for MCTS<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    // Expanded tree nodes are counted as alive, they may still be referenced as parents
+    fn get_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            frontier: 0,
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.memory.get_state_bytes(),
+            nodes: self.tree.capacity() * size_of::<TreeNode<T>>() + self.tree.len() * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::GridNode;
+
+    #[test]
+    fn same_seed_same_solution() {
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let mut search = MCTS::new();
+            search.set_seed(3).set_iterations(300);
+            let solution = search.find_solution(GridNode::root()).unwrap();
+            let actions: Vec<char> = std::iter::successors(Some(Rc::new(*solution.clone())), |node| node.get_parent())
+                .filter_map(|node| node.get_action())
+                .collect();
+            runs.push((solution.get_cost(), actions, search.get_tree_size()));
+        }
+        assert_eq!(runs[0], runs[1]);
+    }
+
+    #[test]
+    fn stops_at_the_budgets() {
+        let mut search = MCTS::new();
+        search.set_iterations(25);
+        search.find_solution(GridNode::root());
+        assert_eq!(search.get_iterations(), 25);
+
+        let time_start = Instant::now();
+        search.set_iterations(usize::MAX).set_time_limit(Duration::from_millis(20));
+        assert!(search.find_solution(GridNode::root()).is_some());
+        assert!(time_start.elapsed() < Duration::from_secs(2));
+        assert!(search.get_iterations() > 0 && search.get_iterations() < usize::MAX);
+    }
+}
\ No newline at end of file
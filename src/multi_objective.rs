@@ -0,0 +1,209 @@
+use super::traits::{
+    algorithm::{Algorithm, AlgorithmExt},
+    multi_objective::{covers, MultiObjectiveNode}
+};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::state_table::StateTable;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::mem::size_of;
+use std::rc::Rc;
+
+pub enum Mode {
+    // Every non-dominated solution
+    Pareto,
+    // Cheapest in the first cost, ties broken by the next ones
+    Lexicographic,
+    // Cheapest in the weighted sum of the costs, ties broken lexicographically
+    WeightedSum(Vec<isize>),
+}
+
+struct Label<T> {
+    key: Vec<isize>,
+    costs: Vec<isize>,
+    node: Box<T>,
+}
+
+impl<T> Ord for Label<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Inverse order: min goes first
+        other.key.cmp(&self.key)
+    }
+}
+
+impl<T> PartialOrd for Label<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Label<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> Eq for Label<T> { }
+
+/*
+ *  NAMOA*: best-first search over labels (paths) rather than states. Every state keeps the cost
+ *  vectors of the non-dominated paths reaching it, new paths covered by one of them are discarded
+ *  and the ones they dominate are dropped from the frontier. Labels are expanded in lexicographic
+ *  order of cost + heuristic, those covered by a solution found are pruned.
+ *
+ *  With admissible heuristics the front holds one solution per non-dominated cost vector, in
+ *  lexicographic order. The other modes stop at the first solution.
+ */
+
+pub struct NamoaStar<T: MultiObjectiveNode> {
+    mode: Mode,
+    p_queue: BinaryHeap<Label<T>>,
+    states: StateTable<Rc<T::State>>,
+    // Non-dominated cost vectors reaching every state, indexed by state id
+    costs: Vec<Vec<Vec<isize>>>,
+    front: Vec<Vec<isize>>,
+    expanded: usize,
+    memory: MemoryBudget<T::State>,
+}
+
+impl<T: MultiObjectiveNode> NamoaStar<T> {
+    pub fn new() -> Self {
+        NamoaStar {
+            mode: Mode::Pareto,
+            p_queue: BinaryHeap::new(),
+            states: StateTable::new(),
+            costs: Vec::new(),
+            front: Vec::new(),
+            expanded: 0,
+            memory: MemoryBudget::new(),
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn get_expanded(&self) -> usize { self.expanded }
+
+    // Solutions in lexicographic order of their costs
+    pub fn find_front(&mut self, root: T) -> Vec<Box<T>> {
+        self.memory.reset();
+        self.p_queue.clear();
+        self.states.clear();
+        self.costs.clear();
+        self.front.clear();
+        self.expanded = 0;
+        let mut solutions = Vec::new();
+        self.push(Box::new(root));
+
+        while let Some(Label { costs, node, .. }) = self.p_queue.pop() {
+            let id = self.states.get_id_hashed(&node.get_state(), self.get_hash(&node)).unwrap();
+            // Stale label, a path dominating it was found after pushing it
+            if !self.costs[id].contains(&costs) { continue; }
+            let estimate = self.get_estimate(&node, &costs);
+            if self.front.iter().any(|solution| covers(solution, &estimate)) { continue; }
+            if node.is_solution() {
+                self.front.push(costs);
+                solutions.push(node);
+                if let Mode::Pareto = self.mode { continue } else { break }
+            }
+            if self.memory.check(&self.get_memory_usage()) { break }
+            self.expanded += 1;
+            for descendant in node.get_descendants() {
+                self.push(descendant);
+            }
+        }
+        solutions
+    }
+
+    fn push(&mut self, node: Box<T>) {
+        let costs = node.get_costs();
+        let (id, inserted) = self.states.intern_with(node.get_state(), node.get_state_hash());
+        if inserted {
+            self.costs.push(Vec::new());
+            self.memory.add_state(self.states.get(id));
+        }
+        if self.costs[id].iter().any(|other| covers(other, &costs)) { return }
+        let estimate = self.get_estimate(&node, &costs);
+        if self.front.iter().any(|solution| covers(solution, &estimate)) { return }
+        self.costs[id].retain(|other| !covers(&costs, other));
+        self.costs[id].push(costs.clone());
+        let key = match &self.mode {
+            Mode::WeightedSum(weights) => {
+                let sum = weights.iter().zip(&estimate).map(|(weight, cost)| weight * cost).sum();
+                [vec![sum], estimate].concat()
+            },
+            _ => estimate,
+        };
+        self.p_queue.push(Label { key, costs, node });
+    }
+
+    fn get_estimate(&self, node: &T, costs: &[isize]) -> Vec<isize> {
+        costs.iter().zip(node.get_heuristics()).map(|(cost, heuristic)| cost + heuristic).collect()
+    }
+
+    fn get_hash(&self, node: &T) -> u64 {
+        node.get_state_hash().unwrap_or_else(|| StateTable::hash_state(&node.get_state()))
+    }
+}
+
+impl<T: MultiObjectiveNode> Default for NamoaStar<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: MultiObjectiveNode> Algorithm<T> for NamoaStar<T> {
+    // First solution of the front
+    fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        self.find_front(*root).into_iter().next()
+    }
+
+    fn get_visited(&self) -> Vec<&Rc<T::State>> {
+        self.states.iter().collect()
+    }
+}
+
+impl<T: MultiObjectiveNode> AlgorithmExt<T> for NamoaStar<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        let objectives = self.front.first().map(|costs| costs.len()).unwrap_or(1);
+        let labels: usize = self.costs.iter().map(|costs| costs.len()).sum();
+        MemoryUsage {
+            frontier: self.p_queue.capacity() * (size_of::<Label<T>>() + 2 * objectives * size_of::<isize>()),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.costs.capacity() * size_of::<Vec<Vec<isize>>>()
+                + labels * (size_of::<Vec<isize>>() + objectives * size_of::<isize>())
+                + self.memory.get_state_bytes(),
+            nodes: (self.p_queue.len() + self.expanded) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::EdgeNode;
+
+    fn get_costs(solutions: &[Box<EdgeNode>]) -> Vec<Vec<isize>> {
+        solutions.iter().map(|solution| solution.get_costs()).collect()
+    }
+
+    #[test]
+    fn finds_the_front_in_every_mode() {
+        let mut search = NamoaStar::new();
+        for _ in 0..2 {
+            assert_eq!(get_costs(&search.find_front(EdgeNode::root())), vec![vec![2, 10], vec![5, 7], vec![6, 2]]);
+        }
+        // Each of these stops with labels still queued
+        search.set_mode(Mode::Lexicographic);
+        assert_eq!(get_costs(&search.find_front(EdgeNode::root())), vec![vec![2, 10]]);
+        search.set_mode(Mode::WeightedSum(vec![1, 1]));
+        assert_eq!(get_costs(&search.find_front(EdgeNode::root())), vec![vec![6, 2]]);
+        assert_eq!(get_costs(&search.find_front(EdgeNode::root())), vec![vec![6, 2]]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,188 @@
+use crate::traits::problem::Problem;
+use crate::utils::path::Path;
+use crate::utils::state_table::{StateId, StateTable};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/*
+ *  Layer synchronous BFS: a pool of threads, started once per search, expands a slice of every
+ *  layer and deduplicates successors against a visited table sharded by state hash. States are
+ *  shared between the shards and the current layer, previous layers only keep the shard and id of
+ *  their states to rebuild the path.
+ */
+
+type Layer<S> = Arc<Vec<(Arc<S>, isize)>>;
+// Layer with the range of it to expand
+type Job<S> = (Layer<S>, usize, usize);
+type Generated<S, A> = Vec<(Arc<S>, Record<A>)>;
+
+struct Record<A> {
+    shard: usize,
+    state: StateId,
+    action: Option<A>,
+    parent: usize,
+    cost: isize,
+}
+
+pub struct BFS<P: Problem> {
+    threads: usize,
+    layer_sizes: Vec<usize>,
+    solution: Option<Path<P::State, P::Action>>,
+}
+
+impl<P> BFS<P>
+where
+    P: Problem + Sync,
+    P::State: Clone + Send + Sync,
+    P::Action: Clone + Send,
+{
+    pub fn new(threads: usize) -> Self {
+        BFS { threads: threads.max(1), layer_sizes: Vec::new(), solution: None }
+    }
+
+    pub fn find_solution(&mut self, problem: &P) -> Option<&Path<P::State, P::Action>> {
+        self.search(problem, true);
+        self.solution.as_ref()
+    }
+
+    // Exhaustive search, number of distinct states reachable from the root
+    pub fn count_reachable(&mut self, problem: &P) -> usize {
+        self.search(problem, false);
+        self.layer_sizes.iter().sum()
+    }
+
+    pub fn get_layer_sizes(&self) -> &[usize] { &self.layer_sizes }
+
+    fn search(&mut self, problem: &P, stop_at_solution: bool) {
+        let shards: Vec<Mutex<StateTable<Arc<P::State>>>> = (0..self.threads * 16)
+            .map(|_| Mutex::new(StateTable::new()))
+            .collect();
+        let shard_of = |hash: u64| hash as usize % shards.len();
+        self.solution = None;
+
+        let root = Arc::new(problem.get_root());
+        let hash = problem.get_state_hash(&root).unwrap_or_else(|| StateTable::hash_state(&root));
+        let root_shard = shard_of(hash);
+        let (root_id, _) = shards[root_shard].lock().unwrap().intern_hashed(root.clone(), hash);
+        let mut layers = vec![vec![Record { shard: root_shard, state: root_id, action: None, parent: 0, cost: 0 }]];
+        let mut found = if stop_at_solution && problem.is_solution(&root) { Some(0) } else { None };
+        let mut frontier: Layer<P::State> = Arc::new(vec![(root, 0)]);
+        let threads = self.threads;
+
+        thread::scope(|scope| {
+            // A job and a result channel per worker, the pool stops once the job senders are dropped
+            let workers: Vec<_> = (0..threads).map(|_| {
+                let (jobs, receiver) = mpsc::channel::<Job<P::State>>();
+                let (sender, results) = mpsc::channel::<Generated<P::State, P::Action>>();
+                let shards = &shards;
+                scope.spawn(move || {
+                    for (layer, start, end) in receiver {
+                        let mut generated = Vec::new();
+                        for (parent, (state, cost)) in layer.iter().enumerate().take(end).skip(start) {
+                            for (action, successor, step_cost) in problem.get_successors(state) {
+                                let hash = problem.get_state_hash(&successor)
+                                    .unwrap_or_else(|| StateTable::hash_state(&successor));
+                                let shard = shard_of(hash);
+                                let successor = Arc::new(successor);
+                                let (id, inserted) = shards[shard].lock().unwrap().intern_hashed(successor.clone(), hash);
+                                if !inserted { continue; }
+                                generated.push((successor, Record {
+                                    shard,
+                                    state: id,
+                                    action: Some(action),
+                                    parent,
+                                    cost: cost + step_cost,
+                                }));
+                            }
+                        }
+                        if sender.send(generated).is_err() { break; }
+                    }
+                });
+                (jobs, results)
+            }).collect();
+
+            while found.is_none() && !frontier.is_empty() {
+                let slice_size = frontier.len().div_ceil(threads);
+                let slices = frontier.len().div_ceil(slice_size);
+                for (slice, (jobs, _)) in workers.iter().enumerate().take(slices) {
+                    let end = ((slice + 1) * slice_size).min(frontier.len());
+                    jobs.send((frontier.clone(), slice * slice_size, end)).unwrap();
+                }
+                // In slice order, a worker that panicked drops its sender
+                let (states, records): (Vec<_>, Vec<_>) = workers.iter().take(slices)
+                    .flat_map(|(_, results)| results.recv().expect("BFS worker panicked"))
+                    .unzip();
+                if stop_at_solution {
+                    found = states.iter().position(|state| problem.is_solution(state));
+                }
+                frontier = Arc::new(states.into_iter().zip(records.iter().map(|record| record.cost)).collect());
+                layers.push(records);
+            }
+        });
+        if frontier.is_empty() { layers.pop(); }
+        self.layer_sizes = layers.iter().map(|layer| layer.len()).collect();
+
+        if let Some(mut index) = found {
+            let cost = layers.last().unwrap()[index].cost;
+            let mut states = Vec::new();
+            let mut actions = Vec::new();
+            for layer in layers.iter().rev() {
+                let record = &layer[index];
+                states.push(P::State::clone(shards[record.shard].lock().unwrap().get(record.state)));
+                actions.extend(record.action.clone());
+                index = record.parent;
+            }
+            states.reverse();
+            actions.reverse();
+            self.solution = Some(Path { states, actions, cost });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cells of a size x size grid, moving right or down from the top left corner to the bottom right
+    struct Grid { size: usize }
+
+    impl Problem for Grid {
+        type State = (usize, usize);
+        type Action = char;
+
+        fn get_root(&self) -> (usize, usize) { (0, 0) }
+
+        fn get_successors(&self, &(x, y): &(usize, usize)) -> Vec<(char, (usize, usize), isize)> {
+            let mut successors = Vec::new();
+            if x + 1 < self.size { successors.push(('R', (x + 1, y), 1)) }
+            if y + 1 < self.size { successors.push(('D', (x, y + 1), 1)) }
+            successors
+        }
+
+        fn get_heuristic(&self, _state: &(usize, usize)) -> isize { 0 }
+        fn is_solution(&self, state: &(usize, usize)) -> bool { *state == (self.size - 1, self.size - 1) }
+    }
+
+    #[test]
+    fn layers_are_the_anti_diagonals() {
+        let problem = Grid { size: 5 };
+        for threads in [1, 4] {
+            let mut search = BFS::new(threads);
+            assert_eq!(search.count_reachable(&problem), 25);
+            assert_eq!(search.get_layer_sizes(), &[1, 2, 3, 4, 5, 4, 3, 2, 1]);
+        }
+    }
+
+    #[test]
+    fn finds_shortest_path() {
+        let problem = Grid { size: 5 };
+        for threads in [1, 4] {
+            let mut search = BFS::new(threads);
+            let path = search.find_solution(&problem).unwrap();
+            assert_eq!(path.states.first(), Some(&(0, 0)));
+            assert_eq!(path.states.last(), Some(&(4, 4)));
+            assert_eq!(path.states.len(), 9);
+            assert_eq!(path.cost, 8);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,320 @@
+use crate::traits::{
+    problem::Problem,
+    p_tuple::PTuple
+};
+use crate::utils::path::Path;
+use crate::utils::state_table::{StateId, StateTable};
+use std::collections::BinaryHeap;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/*
+ *  Hash distributed A*: every state is owned by the thread `hash % threads`, generated nodes are
+ *  sent to their owner which keeps its own open list, closed list and nodes.
+ *
+ *  Termination: `pending` counts messages in flight plus threads with nodes cheaper than the
+ *  incumbent in their open list. It is only increased by someone already holding a unit, so
+ *  once it reaches zero no work is left anywhere and the incumbent is optimal (admissible h).
+ *  A worker that panics never gives its unit back, it sets `abort` instead so the others stop.
+ */
+
+// Parent of a node as (thread, index in that thread's nodes)
+type NodeRef = (usize, usize);
+
+struct HNode<A> {
+    state: StateId,
+    action: Option<A>,
+    parent: Option<NodeRef>,
+    cost: isize,
+}
+
+struct Message<S, A> {
+    state: S,
+    hash: u64,
+    action: Option<A>,
+    parent: Option<NodeRef>,
+    cost: isize,
+}
+
+// `sent` and `received` only count states passed between workers
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ThreadStats {
+    pub expanded: usize,
+    pub generated: usize,
+    pub sent: usize,
+    pub received: usize,
+}
+
+struct Shared {
+    pending: AtomicUsize,
+    abort: AtomicBool,
+    incumbent_cost: AtomicIsize,
+    incumbent: Mutex<Option<NodeRef>>,
+}
+
+struct Worker<'a, P: Problem> {
+    id: usize,
+    problem: &'a P,
+    shared: &'a Shared,
+    senders: Vec<Sender<Message<P::State, P::Action>>>,
+    p_queue: BinaryHeap<PTuple<usize>>,
+    nodes: Vec<HNode<P::Action>>,
+    states: StateTable<P::State>,
+    costs: Vec<isize>,
+    active: bool,
+    stats: ThreadStats,
+}
+
+pub struct HDAStar<P: Problem> {
+    threads: usize,
+    stats: Vec<ThreadStats>,
+    solution: Option<Path<P::State, P::Action>>,
+}
+
+impl<P> HDAStar<P>
+where
+    P: Problem + Sync,
+    P::State: Clone + Send,
+    P::Action: Send,
+{
+    pub fn new(threads: usize) -> Self {
+        HDAStar { threads: threads.max(1), stats: Vec::new(), solution: None }
+    }
+
+    pub fn find_solution(&mut self, problem: &P) -> Option<&Path<P::State, P::Action>> {
+        let shared = Shared {
+            pending: AtomicUsize::new(1),
+            abort: AtomicBool::new(false),
+            incumbent_cost: AtomicIsize::new(isize::MAX),
+            incumbent: Mutex::new(None),
+        };
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..self.threads).map(|_| mpsc::channel()).unzip();
+
+        let root = problem.get_root();
+        let hash = Self::hash_state(problem, &root);
+        let owner = hash as usize % self.threads;
+        let mut root = Some(Message { state: root, hash, action: None, parent: None, cost: 0 });
+
+        let workers: Vec<Worker<P>> = thread::scope(|scope| {
+            let handles: Vec<_> = receivers.into_iter().enumerate()
+                .map(|(id, receiver)| {
+                    let mut worker = Worker {
+                        id,
+                        problem,
+                        shared: &shared,
+                        senders: senders.clone(),
+                        p_queue: BinaryHeap::new(),
+                        nodes: Vec::new(),
+                        states: StateTable::new(),
+                        costs: Vec::new(),
+                        active: false,
+                        stats: ThreadStats::default(),
+                    };
+                    // The owner of the root starts active, it is the one counted by `pending`
+                    if id == owner {
+                        worker.insert(root.take().unwrap());
+                        worker.active = true;
+                    }
+                    scope.spawn(move || { worker.run(receiver); worker })
+                })
+                .collect();
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload)))
+                .collect()
+        });
+
+        self.stats = workers.iter().map(|worker| worker.stats).collect();
+        let incumbent = *shared.incumbent.lock().unwrap();
+        self.solution = incumbent.map(|mut current| {
+            let mut states = Vec::new();
+            let mut actions = Vec::new();
+            let (thread, index) = current;
+            let cost = workers[thread].nodes[index].cost;
+            loop {
+                let worker = &workers[current.0];
+                let node = &worker.nodes[current.1];
+                states.push(worker.states.get(node.state).clone());
+                match (&node.action, node.parent) {
+                    (Some(action), Some(parent)) => {
+                        actions.push(action.clone());
+                        current = parent;
+                    }
+                    _ => break,
+                }
+            }
+            states.reverse();
+            actions.reverse();
+            Path { states, actions, cost }
+        });
+        self.solution.as_ref()
+    }
+
+    pub fn get_stats(&self) -> &[ThreadStats] { &self.stats }
+
+    fn hash_state(problem: &P, state: &P::State) -> u64 {
+        problem.get_state_hash(state).unwrap_or_else(|| StateTable::hash_state(state))
+    }
+}
+
+// Tells the other workers to stop when the worker holding it unwinds
+struct AbortOnPanic<'a>(&'a AtomicBool);
+
+impl Drop for AbortOnPanic<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() { self.0.store(true, Ordering::SeqCst); }
+    }
+}
+
+impl<'a, P> Worker<'a, P>
+where
+    P: Problem + Sync,
+    P::State: Clone + Send,
+    P::Action: Send,
+{
+    fn run(&mut self, receiver: Receiver<Message<P::State, P::Action>>) {
+        let _guard = AbortOnPanic(&self.shared.abort);
+        loop {
+            if self.shared.abort.load(Ordering::SeqCst) { break; }
+            while let Ok(message) = receiver.try_recv() {
+                self.receive(message);
+            }
+            if self.active && !self.has_work() {
+                self.active = false;
+                self.shared.pending.fetch_sub(1, Ordering::SeqCst);
+            }
+            if self.active {
+                self.expand();
+                continue;
+            }
+            if self.shared.pending.load(Ordering::SeqCst) == 0 { break; }
+            match receiver.recv_timeout(Duration::from_micros(100)) {
+                Ok(message) => self.receive(message),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    fn has_work(&self) -> bool {
+        let incumbent = self.shared.incumbent_cost.load(Ordering::SeqCst);
+        self.p_queue.peek().map(|top| top.priority < incumbent).unwrap_or(false)
+    }
+
+    fn receive(&mut self, message: Message<P::State, P::Action>) {
+        self.stats.received += 1;
+        self.insert(message);
+        // Become active before releasing the message so `pending` can't drop to zero in between
+        if !self.active && self.has_work() {
+            self.active = true;
+            self.shared.pending.fetch_add(1, Ordering::SeqCst);
+        }
+        self.shared.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn insert(&mut self, message: Message<P::State, P::Action>) {
+        let Message { state, hash, action, parent, cost } = message;
+        let (id, inserted) = self.states.intern_hashed(state, hash);
+        if inserted { self.costs.push(isize::MAX); }
+        if self.costs[id] <= cost { return }
+        self.costs[id] = cost;
+        let priority = cost + self.problem.get_heuristic(self.states.get(id));
+        if priority >= self.shared.incumbent_cost.load(Ordering::SeqCst) { return }
+        self.nodes.push(HNode { state: id, action, parent, cost });
+        self.p_queue.push(PTuple { priority, node: self.nodes.len() - 1 });
+    }
+
+    fn expand(&mut self) {
+        let PTuple { priority: _, node } = self.p_queue.pop().unwrap();
+        let HNode { state, cost, .. } = self.nodes[node];
+        if cost > self.costs[state] { return }
+
+        if self.problem.is_solution(self.states.get(state)) {
+            let mut incumbent = self.shared.incumbent.lock().unwrap();
+            if cost < self.shared.incumbent_cost.load(Ordering::SeqCst) {
+                self.shared.incumbent_cost.store(cost, Ordering::SeqCst);
+                *incumbent = Some((self.id, node));
+            }
+            return
+        }
+
+        self.stats.expanded += 1;
+        let threads = self.senders.len();
+        for (action, successor, step_cost) in self.problem.get_successors(self.states.get(state)) {
+            self.stats.generated += 1;
+            let hash = HDAStar::<P>::hash_state(self.problem, &successor);
+            let message = Message {
+                state: successor,
+                hash,
+                action: Some(action),
+                parent: Some((self.id, node)),
+                cost: cost + step_cost,
+            };
+            let owner = hash as usize % threads;
+            if owner == self.id {
+                self.insert(message);
+            } else {
+                self.stats.sent += 1;
+                self.shared.pending.fetch_add(1, Ordering::SeqCst);
+                // The owner only drops its receiver once finished or after a panic, `abort` is set then
+                if self.senders[owner].send(message).is_err() { return }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // States 0..=goal on a line, steps of 1 and 2 cost 1 and 3, panics when expanding `panic_at`
+    struct Line { goal: usize, panic_at: Option<usize> }
+
+    impl Problem for Line {
+        type State = usize;
+        type Action = usize;
+
+        fn get_root(&self) -> usize { 0 }
+
+        fn get_successors(&self, state: &usize) -> Vec<(usize, usize, isize)> {
+            if Some(*state) == self.panic_at { panic!("expanding {}", state) }
+            [(1, 1), (2, 3)].into_iter()
+                .filter(|(step, _)| state + step <= self.goal)
+                .map(|(step, cost)| (step, state + step, cost))
+                .collect()
+        }
+
+        fn get_heuristic(&self, state: &usize) -> isize { (self.goal - state) as isize }
+        fn is_solution(&self, state: &usize) -> bool { *state == self.goal }
+    }
+
+    #[test]
+    fn finds_optimal_path() {
+        let problem = Line { goal: 12, panic_at: None };
+        for threads in [1, 4] {
+            let mut search = HDAStar::new(threads);
+            let path = search.find_solution(&problem).unwrap();
+            assert_eq!(path.cost, 12);
+            assert_eq!(path.states, (0..=12).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn every_thread_counts_its_own_work() {
+        let mut search = HDAStar::new(4);
+        assert_eq!(search.find_solution(&Line { goal: 40, panic_at: None }).unwrap().cost, 40);
+        let stats = search.get_stats();
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats.iter().map(|stats| stats.sent).sum::<usize>(), stats.iter().map(|stats| stats.received).sum());
+        assert!(stats.iter().filter(|stats| stats.expanded > 0).count() > 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "expanding 5")]
+    fn passes_on_worker_panics() {
+        HDAStar::new(4).find_solution(&Line { goal: 12, panic_at: Some(5) });
+    }
+}
\ No newline at end of file
@@ -0,0 +1,244 @@
+use super::traits::problem::{Problem, ProblemAlgorithm, Step};
+use super::utils::arena::NodeId;
+use super::utils::path::Path;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/*
+ *  Races several algorithms on the same problem, either on one thread switching between them
+ *  every `slice` steps (`run`) or with one thread per member (`run_concurrent`)
+ */
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PortfolioMode {
+    // Stop at the first solution found by any member
+    First,
+    // Keep the cheapest solution until every member finished or the deadline passed
+    Best,
+}
+
+pub struct PortfolioResult<S, A> {
+    pub winner: usize,
+    pub name: String,
+    pub path: Path<S, A>,
+}
+
+type Member<'a, P> = (String, Box<dyn ProblemAlgorithm<P> + Send + 'a>);
+
+pub struct Portfolio<'a, P: Problem> {
+    members: Vec<Member<'a, P>>,
+    mode: PortfolioMode,
+    deadline: Option<Duration>,
+    slice: usize,
+}
+
+impl<'a, P> Portfolio<'a, P>
+where
+    P: Problem,
+    P::State: Clone,
+{
+    pub fn new(mode: PortfolioMode) -> Self {
+        Portfolio { members: Vec::new(), mode, deadline: None, slice: 1000 }
+    }
+
+    pub fn add<A: ProblemAlgorithm<P> + Send + 'a>(&mut self, name: &str, algorithm: A) -> &mut Self {
+        self.members.push((name.to_owned(), Box::new(algorithm)));
+        self
+    }
+
+    pub fn set_deadline(&mut self, deadline: Duration) -> &mut Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    // Steps given to each member before switching to the next one in `run`
+    pub fn set_slice(&mut self, steps: usize) -> &mut Self {
+        self.slice = steps.max(1);
+        self
+    }
+
+    pub fn run(&mut self, problem: &P) -> Option<PortfolioResult<P::State, P::Action>> {
+        let time_start = Instant::now();
+        let deadline = self.deadline;
+        let expired = |time_start: Instant| deadline.map(|d| time_start.elapsed() >= d).unwrap_or(false);
+        let mut finished = vec![false; self.members.len()];
+        let mut best: Option<(usize, NodeId, isize)> = None;
+        for (_, algorithm) in self.members.iter_mut() {
+            algorithm.start(problem);
+        }
+
+        'outer: while finished.iter().any(|f| !f) {
+            for (i, (_, algorithm)) in self.members.iter_mut().enumerate() {
+                if expired(time_start) { break 'outer; }
+                if finished[i] { continue; }
+                for _ in 0..self.slice {
+                    let (node, done) = match algorithm.step(problem) {
+                        Step::Running => continue,
+                        Step::Improved(node) => (Some(node), false),
+                        Step::Solved(node) => (Some(node), true),
+                        Step::Exhausted => (None, true),
+                    };
+                    finished[i] = done;
+                    if let Some(node) = node {
+                        let cost = algorithm.get_arena().get(node).cost;
+                        if best.map(|(_, _, best_cost)| cost < best_cost).unwrap_or(true) {
+                            best = Some((i, node, cost));
+                        }
+                        if self.mode == PortfolioMode::First { break 'outer; }
+                    }
+                    if done { break; }
+                }
+            }
+        }
+        best.map(|(winner, node, _)| self.build_result(winner, node))
+    }
+
+    pub fn run_concurrent(&mut self, problem: &P) -> Option<PortfolioResult<P::State, P::Action>>
+    where
+        P: Sync,
+    {
+        let time_start = Instant::now();
+        let stop = AtomicBool::new(false);
+        let first = AtomicUsize::new(usize::MAX);
+        let (mode, deadline) = (self.mode, self.deadline);
+        let expired = |time_start: Instant| deadline.map(|d| time_start.elapsed() >= d).unwrap_or(false);
+
+        let results: Vec<Option<NodeId>> = thread::scope(|scope| {
+            let handles: Vec<_> = self.members.iter_mut().enumerate()
+                .map(|(i, (_, algorithm))| {
+                    let (stop, first) = (&stop, &first);
+                    scope.spawn(move || {
+                        algorithm.start(problem);
+                        let mut best: Option<NodeId> = None;
+                        while !stop.load(Ordering::Relaxed) && !expired(time_start) {
+                            let (node, done) = match algorithm.step(problem) {
+                                Step::Running => continue,
+                                Step::Improved(node) => (Some(node), false),
+                                Step::Solved(node) => (Some(node), true),
+                                Step::Exhausted => (None, true),
+                            };
+                            if let Some(node) = node {
+                                let cost = algorithm.get_arena().get(node).cost;
+                                if best.map(|b| cost < algorithm.get_arena().get(b).cost).unwrap_or(true) {
+                                    best = Some(node);
+                                }
+                                if mode == PortfolioMode::First {
+                                    // Only the first member to find a solution wins, the rest are cancelled
+                                    let _ = first.compare_exchange(usize::MAX, i, Ordering::SeqCst, Ordering::SeqCst);
+                                    stop.store(true, Ordering::SeqCst);
+                                }
+                            }
+                            if done { break; }
+                        }
+                        best
+                    })
+                })
+                .collect();
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload)))
+                .collect()
+        });
+
+        let winner = match mode {
+            PortfolioMode::First => Some(first.into_inner()).filter(|i| *i != usize::MAX),
+            PortfolioMode::Best => results.iter().enumerate()
+                .filter_map(|(i, node)| node.map(|node| (i, self.members_cost(i, node))))
+                .min_by_key(|(_, cost)| *cost)
+                .map(|(i, _)| i),
+        };
+        winner.map(|winner| self.build_result(winner, results[winner].unwrap()))
+    }
+
+    fn members_cost(&self, member: usize, node: NodeId) -> isize {
+        self.members[member].1.get_arena().get(node).cost
+    }
+
+    fn build_result(&self, winner: usize, node: NodeId) -> PortfolioResult<P::State, P::Action> {
+        let (name, algorithm) = &self.members[winner];
+        PortfolioResult {
+            winner,
+            name: name.clone(),
+            path: Path::from_arena(algorithm.get_arena(), algorithm.get_states(), node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::{a_star::AStar, bfs::BFS, dfbnb::DFBnB, dijkstra::Dijkstra};
+    use crate::utils::fixtures::Grid;
+
+    // The grid, panics when asked for the successors of `(4, 0)`
+    struct Trap;
+
+    impl Problem for Trap {
+        type State = (usize, usize);
+        type Action = char;
+
+        fn get_root(&self) -> (usize, usize) { Grid.get_root() }
+
+        fn get_successors(&self, state: &(usize, usize)) -> Vec<(char, (usize, usize), isize)> {
+            if *state == (4, 0) { panic!("trapped") }
+            Grid.get_successors(state)
+        }
+
+        fn get_heuristic(&self, state: &(usize, usize)) -> isize { Grid.get_heuristic(state) }
+        fn is_solution(&self, state: &(usize, usize)) -> bool { Grid.is_solution(state) }
+    }
+
+    fn optimum() -> isize {
+        let mut dijkstra = Dijkstra::new();
+        let solution = dijkstra.find_solution(&Grid).unwrap();
+        dijkstra.get_arena().get(solution).cost
+    }
+
+    #[test]
+    fn first_stops_at_the_first_improvement() {
+        let mut first = Portfolio::new(PortfolioMode::First);
+        first.add("dfbnb", DFBnB::new());
+        let mut best = Portfolio::new(PortfolioMode::Best);
+        best.add("dfbnb", DFBnB::new());
+        let first = first.run(&Grid).unwrap().path.cost;
+        assert_eq!(best.run(&Grid).unwrap().path.cost, optimum());
+        assert!(first > optimum());
+    }
+
+    #[test]
+    fn both_runs_agree_on_the_best_cost() {
+        let mut portfolio = Portfolio::new(PortfolioMode::Best);
+        portfolio.add("bfs", BFS::new()).add("a_star", AStar::new()).add("dfbnb", DFBnB::new()).set_slice(3);
+        let sequential = portfolio.run(&Grid).unwrap();
+        let concurrent = portfolio.run_concurrent(&Grid).unwrap();
+        assert_eq!(sequential.path.cost, optimum());
+        assert_eq!(concurrent.path.cost, optimum());
+        assert_ne!(concurrent.name, "bfs");
+    }
+
+    #[test]
+    fn first_concurrent_returns_the_winner_path() {
+        let mut portfolio = Portfolio::new(PortfolioMode::First);
+        portfolio.add("bfs", BFS::new()).add("a_star", AStar::new());
+        let result = portfolio.run_concurrent(&Grid).unwrap();
+        assert_eq!(result.name, ["bfs", "a_star"][result.winner]);
+        assert_eq!(result.path.states.last(), Some(&(4, 4)));
+    }
+
+    #[test]
+    fn nothing_is_found_past_the_deadline() {
+        let mut portfolio = Portfolio::new(PortfolioMode::Best);
+        portfolio.add("a_star", AStar::new()).set_deadline(Duration::ZERO);
+        assert!(portfolio.run(&Grid).is_none());
+        assert!(portfolio.run_concurrent(&Grid).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "trapped")]
+    fn passes_on_member_panics() {
+        let mut portfolio = Portfolio::new(PortfolioMode::Best);
+        portfolio.add("bfs", BFS::new()).add("dijkstra", Dijkstra::new());
+        portfolio.run_concurrent(&Trap);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,145 @@
+use super::traits::{
+    algorithm::{Algorithm, AlgorithmExt},
+    multi_objective::covers,
+    p_tuple::PTuple,
+    resources::ResourceNode
+};
+use super::utils::memory::{rc_size, MemoryBudget, MemoryUsage};
+use super::utils::state_table::StateTable;
+use std::collections::BinaryHeap;
+use std::mem::size_of;
+use std::rc::Rc;
+
+/*
+ *  A* over labels (cost and consumed resources) for the cheapest solution within resource limits.
+ *  Labels that would go over a limit, counting the resource heuristics, are pruned. Every state keeps
+ *  its non-dominated labels (no higher cost nor any resource), a label covered by one of them is
+ *  discarded and the ones it dominates are dropped from the frontier.
+ */
+
+pub struct ResourceConstrained<T: ResourceNode> {
+    limits: Vec<isize>,
+    p_queue: BinaryHeap<PTuple<(Box<T>, Vec<isize>)>>,
+    states: StateTable<Rc<T::State>>,
+    // Cost followed by the resources of the non-dominated labels at every state, indexed by state id
+    labels: Vec<Vec<Vec<isize>>>,
+    expanded: usize,
+    infeasible: usize,
+    dominated: usize,
+    memory: MemoryBudget<T::State>,
+}
+
+impl<T: ResourceNode> ResourceConstrained<T> {
+    pub fn new(limits: Vec<isize>) -> Self {
+        ResourceConstrained {
+            limits,
+            p_queue: BinaryHeap::new(),
+            states: StateTable::new(),
+            labels: Vec::new(),
+            expanded: 0,
+            infeasible: 0,
+            dominated: 0,
+            memory: MemoryBudget::new(),
+        }
+    }
+
+    pub fn get_expanded(&self) -> usize { self.expanded }
+    // Labels pruned for going over a limit, and for being dominated
+    pub fn get_infeasible(&self) -> usize { self.infeasible }
+    pub fn get_dominated(&self) -> usize { self.dominated }
+
+    fn push(&mut self, node: Box<T>) {
+        let resources = node.get_resources();
+        let feasible = resources.iter().zip(node.get_resource_heuristics()).zip(&self.limits)
+            .all(|((used, remaining), limit)| used + remaining <= *limit);
+        if !feasible {
+            self.infeasible += 1;
+            return
+        }
+        let label = [vec![node.get_cost()], resources].concat();
+        let (id, inserted) = self.states.intern_with(node.get_state(), node.get_state_hash());
+        if inserted {
+            self.labels.push(Vec::new());
+            self.memory.add_state(self.states.get(id));
+        }
+        if self.labels[id].iter().any(|other| covers(other, &label)) {
+            self.dominated += 1;
+            return
+        }
+        let before = self.labels[id].len();
+        self.labels[id].retain(|other| !covers(&label, other));
+        self.dominated += before - self.labels[id].len();
+        self.labels[id].push(label.clone());
+        self.p_queue.push(PTuple { priority: node.get_cost() + node.get_heuristic(), node: (node, label) });
+    }
+}
+
+impl<T: ResourceNode> Algorithm<T> for ResourceConstrained<T> {
+    fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>> {
+        self.memory.reset();
+        self.p_queue.clear();
+        self.states.clear();
+        self.labels.clear();
+        self.expanded = 0;
+        self.infeasible = 0;
+        self.dominated = 0;
+        self.push(root);
+
+        while let Some(PTuple { node: (node, label), .. }) = self.p_queue.pop() {
+            let hash = node.get_state_hash().unwrap_or_else(|| StateTable::hash_state(&node.get_state()));
+            let id = self.states.get_id_hashed(&node.get_state(), hash).unwrap();
+            // Stale label, a label dominating it was found after pushing it
+            if !self.labels[id].contains(&label) { continue; }
+            if node.is_solution() { return Some(node) }
+            if self.memory.check(&self.get_memory_usage()) { return None }
+            self.expanded += 1;
+            for descendant in node.get_descendants() {
+                self.push(descendant);
+            }
+        }
+        None
+    }
+
+    fn get_visited(&self) -> Vec<&Rc<T::State>> {
+        self.states.iter().collect()
+    }
+}
+
+impl<T: ResourceNode> AlgorithmExt<T> for ResourceConstrained<T> {
+    fn get_states(&self) -> &StateTable<Rc<T::State>> { &self.states }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        let label_size = size_of::<Vec<isize>>() + (self.limits.len() + 1) * size_of::<isize>();
+        let labels: usize = self.labels.iter().map(|labels| labels.len()).sum();
+        MemoryUsage {
+            frontier: self.p_queue.capacity() * (size_of::<PTuple<(Box<T>, Vec<isize>)>>() + label_size),
+            closed: self.states.get_memory_usage()
+                + self.states.len() * rc_size::<T::State>()
+                + self.labels.capacity() * size_of::<Vec<Vec<isize>>>()
+                + labels * label_size
+                + self.memory.get_state_bytes(),
+            nodes: (self.p_queue.len() + self.expanded) * rc_size::<T>(),
+        }
+    }
+
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State> { &mut self.memory }
+    fn is_limit_reached(&self) -> bool { self.memory.is_reached() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::node::Node;
+    use crate::utils::fixtures::EdgeNode;
+
+    #[test]
+    fn finds_the_cheapest_path_within_the_limits() {
+        let mut search = ResourceConstrained::new(vec![8]);
+        for _ in 0..2 {
+            let solution = search.find_solution(Box::new(EdgeNode::root())).unwrap();
+            assert_eq!((solution.get_cost(), solution.get_resources()), (5, vec![7]));
+            assert_eq!(search.get_infeasible(), 1);
+        }
+        assert!(ResourceConstrained::new(vec![1]).find_solution(Box::new(EdgeNode::root())).is_none());
+    }
+}
\ No newline at end of file
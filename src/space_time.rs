@@ -0,0 +1,203 @@
+use super::traits::problem::Problem;
+use super::utils::reservation::ReservationTable;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TimedAction<A> {
+    Move(A),
+    Wait,
+}
+
+// Whether the state is blocked at the timestep
+pub type BlockedFn<S> = Box<dyn Fn(&S, usize) -> bool>;
+
+/*
+ *  Time-expanded view of a problem for the arena algorithms: states are (state, timestep), every step
+ *  takes one timestep and the agent may also wait in place. Successors taken in the reservation table
+ *  or blocked by the callback are discarded, and nothing is generated past the horizon. Waits are
+ *  pruned once nothing is blocked later on, as they can no longer help.
+ *
+ *  Solutions are solution states that are never blocked again, so the agent can stay there.
+ */
+
+pub struct SpaceTime<'a, P: Problem> {
+    problem: &'a P,
+    horizon: usize,
+    start: usize,
+    wait_cost: isize,
+    reservations: ReservationTable<P::State>,
+    // The callback blocks nothing from the given timestep on
+    blocked: Option<(BlockedFn<P::State>, usize)>,
+}
+
+impl<'a, P: Problem> SpaceTime<'a, P> where P::State: Clone {
+    pub fn new(problem: &'a P, horizon: usize) -> Self {
+        SpaceTime {
+            problem,
+            horizon,
+            start: 0,
+            wait_cost: 1,
+            reservations: ReservationTable::new(),
+            blocked: None,
+        }
+    }
+
+    // Timestep of the root
+    pub fn set_start_time(&mut self, start: usize) -> &mut Self {
+        self.start = start;
+        self
+    }
+
+    pub fn set_wait_cost(&mut self, cost: isize) -> &mut Self {
+        self.wait_cost = cost;
+        self
+    }
+
+    pub fn set_reservations(&mut self, reservations: ReservationTable<P::State>) -> &mut Self {
+        self.reservations = reservations;
+        self
+    }
+
+    pub fn set_blocked(&mut self, blocked: BlockedFn<P::State>, until: usize) -> &mut Self {
+        self.blocked = Some((blocked, until));
+        self
+    }
+
+    pub fn get_reservations(&mut self) -> &mut ReservationTable<P::State> { &mut self.reservations }
+
+    fn is_blocked(&self, from: &P::State, to: &P::State, time: usize) -> bool {
+        self.reservations.is_move_reserved(from, to, time)
+            || self.blocked.as_ref().map(|(blocked, until)| time < *until && blocked(to, time)).unwrap_or(false)
+    }
+
+    // Nothing is blocked after this timestep
+    fn get_static_time(&self) -> usize {
+        let until = self.blocked.as_ref().map(|(_, until)| *until).unwrap_or(0);
+        self.reservations.get_last_time().max(until)
+    }
+}
+
+impl<'a, P: Problem> Problem for SpaceTime<'a, P> where P::State: Clone {
+    type State = (P::State, usize);
+    type Action = TimedAction<P::Action>;
+
+    fn get_root(&self) -> Self::State {
+        (self.problem.get_root(), self.start)
+    }
+
+    fn get_successors(&self, (state, time): &Self::State) -> Vec<(Self::Action, Self::State, isize)> {
+        if *time >= self.horizon { return Vec::new() }
+        let mut successors: Vec<(Self::Action, Self::State, isize)> = self.problem.get_successors(state).into_iter()
+            .filter(|(_, next, _)| !self.is_blocked(state, next, time + 1))
+            .map(|(action, next, cost)| (TimedAction::Move(action), (next, time + 1), cost))
+            .collect();
+        if *time < self.get_static_time() && !self.is_blocked(state, state, time + 1) {
+            successors.push((TimedAction::Wait, (state.clone(), time + 1), self.wait_cost));
+        }
+        successors
+    }
+
+    fn get_heuristic(&self, (state, _): &Self::State) -> isize {
+        self.problem.get_heuristic(state)
+    }
+
+    fn is_solution(&self, (state, time): &Self::State) -> bool {
+        self.problem.is_solution(state)
+            && !self.reservations.is_reserved_after(state, *time)
+            && self.blocked.as_ref().map(|(blocked, until)| (time + 1..*until).all(|later| !blocked(state, later))).unwrap_or(true)
+    }
+
+    fn get_state_hash(&self, (state, time): &Self::State) -> Option<u64> {
+        self.problem.get_state_hash(state).map(|hash| hash ^ (*time as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::a_star::AStar;
+    use crate::traits::problem::ProblemAlgorithm;
+    use crate::utils::path::Path;
+
+    // Cells 0 to 5 on a line from 0 to 4
+    struct Corridor;
+
+    impl Problem for Corridor {
+        type State = usize;
+        type Action = char;
+
+        fn get_root(&self) -> usize { 0 }
+
+        fn get_successors(&self, &cell: &usize) -> Vec<(char, usize, isize)> {
+            [('L', cell.wrapping_sub(1)), ('R', cell + 1)].into_iter()
+                .filter(|(_, next)| *next < 6)
+                .map(|(action, next)| (action, next, 1))
+                .collect()
+        }
+
+        fn get_heuristic(&self, &cell: &usize) -> isize { (cell as isize - 4).abs() }
+        fn is_solution(&self, &cell: &usize) -> bool { cell == 4 }
+    }
+
+    fn solve(problem: &SpaceTime<Corridor>) -> Option<Path<(usize, usize), TimedAction<char>>> {
+        let mut a_star = AStar::new();
+        let solution = a_star.find_solution(problem)?;
+        Some(Path::from_arena(a_star.get_arena(), a_star.get_states(), solution))
+    }
+
+    #[test]
+    fn waits_for_a_reserved_cell() {
+        let mut problem = SpaceTime::new(&Corridor, 20);
+        problem.get_reservations().reserve(2, 2);
+        let path = solve(&problem).unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(path.actions.iter().filter(|action| **action == TimedAction::Wait).count(), 1);
+        assert!(!path.states.contains(&(2, 2)));
+        assert_eq!(path.states.last(), Some(&(4, 5)));
+    }
+
+    #[test]
+    fn never_swaps_places() {
+        // The other agent moves from 2 to 1 arriving at 2, when this one would move from 1 to 2
+        let mut problem = SpaceTime::new(&Corridor, 20);
+        problem.get_reservations().reserve_path(&[2, 1], 1);
+        let path = solve(&problem).unwrap();
+        assert!(path.states.windows(2).all(|pair| pair != [(1, 1), (2, 2)]));
+        assert_eq!(path.cost, 6);
+
+        // Following the other agent in the same direction is allowed
+        let mut open = SpaceTime::new(&Corridor, 20);
+        open.get_reservations().reserve_path(&[3, 4], 1);
+        open.get_reservations().reserve(5, 3);
+        assert_eq!(solve(&open).unwrap().cost, 4);
+    }
+
+    #[test]
+    fn stops_at_the_horizon() {
+        assert!(solve(&SpaceTime::new(&Corridor, 3)).is_none());
+        assert_eq!(solve(&SpaceTime::new(&Corridor, 4)).unwrap().cost, 4);
+
+        // The horizon is a timestep, not a number of steps
+        let mut late = SpaceTime::new(&Corridor, 13);
+        late.set_start_time(10);
+        assert!(solve(&late).is_none());
+        let mut late = SpaceTime::new(&Corridor, 14);
+        late.set_start_time(10);
+        assert_eq!(solve(&late).unwrap().states.last(), Some(&(4, 14)));
+    }
+
+    #[test]
+    fn goals_must_stay_free() {
+        // Someone passes through the goal at 6, arriving before is not a solution
+        let mut problem = SpaceTime::new(&Corridor, 20);
+        problem.get_reservations().reserve(4, 6);
+        assert!(!problem.is_solution(&(4, 4)));
+        assert!(problem.is_solution(&(4, 7)));
+        assert_eq!(solve(&problem).unwrap().states.last(), Some(&(4, 7)));
+
+        let mut problem = SpaceTime::new(&Corridor, 20);
+        problem.set_blocked(Box::new(|cell, time| *cell == 4 && time == 6), 10);
+        assert!(!problem.is_solution(&(4, 4)));
+        assert!(problem.is_solution(&(4, 7)));
+        assert_eq!(solve(&problem).unwrap().states.last(), Some(&(4, 7)));
+    }
+}
\ No newline at end of file
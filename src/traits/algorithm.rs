@@ -1,7 +1,22 @@
 use std::rc::Rc;
 use super::node::Node;
+use crate::utils::memory::{MemoryBudget, MemoryUsage};
+use crate::utils::state_table::StateTable;
 
 pub trait Algorithm<T: Node> {
     fn find_solution(&mut self, root: Box<T>) -> Option<Box<T>>;
     fn get_visited(&self) -> Vec<&Rc<T::State>>;
-}
\ No newline at end of file
+}
+
+// Implemented by the algorithms of the crate, `Algorithm` alone is enough for your own
+pub trait AlgorithmExt<T: Node>: Algorithm<T> {
+    // Ids are assigned in generation order, `get_visited()[id]` is the state with that id.
+    // The table keeps one `Rc` per distinct state, duplicates are dropped when interned: `get_visited`
+    // hands out `&Rc<State>` and states are not required to be `Clone`, so they are not copied out
+    fn get_states(&self) -> &StateTable<Rc<T::State>>;
+    fn get_memory_usage(&self) -> MemoryUsage;
+    // Configure a limit and state size function before searching
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<T::State>;
+    // The search stopped early because the memory limit was reached
+    fn is_limit_reached(&self) -> bool;
+}
@@ -0,0 +1,23 @@
+use std::hash::Hash;
+
+// Possible resulting states of an action with their probability
+pub type Outcomes<S> = Vec<(S, f64)>;
+
+/*
+ *  Nondeterministic counterpart of `Problem`: an action may lead to several states and the
+ *  agent only learns which one after taking it, solutions are policies instead of paths
+ */
+
+pub trait AndOrProblem {
+    type State: Eq + Hash + Clone;
+    type Action: Clone;
+
+    fn get_root(&self) -> Self::State;
+    // (action, step cost, outcomes)
+    fn get_actions(&self, state: &Self::State) -> Vec<(Self::Action, isize, Outcomes<Self::State>)>;
+    fn get_heuristic(&self, state: &Self::State) -> isize;
+    fn is_solution(&self, state: &Self::State) -> bool;
+
+    // Opt-in precomputed hash of the state, must be equal for equal states
+    fn get_state_hash(&self, _state: &Self::State) -> Option<u64> { None }
+}
\ No newline at end of file
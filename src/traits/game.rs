@@ -0,0 +1,29 @@
+use std::hash::Hash;
+use crate::utils::state_table::StateTable;
+
+/*
+ *  Player 0 maximizes the evaluation, every other player minimizes it.
+ *  This covers two player zero-sum games and one agent against a team (ex: Pacman and ghosts).
+ *  Chance nodes (ex: randomly moving ghosts) are only understood by expectimax.
+ */
+
+pub trait GameState: Eq + Hash + Sized {
+    type Move: Clone + PartialEq;
+
+    // Player to move, in 0..get_players()
+    fn get_player(&self) -> usize;
+    fn get_players(&self) -> usize;
+    fn get_moves(&self) -> Vec<Self::Move>;
+    fn apply(&self, action: &Self::Move) -> Self;
+    fn is_terminal(&self) -> bool;
+    // From the point of view of player 0
+    fn evaluate(&self) -> isize;
+
+    // Chance nodes take a random outcome instead of a move chosen by a player
+    fn is_chance(&self) -> bool { false }
+    // (outcome, probability) of a chance node, probabilities sum to 1
+    fn get_outcomes(&self) -> Vec<(Self::Move, f64)> { Vec::new() }
+
+    // Used by transposition tables, override for incremental hashing (ex: Zobrist)
+    fn get_state_hash(&self) -> u64 { StateTable::<Self>::hash_state(self) }
+}
\ No newline at end of file
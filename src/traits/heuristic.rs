@@ -0,0 +1,15 @@
+/*
+ *  Estimate of the cost left from a state to a solution, kept apart from `Node::get_heuristic` and
+ *  `Problem::get_heuristic` so several can be combined (`heuristics::combinators`) and checked
+ *  (`heuristics::checker`) before one of them is returned there. Closures over the state are heuristics.
+ */
+
+pub trait Heuristic<S> {
+    fn get_heuristic(&self, state: &S) -> isize;
+}
+
+impl<S, F: Fn(&S) -> isize> Heuristic<S> for F {
+    fn get_heuristic(&self, state: &S) -> isize { self(state) }
+}
+
+pub type BoxedHeuristic<S> = Box<dyn Heuristic<S> + Send + Sync>;
\ No newline at end of file
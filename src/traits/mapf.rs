@@ -0,0 +1,13 @@
+use std::hash::Hash;
+
+/*
+ *  Graph shared by the agents of a multi-agent path finding problem: every move takes one timestep
+ *  and agents may also wait where they are. Moves must be reversible (an undirected graph), the
+ *  distances to the goals are computed by breadth-first search from them.
+ */
+
+pub trait MapfGraph {
+    type Vertex: Eq + Hash + Clone;
+
+    fn get_neighbours(&self, vertex: &Self::Vertex) -> Vec<Self::Vertex>;
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+use super::node::Node;
+
+/*
+ *  Node with several costs to minimize at once (ex: arrival time and transfers), every node of a
+ *  problem returns vectors of the same length. Paths are compared by dominance at the state they
+ *  reach, so the cost of the rest of a path may only depend on the state. `get_cost` and
+ *  `get_heuristic` are not used by the multi-objective algorithms.
+ */
+
+pub trait MultiObjectiveNode: Node {
+    fn get_costs(&self) -> Vec<isize>;
+    // Admissible estimate of every remaining cost
+    fn get_heuristics(&self) -> Vec<isize> { vec![0; self.get_costs().len()] }
+}
+
+// Not worse in any cost and better in at least one
+pub fn dominates(a: &[isize], b: &[isize]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y) && a != b
+}
+
+// Not worse in any cost
+pub fn covers(a: &[isize], b: &[isize]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y)
+}
\ No newline at end of file
@@ -12,4 +12,7 @@ pub trait Node {
     fn get_cost(&self) -> isize;
     fn get_heuristic(&self) -> isize;
     fn is_solution(&self) -> bool;
+
+    // Opt-in precomputed hash of the state (ex: incremental Zobrist), must be equal for equal states
+    fn get_state_hash(&self) -> Option<u64> { None }
 }
\ No newline at end of file
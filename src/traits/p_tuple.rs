@@ -8,14 +8,13 @@ pub struct PTuple<T> {
 impl<T> Ord for PTuple<T> {
     fn cmp(&self, other: &Self) -> Ordering {
         // Inverse order: min goes first
-        self.partial_cmp(other).unwrap()
+        other.priority.cmp(&self.priority)
     }
 }
 
 impl<T> PartialOrd for PTuple<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        // Inverse order: min goes first
-        other.priority.partial_cmp(&self.priority)
+        Some(self.cmp(other))
     }
 }
 
@@ -0,0 +1,57 @@
+use std::hash::Hash;
+use crate::utils::arena::{Arena, NodeId};
+use crate::utils::memory::{MemoryBudget, MemoryUsage};
+use crate::utils::state_table::StateTable;
+
+/*
+ *  Allocation-free alternative to `Node`: the problem only describes the state space,
+ *  the algorithms keep nodes in an arena and states interned in a table
+ */
+
+pub trait Problem {
+    type State: Eq + Hash;
+    type Action: Clone;
+
+    fn get_root(&self) -> Self::State;
+    // (action, resulting state, step cost)
+    fn get_successors(&self, state: &Self::State) -> Vec<(Self::Action, Self::State, isize)>;
+    fn get_heuristic(&self, state: &Self::State) -> isize;
+    fn is_solution(&self, state: &Self::State) -> bool;
+
+    // Opt-in precomputed hash of the state, must be equal for equal states
+    fn get_state_hash(&self, _state: &Self::State) -> Option<u64> { None }
+}
+
+pub enum Step {
+    Running,
+    // Anytime algorithms found a better solution and keep searching
+    Improved(NodeId),
+    Solved(NodeId),
+    Exhausted,
+}
+
+pub trait ProblemAlgorithm<P: Problem> {
+    // Drops everything from a previous search, the same instance can be started again
+    fn start(&mut self, problem: &P);
+    // Expands a single node
+    fn step(&mut self, problem: &P) -> Step;
+
+    fn find_solution(&mut self, problem: &P) -> Option<NodeId> {
+        self.start(problem);
+        let mut best = None;
+        loop {
+            match self.step(problem) {
+                Step::Running => (),
+                Step::Improved(node) => best = Some(node),
+                Step::Solved(node) => return Some(node),
+                Step::Exhausted => return best,
+            }
+        }
+    }
+
+    fn get_arena(&self) -> &Arena<P::Action>;
+    fn get_states(&self) -> &StateTable<P::State>;
+    fn get_memory_usage(&self) -> MemoryUsage;
+    fn get_memory_budget(&mut self) -> &mut MemoryBudget<P::State>;
+    fn is_limit_reached(&self) -> bool;
+}
\ No newline at end of file
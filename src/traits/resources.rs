@@ -0,0 +1,14 @@
+use super::node::Node;
+
+/*
+ *  Node consuming resources along its path (ex: flight duration, crane moves), every node of a
+ *  problem returns vectors of the same length. Consumption may not be negative, and the rest of a
+ *  path may only depend on the state reached: paths to the same state are compared by dominance.
+ */
+
+pub trait ResourceNode: Node {
+    // Total consumed from the root
+    fn get_resources(&self) -> Vec<isize>;
+    // Lower bound of what is still to be consumed to reach a solution
+    fn get_resource_heuristics(&self) -> Vec<isize> { vec![0; self.get_resources().len()] }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use super::state_table::StateId;
+use std::mem::size_of;
+
+pub type NodeId = usize;
+
+pub struct ArenaNode<A> {
+    pub state: StateId,
+    pub action: Option<A>,
+    pub parent: Option<NodeId>,
+    pub cost: isize,
+}
+
+pub struct Arena<A> {
+    nodes: Vec<ArenaNode<A>>,
+}
+
+impl<A> Arena<A> {
+    pub fn new() -> Self {
+        Arena { nodes: Vec::new() }
+    }
+
+    pub fn push(&mut self, node: ArenaNode<A>) -> NodeId {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    // Keeps the allocation, ids handed out before are no longer valid
+    pub fn clear(&mut self) { self.nodes.clear() }
+
+    pub fn get(&self, id: NodeId) -> &ArenaNode<A> { &self.nodes[id] }
+    pub fn len(&self) -> usize { self.nodes.len() }
+    pub fn is_empty(&self) -> bool { self.nodes.is_empty() }
+    pub fn get_memory_usage(&self) -> usize { self.nodes.capacity() * size_of::<ArenaNode<A>>() }
+
+    // Root first, last node of the path is `id`
+    pub fn get_path(&self, id: NodeId) -> Vec<NodeId> {
+        let mut path = vec![id];
+        while let Some(parent) = self.nodes[*path.last().unwrap()].parent {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl<A> Default for Arena<A> {
+    fn default() -> Self { Self::new() }
+}
\ No newline at end of file
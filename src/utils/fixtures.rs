@@ -0,0 +1,137 @@
+use crate::traits::game::GameState;
+use crate::traits::multi_objective::MultiObjectiveNode;
+use crate::traits::node::Node;
+use crate::traits::problem::Problem;
+use crate::traits::resources::ResourceNode;
+use std::rc::Rc;
+
+/*
+ *  Small instances shared by the tests of several modules
+ */
+
+const WALLS: [(usize, usize); 4] = [(1, 1), (1, 2), (1, 3), (3, 2)];
+
+// 5x5 grid with walls from (0, 0) to (4, 4), every cell has its own entry cost
+pub struct Grid;
+
+impl Problem for Grid {
+    type State = (usize, usize);
+    type Action = char;
+
+    fn get_root(&self) -> (usize, usize) { (0, 0) }
+
+    fn get_successors(&self, &(x, y): &(usize, usize)) -> Vec<(char, (usize, usize), isize)> {
+        [('R', x + 1, y), ('L', x.wrapping_sub(1), y), ('D', x, y + 1), ('U', x, y.wrapping_sub(1))].into_iter()
+            .filter(|(_, x, y)| *x < 5 && *y < 5 && !WALLS.contains(&(*x, *y)))
+            .map(|(action, x, y)| (action, (x, y), 1 + ((x * 3 + y * 5) % 4) as isize))
+            .collect()
+    }
+
+    fn get_heuristic(&self, &(x, y): &(usize, usize)) -> isize { (8 - x - y) as isize }
+    fn is_solution(&self, state: &(usize, usize)) -> bool { *state == (4, 4) }
+}
+
+// The same grid for the `Node` algorithms
+#[derive(Clone)]
+pub struct GridNode {
+    state: Rc<(usize, usize)>,
+    action: Option<char>,
+    parent: Option<Rc<GridNode>>,
+    cost: isize,
+}
+
+impl GridNode {
+    pub fn root() -> Box<Self> {
+        Box::new(GridNode { state: Rc::new(Grid.get_root()), action: None, parent: None, cost: 0 })
+    }
+}
+
+impl Node for GridNode {
+    type State = (usize, usize);
+    type Action = char;
+
+    fn get_descendants(self) -> Vec<Box<Self>> {
+        let successors = Grid.get_successors(&self.state);
+        let cost = self.cost;
+        let parent = Rc::new(self);
+        successors.into_iter()
+            .map(|(action, state, step)| Box::new(GridNode {
+                state: Rc::new(state),
+                action: Some(action),
+                parent: Some(parent.clone()),
+                cost: cost + step,
+            }))
+            .collect()
+    }
+
+    fn get_state(&self) -> Rc<(usize, usize)> { self.state.clone() }
+    fn get_action(&self) -> Option<char> { self.action }
+    fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+    fn get_cost(&self) -> isize { self.cost }
+    fn get_heuristic(&self) -> isize { Grid.get_heuristic(&self.state) }
+    fn is_solution(&self) -> bool { Grid.is_solution(&self.state) }
+}
+
+// (from, to, cost, second cost or resource) from 0 to 3, the cheapest path has the highest second cost
+const EDGES: [(usize, usize, isize, isize); 5] = [(0, 1, 1, 5), (0, 2, 3, 1), (1, 3, 1, 5), (2, 3, 3, 1), (1, 2, 1, 1)];
+
+// Small directed graph for the algorithms with two costs per edge
+#[derive(Clone)]
+pub struct EdgeNode { state: Rc<usize>, parent: Option<Rc<EdgeNode>>, costs: [isize; 2] }
+
+impl EdgeNode {
+    pub fn root() -> Self { EdgeNode { state: Rc::new(0), parent: None, costs: [0, 0] } }
+}
+
+impl Node for EdgeNode {
+    type State = usize;
+    type Action = usize;
+
+    fn get_descendants(self) -> Vec<Box<Self>> {
+        let (state, costs) = (*self.state, self.costs);
+        let parent = Rc::new(self);
+        EDGES.iter().filter(|edge| edge.0 == state)
+            .map(|(_, to, cost, second)| Box::new(EdgeNode {
+                state: Rc::new(*to),
+                parent: Some(parent.clone()),
+                costs: [costs[0] + cost, costs[1] + second],
+            }))
+            .collect()
+    }
+
+    fn get_state(&self) -> Rc<usize> { self.state.clone() }
+    fn get_action(&self) -> Option<usize> { self.parent.as_ref().map(|_| *self.state) }
+    fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+    fn get_cost(&self) -> isize { self.costs[0] }
+    fn get_heuristic(&self) -> isize { 0 }
+    fn is_solution(&self) -> bool { *self.state == 3 }
+}
+
+impl MultiObjectiveNode for EdgeNode {
+    fn get_costs(&self) -> Vec<isize> { self.costs.to_vec() }
+}
+
+impl ResourceNode for EdgeNode {
+    fn get_resources(&self) -> Vec<isize> { vec![self.costs[1]] }
+}
+
+// Players take 1 to 3 stones in turn, every take scores for player 0 or against it
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Stones { pub stones: usize, pub score: isize, pub player: usize }
+
+impl GameState for Stones {
+    type Move = usize;
+
+    fn get_player(&self) -> usize { self.player }
+    fn get_players(&self) -> usize { 2 }
+    fn get_moves(&self) -> Vec<usize> { (1..=self.stones.min(3)).collect() }
+
+    fn apply(&self, take: &usize) -> Self {
+        let points = ((self.stones * 7 + take * 13) % 11) as isize - 5;
+        let sign = if self.player == 0 { 1 } else { -1 };
+        Stones { stones: self.stones - take, score: self.score + sign * points, player: 1 - self.player }
+    }
+
+    fn is_terminal(&self) -> bool { self.stones == 0 }
+    fn evaluate(&self) -> isize { self.score }
+}
\ No newline at end of file
@@ -0,0 +1,101 @@
+use std::collections::BTreeSet;
+
+/*
+ *  Open list for bounded-suboptimal search: entries have a lower bound, a cost and a secondary key,
+ *  and are popped by secondary key among those whose cost is at most `weight` times the lowest bound
+ *  in the list (the focal list). Bounds must not decrease over the search, as with a consistent
+ *  heuristic, with a weight of 1 this is best-first search with ties broken by the secondary key.
+ */
+
+pub struct FocalList {
+    weight: f64,
+    by_bound: BTreeSet<(isize, usize)>,
+    by_cost: BTreeSet<(isize, usize)>,
+    focal: BTreeSet<(isize, isize, usize)>,
+    // (bound, cost, secondary) of every id in the list
+    entries: Vec<Option<(isize, isize, isize)>>,
+    threshold: isize,
+}
+
+impl FocalList {
+    pub fn new(weight: f64) -> Self {
+        FocalList {
+            weight: weight.max(1.0),
+            by_bound: BTreeSet::new(),
+            by_cost: BTreeSet::new(),
+            focal: BTreeSet::new(),
+            entries: Vec::new(),
+            threshold: isize::MIN,
+        }
+    }
+
+    pub fn push(&mut self, id: usize, bound: isize, cost: isize, secondary: isize) {
+        if id >= self.entries.len() { self.entries.resize(id + 1, None); }
+        self.entries[id] = Some((bound, cost, secondary));
+        self.by_bound.insert((bound, id));
+        self.by_cost.insert((cost, id));
+        if cost <= self.threshold { self.focal.insert((secondary, cost, id)); }
+    }
+
+    pub fn pop(&mut self) -> Option<usize> {
+        let bound = self.get_min_bound()?;
+        let threshold = (self.weight * bound as f64).floor() as isize;
+        if threshold > self.threshold {
+            let start = if self.threshold == isize::MIN { (isize::MIN, 0) } else { (self.threshold + 1, 0) };
+            for (cost, id) in self.by_cost.range(start..=(threshold, usize::MAX)) {
+                let (_, _, secondary) = self.entries[*id].unwrap();
+                self.focal.insert((secondary, *cost, *id));
+            }
+            self.threshold = threshold;
+        }
+
+        let id = match self.focal.pop_first() {
+            Some((_, _, id)) => id,
+            None => self.by_bound.first().unwrap().1,
+        };
+        let (bound, cost, secondary) = self.entries[id].take().unwrap();
+        self.by_bound.remove(&(bound, id));
+        self.by_cost.remove(&(cost, id));
+        self.focal.remove(&(secondary, cost, id));
+        Some(id)
+    }
+
+    pub fn get_min_bound(&self) -> Option<isize> { self.by_bound.first().map(|(bound, _)| *bound) }
+    pub fn len(&self) -> usize { self.by_bound.len() }
+    pub fn is_empty(&self) -> bool { self.by_bound.is_empty() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pops_by_secondary_within_the_weight() {
+        let mut open = FocalList::new(1.5);
+        open.push(0, 10, 10, 5);
+        open.push(1, 12, 14, 1);
+        open.push(2, 12, 16, 0);
+        // Threshold 15: 1 has fewer conflicts than 0, 2 costs too much
+        assert_eq!(open.pop(), Some(1));
+        assert_eq!(open.pop(), Some(0));
+        // Threshold 18 once the lowest bound is 12
+        assert_eq!(open.pop(), Some(2));
+        assert_eq!(open.pop(), None);
+    }
+
+    #[test]
+    fn is_best_first_with_a_weight_of_one() {
+        let mut open = FocalList::new(1.0);
+        open.push(0, 3, 3, 9);
+        open.push(1, 1, 1, 5);
+        open.push(2, 1, 1, 2);
+        assert_eq!(open.pop(), Some(2));
+        // Entries pushed under the current threshold join the focal list right away
+        open.push(3, 1, 1, 0);
+        assert_eq!(open.pop(), Some(3));
+        assert_eq!(open.pop(), Some(1));
+        assert_eq!(open.get_min_bound(), Some(3));
+        assert_eq!(open.pop(), Some(0));
+        assert!(open.is_empty());
+    }
+}
\ No newline at end of file
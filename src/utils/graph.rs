@@ -0,0 +1,100 @@
+use crate::traits::{
+    problem::Problem,
+    p_tuple::PTuple
+};
+use super::state_table::{StateId, StateTable};
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+
+pub type VertexId = StateId;
+
+/*
+ *  Explicit directed graph with non-negative edge costs, for precomputations over every vertex.
+ *  Vertex ids are given in insertion order.
+ */
+
+pub struct Graph<V> {
+    vertices: StateTable<V>,
+    edges: Vec<Vec<(VertexId, isize)>>,
+}
+
+impl<V: Eq + Hash> Graph<V> {
+    pub fn new() -> Self {
+        Graph { vertices: StateTable::new(), edges: Vec::new() }
+    }
+
+    // Every state reachable from the root, solutions included
+    pub fn from_problem<P: Problem<State = V>>(problem: &P) -> Self {
+        let mut graph = Graph::new();
+        let root = graph.add_vertex(problem.get_root());
+        let mut queue = VecDeque::from([root]);
+        while let Some(id) = queue.pop_front() {
+            for (_, successor, step_cost) in problem.get_successors(graph.get_vertex(id)) {
+                let inserted = graph.get_id(&successor).is_none();
+                let successor = graph.add_vertex(successor);
+                if inserted { queue.push_back(successor); }
+                graph.add_edge(id, successor, step_cost);
+            }
+        }
+        graph
+    }
+
+    // Id of the vertex, added if it is new
+    pub fn add_vertex(&mut self, vertex: V) -> VertexId {
+        let (id, inserted) = self.vertices.intern(vertex);
+        if inserted { self.edges.push(Vec::new()); }
+        id
+    }
+
+    pub fn add_edge(&mut self, from: VertexId, to: VertexId, cost: isize) {
+        self.edges[from].push((to, cost));
+    }
+
+    pub fn get_id(&self, vertex: &V) -> Option<VertexId> { self.vertices.get_id_with(vertex, None) }
+    pub fn get_vertex(&self, id: VertexId) -> &V { self.vertices.get(id) }
+    pub fn get_edges(&self, id: VertexId) -> &[(VertexId, isize)] { &self.edges[id] }
+    pub fn len(&self) -> usize { self.vertices.len() }
+    pub fn is_empty(&self) -> bool { self.vertices.is_empty() }
+
+    // Dijkstra from the source to every vertex, or from every vertex to the source if reversed
+    pub fn get_distances(&self, source: VertexId, reversed: bool) -> Vec<Option<isize>> {
+        self.get_distances_from(&[source], reversed)
+    }
+
+    // Same with the nearest of several sources
+    pub fn get_distances_from(&self, sources: &[VertexId], reversed: bool) -> Vec<Option<isize>> {
+        let mut edges = &self.edges;
+        let reverse: Vec<Vec<(VertexId, isize)>>;
+        if reversed {
+            let mut incoming = vec![Vec::new(); self.len()];
+            for (from, outgoing) in self.edges.iter().enumerate() {
+                for (to, cost) in outgoing {
+                    incoming[*to].push((from, *cost));
+                }
+            }
+            reverse = incoming;
+            edges = &reverse;
+        }
+
+        let mut distances = vec![None; self.len()];
+        let mut p_queue: BinaryHeap<PTuple<VertexId>> = BinaryHeap::new();
+        for source in sources {
+            distances[*source] = Some(0);
+            p_queue.push(PTuple { priority: 0, node: *source });
+        }
+        while let Some(PTuple { priority, node }) = p_queue.pop() {
+            if distances[node] != Some(priority) { continue; }
+            for (next, cost) in &edges[node] {
+                let distance = priority + cost;
+                if distances[*next].map(|current| current <= distance).unwrap_or(false) { continue; }
+                distances[*next] = Some(distance);
+                p_queue.push(PTuple { priority: distance, node: *next });
+            }
+        }
+        distances
+    }
+}
+
+impl<V: Eq + Hash> Default for Graph<V> {
+    fn default() -> Self { Self::new() }
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+use std::mem::size_of;
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct MemoryUsage {
+    pub frontier: usize,
+    pub closed: usize,
+    // Expanded nodes are counted as alive, they may still be referenced as parents
+    pub nodes: usize,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize { self.frontier + self.closed + self.nodes }
+}
+
+pub type StateSizeFn<S> = Box<dyn Fn(&S) -> usize + Send>;
+
+/*
+ *  Approximate accounting in bytes: containers are measured by capacity, heap data owned by the
+ *  states themselves is only known through the optional `state_size` function
+ */
+
+pub struct MemoryBudget<S> {
+    limit: Option<usize>,
+    state_size: Option<StateSizeFn<S>>,
+    state_bytes: usize,
+    reached: bool,
+}
+
+impl<S> MemoryBudget<S> {
+    pub fn new() -> Self {
+        MemoryBudget { limit: None, state_size: None, state_bytes: 0, reached: false }
+    }
+
+    pub fn set_limit(&mut self, bytes: usize) -> &mut Self {
+        self.limit = Some(bytes);
+        self
+    }
+
+    pub fn set_state_size<F: Fn(&S) -> usize + Send + 'static>(&mut self, state_size: F) -> &mut Self {
+        self.state_size = Some(Box::new(state_size));
+        self
+    }
+
+    pub fn add_state(&mut self, state: &S) {
+        if let Some(state_size) = &self.state_size {
+            self.state_bytes += state_size(state);
+        }
+    }
+
+    // Counters only, the limit and `state_size` are kept for the next search
+    pub fn reset(&mut self) {
+        self.state_bytes = 0;
+        self.reached = false;
+    }
+
+    pub fn get_state_bytes(&self) -> usize { self.state_bytes }
+
+    // Returns true once the usage went over the limit, the search should stop
+    pub fn check(&mut self, usage: &MemoryUsage) -> bool {
+        if let Some(limit) = self.limit {
+            self.reached = self.reached || usage.total() > limit;
+        }
+        self.reached
+    }
+
+    pub fn is_reached(&self) -> bool { self.reached }
+}
+
+impl<S> Default for MemoryBudget<S> {
+    fn default() -> Self { Self::new() }
+}
+
+// Size of the shared allocation behind an `Rc<T>` (value plus strong and weak counters)
+pub fn rc_size<T>() -> usize {
+    size_of::<T>() + 2 * size_of::<usize>()
+}
\ No newline at end of file
@@ -0,0 +1,21 @@
+use std::hash::Hash;
+use super::arena::{Arena, NodeId};
+use super::state_table::StateTable;
+
+// Owned solution, `states` has one more element than `actions` (the root)
+pub struct Path<S, A> {
+    pub states: Vec<S>,
+    pub actions: Vec<A>,
+    pub cost: isize,
+}
+
+impl<S: Eq + Hash + Clone, A: Clone> Path<S, A> {
+    pub fn from_arena(arena: &Arena<A>, states: &StateTable<S>, id: NodeId) -> Self {
+        let nodes: Vec<_> = arena.get_path(id).into_iter().map(|id| arena.get(id)).collect();
+        Path {
+            states: nodes.iter().map(|node| states.get(node.state).clone()).collect(),
+            actions: nodes.iter().filter_map(|node| node.action.clone()).collect(),
+            cost: arena.get(id).cost,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+// How the cost of the outcomes of an action is aggregated
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CostModel {
+    // Most expensive outcome
+    WorstCase,
+    // Outcomes weighted by their probability
+    Expected,
+}
+
+impl CostModel {
+    // Aggregates (probability, cost) pairs
+    pub fn combine(&self, outcomes: impl Iterator<Item = (f64, f64)>) -> f64 {
+        match self {
+            CostModel::WorstCase => outcomes.map(|(_, cost)| cost).fold(0.0, f64::max),
+            CostModel::Expected => outcomes.filter(|(probability, _)| *probability > 0.0)
+                .map(|(probability, cost)| probability * cost)
+                .sum(),
+        }
+    }
+}
+
+// Sub-policy of every outcome of an action, with its probability
+pub type Branches<S, A> = Vec<(f64, Policy<S, A>)>;
+
+// Sweeps of value iteration over a policy with loops, and the relative change at which they stop
+const MAX_SWEEPS: usize = 100_000;
+const TOLERANCE: f64 = 1e-12;
+
+/*
+ *  Conditional plan, leaves are solutions or loops and have no action. A loop is an outcome back in
+ *  the state of an ancestor (ex: a cancelled flight), the policy continues from the closest one.
+ */
+
+pub struct Policy<S, A> {
+    pub state: S,
+    pub action: Option<A>,
+    // Step cost of the action
+    pub cost: isize,
+    pub outcomes: Branches<S, A>,
+    pub looped: bool,
+}
+
+impl<S: PartialEq, A> Policy<S, A> {
+    // A loop may repeat forever, so a policy with one has no bounded worst case. The expected cost
+    // is the fixed point of value iteration, every loop costs what its ancestor did in the last sweep.
+    pub fn get_cost(&self, model: CostModel) -> f64 {
+        if !self.has_loops() { return self.get_acyclic_cost(model) }
+        if model == CostModel::WorstCase { return f64::INFINITY }
+        let mut values = Vec::new();
+        for _ in 0..MAX_SWEEPS {
+            let mut next = Vec::with_capacity(values.len());
+            self.sweep(model, &values, &mut next, &mut Vec::new());
+            let converged = values.len() == next.len() && values.iter().zip(&next)
+                .all(|(value, next)| (next - value).abs() <= TOLERANCE * next.abs().max(1.0));
+            values = next;
+            if converged { break; }
+        }
+        values[0]
+    }
+
+    pub fn has_loops(&self) -> bool {
+        self.looped || self.outcomes.iter().any(|(_, policy)| policy.has_loops())
+    }
+
+    fn get_acyclic_cost(&self, model: CostModel) -> f64 {
+        if self.action.is_none() { return 0.0 }
+        self.cost as f64 + model.combine(self.outcomes.iter().map(|(probability, policy)| (*probability, policy.get_acyclic_cost(model))))
+    }
+
+    // Values in preorder, `path` holds the states above with their index
+    fn sweep<'a>(&'a self, model: CostModel, previous: &[f64], next: &mut Vec<f64>, path: &mut Vec<(&'a S, usize)>) -> f64 {
+        let index = next.len();
+        next.push(0.0);
+        let value = if self.looped {
+            path.iter().rev()
+                .find(|(state, _)| *state == &self.state)
+                .and_then(|(_, ancestor)| previous.get(*ancestor).copied())
+                .unwrap_or(0.0)
+        } else if self.action.is_none() {
+            0.0
+        } else {
+            path.push((&self.state, index));
+            let outcomes: Vec<(f64, f64)> = self.outcomes.iter()
+                .map(|(probability, policy)| (*probability, policy.sweep(model, previous, next, path)))
+                .collect();
+            path.pop();
+            self.cost as f64 + model.combine(outcomes.into_iter())
+        };
+        next[index] = value;
+        value
+    }
+
+    // Number of nodes in the policy, loops included
+    pub fn get_size(&self) -> usize {
+        1 + self.outcomes.iter().map(|(_, policy)| policy.get_size()).sum::<usize>()
+    }
+
+    // Most actions taken before reaching a solution or a loop
+    pub fn get_depth(&self) -> usize {
+        self.outcomes.iter().map(|(_, policy)| policy.get_depth() + 1).max().unwrap_or(0)
+    }
+
+    // Action to take in `state`, searched through every branch of the policy
+    pub fn get_action(&self, state: &S) -> Option<&A> {
+        if &self.state == state && !self.looped { return self.action.as_ref() }
+        self.outcomes.iter().find_map(|(_, policy)| policy.get_action(state))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,118 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/*
+ *  States taken by other agents over time: at a single timestep, from a timestep on (an agent staying
+ *  at its goal), or while moving between two states, so that a move in the opposite direction at the
+ *  same time would swap places with it
+ */
+
+pub struct ReservationTable<S> {
+    states: HashMap<S, HashSet<usize>>,
+    // (destination, arrival time) of the moves from every state
+    moves: HashMap<S, Vec<(S, usize)>>,
+    permanent: HashMap<S, usize>,
+    last_time: usize,
+}
+
+impl<S: Eq + Hash + Clone> ReservationTable<S> {
+    pub fn new() -> Self {
+        ReservationTable {
+            states: HashMap::new(),
+            moves: HashMap::new(),
+            permanent: HashMap::new(),
+            last_time: 0,
+        }
+    }
+
+    pub fn reserve(&mut self, state: S, time: usize) {
+        self.states.entry(state).or_default().insert(time);
+        self.last_time = self.last_time.max(time);
+    }
+
+    // Move from `from` to `to` arriving at `time`
+    pub fn reserve_move(&mut self, from: S, to: S, time: usize) {
+        self.moves.entry(from).or_default().push((to, time));
+        self.last_time = self.last_time.max(time);
+    }
+
+    // The state stays taken from `time` on
+    pub fn reserve_from(&mut self, state: S, time: usize) {
+        let since = self.permanent.entry(state).or_insert(time);
+        *since = (*since).min(time);
+        self.last_time = self.last_time.max(time);
+    }
+
+    // Every state of the path at its timestep, the first one at `start`, and the moves between them
+    pub fn reserve_path(&mut self, path: &[S], start: usize) {
+        for (i, state) in path.iter().enumerate() {
+            self.reserve(state.clone(), start + i);
+            if i > 0 && path[i - 1] != *state { self.reserve_move(path[i - 1].clone(), state.clone(), start + i); }
+        }
+    }
+
+    pub fn is_reserved(&self, state: &S, time: usize) -> bool {
+        self.permanent.get(state).map(|since| time >= *since).unwrap_or(false)
+            || self.states.get(state).map(|times| times.contains(&time)).unwrap_or(false)
+    }
+
+    // Arriving at a reserved state or swapping places with a reserved move
+    pub fn is_move_reserved(&self, from: &S, to: &S, time: usize) -> bool {
+        self.is_reserved(to, time)
+            || self.moves.get(to).map(|moves| moves.iter().any(|(destination, arrival)| destination == from && *arrival == time)).unwrap_or(false)
+    }
+
+    pub fn is_reserved_after(&self, state: &S, time: usize) -> bool {
+        self.permanent.contains_key(state)
+            || self.states.get(state).map(|times| times.iter().any(|reserved| *reserved > time)).unwrap_or(false)
+    }
+
+    // Latest timestep with a reservation, nothing changes after it
+    pub fn get_last_time(&self) -> usize { self.last_time }
+
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.moves.clear();
+        self.permanent.clear();
+        self.last_time = 0;
+    }
+}
+
+impl<S: Eq + Hash + Clone> Default for ReservationTable<S> {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_reserve_their_states_and_moves() {
+        let mut table = ReservationTable::new();
+        table.reserve_path(&[2, 1, 1], 3);
+        assert!(table.is_reserved(&2, 3) && table.is_reserved(&1, 4) && table.is_reserved(&1, 5));
+        assert!(!table.is_reserved(&2, 4) && !table.is_reserved(&1, 3));
+        assert_eq!(table.get_last_time(), 5);
+
+        // Head-on into the move from 2 to 1, following it is allowed
+        assert!(table.is_move_reserved(&1, &2, 4));
+        assert!(!table.is_move_reserved(&3, &2, 4));
+        assert!(!table.is_move_reserved(&1, &2, 5));
+    }
+
+    #[test]
+    fn permanent_reservations_last() {
+        let mut table = ReservationTable::new();
+        table.reserve(4, 2);
+        table.reserve_from(7, 5);
+        table.reserve_from(7, 3);
+        assert!(!table.is_reserved(&7, 2));
+        assert!(table.is_reserved(&7, 3) && table.is_reserved(&7, 100));
+        assert!(table.is_reserved_after(&4, 1) && !table.is_reserved_after(&4, 2));
+        assert!(table.is_reserved_after(&7, 100));
+
+        table.clear();
+        assert!(!table.is_reserved(&7, 100) && !table.is_reserved(&4, 2));
+        assert_eq!(table.get_last_time(), 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,506 @@
+use crate::traits::node::Node;
+use crate::traits::problem::Problem;
+use super::state_table::StateTable;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+pub type StateFormatFn<S> = Box<dyn Fn(&S) -> String + Send>;
+pub type ActionFormatFn<A> = Box<dyn Fn(&A) -> String + Send>;
+
+struct TreeNode {
+    parent: Option<usize>,
+    hash: u64,
+    state: String,
+    action: Option<String>,
+    cost: isize,
+    heuristic: isize,
+    depth: usize,
+    // Expansion order, from 1
+    expansion: Option<usize>,
+    // Its state was generated before by another node
+    duplicate: bool,
+    // Discarded without being expanded, stale or past a bound
+    pruned: bool,
+    solution: bool,
+}
+
+/*
+ *  Generated and expanded nodes of a search, recorded through `RecordedNode` (any `Node` algorithm)
+ *  or `RecordedProblem` (any `Problem` algorithm) and written as a Graphviz DOT tree. Labels are
+ *  formatted when a node is generated, nodes past the depth or node limits are not recorded.
+ *  Nodes have the same state when both the state hash and the label are equal, the label must tell
+ *  apart the states of a search for duplicates and expansions to be matched through hash collisions.
+ */
+
+pub struct TreeRecorder<S, A> {
+    state_format: StateFormatFn<S>,
+    action_format: Option<ActionFormatFn<A>>,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+    nodes: Vec<TreeNode>,
+    // Nodes generated for every state hash, their labels tell colliding states apart
+    by_state: HashMap<u64, Vec<usize>>,
+    expansions: usize,
+    skipped: usize,
+}
+
+impl<S, A> TreeRecorder<S, A> {
+    pub fn new<F: Fn(&S) -> String + Send + 'static>(state_format: F) -> Self {
+        TreeRecorder {
+            state_format: Box::new(state_format),
+            action_format: None,
+            max_depth: None,
+            max_nodes: None,
+            nodes: Vec::new(),
+            by_state: HashMap::new(),
+            expansions: 0,
+            skipped: 0,
+        }
+    }
+
+    // Edges are unlabeled without it
+    pub fn set_action_format<F: Fn(&A) -> String + Send + 'static>(&mut self, action_format: F) -> &mut Self {
+        self.action_format = Some(Box::new(action_format));
+        self
+    }
+
+    // The root is at depth 0
+    pub fn set_max_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    pub fn set_max_nodes(&mut self, nodes: usize) -> &mut Self {
+        self.max_nodes = Some(nodes);
+        self
+    }
+
+    pub fn len(&self) -> usize { self.nodes.len() }
+    pub fn is_empty(&self) -> bool { self.nodes.is_empty() }
+    pub fn get_expansions(&self) -> usize { self.expansions }
+    // Descendants of recorded nodes left out by the limits
+    pub fn get_skipped(&self) -> usize { self.skipped }
+
+    // None if the node is past the limits or its parent was not recorded
+    fn add(&mut self, parent: Option<usize>, state: &S, hash: u64, action: Option<&A>, cost: isize, heuristic: isize) -> Option<usize> {
+        let depth = parent.map(|parent| self.nodes[parent].depth + 1).unwrap_or(0);
+        if self.max_depth.map(|max| depth > max).unwrap_or(false)
+            || self.max_nodes.map(|max| self.nodes.len() >= max).unwrap_or(false) {
+            self.skipped += 1;
+            return None
+        }
+        let id = self.nodes.len();
+        let label = (self.state_format)(state);
+        let generated = self.by_state.entry(hash).or_default();
+        let duplicate = generated.iter().any(|other| self.nodes[*other].state == label);
+        // Already stale if its state was expanded on a path as cheap
+        let pruned = generated.iter().any(|other| {
+            let other = &self.nodes[*other];
+            other.state == label && other.expansion.is_some() && other.cost <= cost
+        });
+        generated.push(id);
+        self.nodes.push(TreeNode {
+            parent,
+            hash,
+            state: label,
+            action: action.and_then(|action| self.action_format.as_ref().map(|format| format(action))),
+            cost,
+            heuristic,
+            depth,
+            expansion: None,
+            duplicate,
+            pruned,
+            solution: false,
+        });
+        Some(id)
+    }
+
+    // Nodes with the same state and no lower cost are stale once it is expanded, algorithms with a
+    // closed list drop them. The mark goes away if one of them is expanded anyway.
+    fn expand(&mut self, id: usize) {
+        self.expansions += 1;
+        let node = &mut self.nodes[id];
+        if node.expansion.is_none() { node.expansion = Some(self.expansions); }
+        node.pruned = false;
+        let (cost, hash) = (node.cost, node.hash);
+        for other in self.by_state.get(&hash).cloned().unwrap_or_default() {
+            if other != id && self.nodes[other].state == self.nodes[id].state && self.nodes[other].cost >= cost {
+                self.prune(other);
+            }
+        }
+    }
+
+    fn prune(&mut self, id: usize) {
+        let node = &mut self.nodes[id];
+        if node.expansion.is_none() { node.pruned = true; }
+    }
+
+    // Marks the nodes not expanded with g + h at least `bound` as pruned, ex: the cost of the solution
+    // found by a branch and bound search
+    pub fn mark_pruned_by_bound(&mut self, bound: isize) {
+        for id in 0..self.nodes.len() {
+            if self.nodes[id].cost + self.nodes[id].heuristic >= bound { self.prune(id); }
+        }
+    }
+
+    // Cheapest node with the state not expanded yet, the earliest one on ties
+    fn find_unexpanded(&self, state: &S, hash: u64) -> Option<usize> {
+        let label = (self.state_format)(state);
+        self.by_state.get(&hash)?.iter().cloned()
+            .filter(|id| self.nodes[*id].expansion.is_none() && self.nodes[*id].state == label)
+            .min_by_key(|id| self.nodes[*id].cost)
+    }
+
+    // Highlights the path from the root to the node
+    fn mark_path(&mut self, mut id: usize) {
+        loop {
+            self.nodes[id].solution = true;
+            match self.nodes[id].parent {
+                Some(parent) => id = parent,
+                None => break,
+            }
+        }
+    }
+
+    /*
+     *  Expanded nodes are labeled with their expansion order, pruned nodes are orange and the other
+     *  nodes never expanded gray, duplicates of an already generated state are dashed and the solution
+     *  path is red
+     */
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph search {\n    node [shape=box, fontname=\"monospace\"];\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let order = node.expansion.map(|order| format!("#{}\\l", order)).unwrap_or_default();
+            let mut style = Vec::new();
+            if node.duplicate { style.push("style=dashed"); }
+            if node.solution {
+                style.push("color=red, penwidth=2");
+            } else if node.pruned {
+                style.push("color=orange, fontcolor=orange");
+            } else if node.expansion.is_none() {
+                style.push("color=gray, fontcolor=gray");
+            }
+            let style = if style.is_empty() { String::new() } else { format!(", {}", style.join(", ")) };
+            dot += &format!(
+                "    n{} [label=\"{}{}g={} h={}\\l\"{}];\n",
+                id, order, escape(&node.state), node.cost, node.heuristic, style,
+            );
+            if let Some(parent) = node.parent {
+                let label = node.action.as_ref().map(|action| format!("label=\"{}\"", escape(action).trim_end_matches("\\l"))).unwrap_or_default();
+                let style = if node.solution { "color=red, penwidth=2" } else { "" };
+                let attributes = [label.as_str(), style].iter().filter(|s| !s.is_empty()).cloned().collect::<Vec<&str>>().join(", ");
+                dot += &format!("    n{} -> n{} [{}];\n", parent, id, attributes);
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+
+    pub fn write_dot(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_dot())
+    }
+}
+
+// Left-justified lines
+fn escape(label: &str) -> String {
+    let mut escaped = String::new();
+    for line in label.lines() {
+        escaped += &line.replace('\\', "\\\\").replace('"', "\\\"");
+        escaped += "\\l";
+    }
+    escaped
+}
+
+/*
+ *  Node wrapper recording into a shared recorder: descendants when they are generated, and the
+ *  expansion order when the algorithm asks for them. Call `mark_solution` on the returned solution.
+ *
+ *  `get_descendants` consumes the node, so the wrapped one must be `Clone` to be expanded while the
+ *  wrapper stays the parent of its descendants. Every wrapped node keeps its own parent chain next to
+ *  the wrapper's, recording roughly doubles the memory held by the nodes.
+ */
+
+pub struct RecordedNode<T: Node + Clone> {
+    node: T,
+    id: Option<usize>,
+    parent: Option<Rc<Self>>,
+    recorder: Rc<RefCell<TreeRecorder<T::State, T::Action>>>,
+}
+
+impl<T: Node + Clone> RecordedNode<T> {
+    pub fn new(root: T, recorder: Rc<RefCell<TreeRecorder<T::State, T::Action>>>) -> Self {
+        let id = recorder.borrow_mut().add(None, &root.get_state(), Self::hash(&root), None, root.get_cost(), root.get_heuristic());
+        RecordedNode { node: root, id, parent: None, recorder }
+    }
+
+    fn hash(node: &T) -> u64 {
+        node.get_state_hash().unwrap_or_else(|| StateTable::hash_state(&node.get_state()))
+    }
+
+    pub fn get_node(&self) -> &T { &self.node }
+    pub fn into_node(self) -> T { self.node }
+
+    pub fn mark_solution(&self) {
+        if let Some(id) = self.id { self.recorder.borrow_mut().mark_path(id); }
+    }
+
+    // For algorithms discarding a generated node without expanding it
+    pub fn mark_pruned(&self) {
+        if let Some(id) = self.id { self.recorder.borrow_mut().prune(id); }
+    }
+}
+
+impl<T: Node + Clone> Node for RecordedNode<T> {
+    type State = T::State;
+    type Action = T::Action;
+
+    fn get_descendants(self) -> Vec<Box<Self>> {
+        if let Some(id) = self.id { self.recorder.borrow_mut().expand(id); }
+        let descendants = self.node.clone().get_descendants();
+        let parent = Rc::new(self);
+        descendants.into_iter().map(|descendant| {
+            let id = parent.id.and_then(|id| parent.recorder.borrow_mut().add(
+                Some(id),
+                &descendant.get_state(),
+                Self::hash(&descendant),
+                descendant.get_action().as_ref(),
+                descendant.get_cost(),
+                descendant.get_heuristic(),
+            ));
+            Box::new(RecordedNode { node: *descendant, id, parent: Some(parent.clone()), recorder: parent.recorder.clone() })
+        }).collect()
+    }
+
+    fn get_state(&self) -> Rc<T::State> { self.node.get_state() }
+    fn get_action(&self) -> Option<T::Action> { self.node.get_action() }
+    fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+    fn get_cost(&self) -> isize { self.node.get_cost() }
+    fn get_heuristic(&self) -> isize { self.node.get_heuristic() }
+    fn is_solution(&self) -> bool { self.node.is_solution() }
+    fn get_state_hash(&self) -> Option<u64> { self.node.get_state_hash() }
+}
+
+/*
+ *  Problem wrapper recording every call to `get_successors` as the expansion of the cheapest node
+ *  generated with that state and not expanded yet, the one kept by algorithms that only push a state
+ *  again on a cheaper path (A*, Dijkstra, DFBnB). Costs are summed along the recorded tree.
+ */
+
+pub struct RecordedProblem<'a, P: Problem> {
+    problem: &'a P,
+    recorder: Mutex<TreeRecorder<P::State, P::Action>>,
+}
+
+impl<'a, P: Problem> RecordedProblem<'a, P> {
+    pub fn new(problem: &'a P, mut recorder: TreeRecorder<P::State, P::Action>) -> Self {
+        let root = problem.get_root();
+        recorder.add(None, &root, Self::hash(problem, &root), None, 0, problem.get_heuristic(&root));
+        RecordedProblem { problem, recorder: Mutex::new(recorder) }
+    }
+
+    fn hash(problem: &P, state: &P::State) -> u64 {
+        problem.get_state_hash(state).unwrap_or_else(|| StateTable::hash_state(state))
+    }
+
+    // Highlights the states of a solution path (ex: `Path::from_arena(..).states`) from the root
+    pub fn mark_solution(&self, states: &[P::State]) {
+        let mut recorder = self.recorder.lock().unwrap();
+        if recorder.is_empty() { return }
+        // Cheapest child with the next state, down from the root
+        let mut current = 0;
+        for state in states.iter().skip(1) {
+            let hash = Self::hash(self.problem, state);
+            let label = (recorder.state_format)(state);
+            let child = (0..recorder.nodes.len())
+                .filter(|id| {
+                    let node = &recorder.nodes[*id];
+                    node.parent == Some(current) && node.hash == hash && node.state == label
+                })
+                .min_by_key(|id| recorder.nodes[*id].cost);
+            match child {
+                Some(child) => current = child,
+                None => break,
+            }
+        }
+        recorder.mark_path(current);
+    }
+
+    // Every node with the state not expanded yet, for algorithms discarding a generated state
+    pub fn mark_pruned(&self, state: &P::State) {
+        let mut recorder = self.recorder.lock().unwrap();
+        let label = (recorder.state_format)(state);
+        let generated = recorder.by_state.get(&Self::hash(self.problem, state)).cloned().unwrap_or_default();
+        for id in generated {
+            if recorder.nodes[id].state == label { recorder.prune(id); }
+        }
+    }
+
+    pub fn into_recorder(self) -> TreeRecorder<P::State, P::Action> { self.recorder.into_inner().unwrap() }
+}
+
+impl<'a, P: Problem> Problem for RecordedProblem<'a, P> {
+    type State = P::State;
+    type Action = P::Action;
+
+    fn get_root(&self) -> P::State { self.problem.get_root() }
+
+    fn get_successors(&self, state: &P::State) -> Vec<(P::Action, P::State, isize)> {
+        let successors = self.problem.get_successors(state);
+        let mut recorder = self.recorder.lock().unwrap();
+        let Some(id) = recorder.find_unexpanded(state, Self::hash(self.problem, state)) else { return successors };
+        recorder.expand(id);
+        let cost = recorder.nodes[id].cost;
+        for (action, successor, step_cost) in &successors {
+            let heuristic = self.problem.get_heuristic(successor);
+            recorder.add(Some(id), successor, Self::hash(self.problem, successor), Some(action), cost + step_cost, heuristic);
+        }
+        successors
+    }
+
+    fn get_heuristic(&self, state: &P::State) -> isize { self.problem.get_heuristic(state) }
+    fn is_solution(&self, state: &P::State) -> bool { self.problem.is_solution(state) }
+    fn get_state_hash(&self, state: &P::State) -> Option<u64> { self.problem.get_state_hash(state) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a_star::AStar;
+    use crate::arena::{a_star::AStar as ArenaAStar, bfs::BFS};
+    use crate::traits::algorithm::Algorithm;
+    use crate::traits::problem::ProblemAlgorithm;
+    use crate::utils::fixtures::GridNode;
+    use crate::utils::path::Path as SolutionPath;
+
+    // Complete binary tree of depth 3, state n has the children 2n + 1 and 2n + 2
+    struct Tree;
+
+    impl Problem for Tree {
+        type State = usize;
+        type Action = usize;
+
+        fn get_root(&self) -> usize { 0 }
+
+        fn get_successors(&self, state: &usize) -> Vec<(usize, usize, isize)> {
+            if *state >= 7 { return Vec::new() }
+            vec![(0, state * 2 + 1, 1), (1, state * 2 + 2, 1)]
+        }
+
+        fn get_heuristic(&self, _state: &usize) -> isize { 0 }
+        fn is_solution(&self, _state: &usize) -> bool { false }
+    }
+
+    // 0 -> 1 -> 3 -> 4 is the cheapest path, 0 -> 2 -> 3 reaches 3 again at a higher cost
+    struct Diamond;
+
+    impl Problem for Diamond {
+        type State = usize;
+        type Action = char;
+
+        fn get_root(&self) -> usize { 0 }
+
+        fn get_successors(&self, state: &usize) -> Vec<(char, usize, isize)> {
+            match state {
+                0 => vec![('a', 1, 1), ('b', 2, 1)],
+                1 => vec![('c', 3, 1)],
+                2 => vec![('d', 3, 2)],
+                3 => vec![('e', 4, 1)],
+                _ => Vec::new(),
+            }
+        }
+
+        fn get_heuristic(&self, _state: &usize) -> isize { 0 }
+        fn is_solution(&self, state: &usize) -> bool { *state == 4 }
+    }
+
+    fn count_nodes(dot: &str, attribute: &str) -> usize {
+        dot.lines().filter(|line| !line.contains("->") && line.contains(" [label=") && line.contains(attribute)).count()
+    }
+
+    fn count_edges(dot: &str, attribute: &str) -> usize {
+        dot.lines().filter(|line| line.contains("->") && line.contains(attribute)).count()
+    }
+
+    fn record_tree(recorder: TreeRecorder<usize, usize>) -> TreeRecorder<usize, usize> {
+        let problem = RecordedProblem::new(&Tree, recorder);
+        assert!(BFS::new().find_solution(&problem).is_none());
+        problem.into_recorder()
+    }
+
+    #[test]
+    fn records_every_generated_node() {
+        let recorder = record_tree(TreeRecorder::new(|state: &usize| state.to_string()));
+        let dot = recorder.to_dot();
+        assert_eq!((recorder.len(), recorder.get_expansions(), recorder.get_skipped()), (15, 15, 0));
+        assert_eq!(count_nodes(&dot, ""), 15);
+        assert_eq!(count_edges(&dot, ""), 14);
+        assert!(dot.starts_with("digraph search {") && dot.ends_with("}\n"));
+        assert!(dot.contains("n0 [label=\"#1\\l0\\lg=0 h=0\\l\"]"));
+    }
+
+    #[test]
+    fn stops_at_the_limits() {
+        let mut recorder = TreeRecorder::new(|state: &usize| state.to_string());
+        recorder.set_max_depth(2).set_action_format(|action: &usize| action.to_string());
+        let recorder = record_tree(recorder);
+        let dot = recorder.to_dot();
+        assert_eq!((recorder.len(), recorder.get_skipped()), (7, 8));
+        assert_eq!(count_edges(&dot, "label=\"1\""), 3);
+        // The leaves at depth 2 are expanded, their children are not recorded
+        assert_eq!(count_nodes(&dot, "color=gray"), 0);
+
+        let mut recorder = TreeRecorder::new(|state: &usize| state.to_string());
+        recorder.set_max_nodes(5);
+        let recorder = record_tree(recorder);
+        assert_eq!(recorder.len(), 5);
+        assert_eq!(count_nodes(&recorder.to_dot(), ""), 5);
+        assert!(recorder.get_skipped() > 0);
+    }
+
+    #[test]
+    fn marks_duplicates_pruned_nodes_and_the_solution() {
+        let mut recorder = TreeRecorder::new(|state: &usize| state.to_string());
+        recorder.set_action_format(|action: &char| action.to_string());
+        let problem = RecordedProblem::new(&Diamond, recorder);
+        let mut algorithm = ArenaAStar::new();
+        let solution = algorithm.find_solution(&problem).unwrap();
+        let path = SolutionPath::from_arena(algorithm.get_arena(), algorithm.get_states(), solution);
+        problem.mark_solution(&path.states);
+        let dot = problem.into_recorder().to_dot();
+
+        assert_eq!(count_nodes(&dot, ""), 6);
+        assert_eq!(count_nodes(&dot, "style=dashed"), 1);
+        // The second node with state 3 is stale once the cheaper one is expanded
+        assert_eq!(count_nodes(&dot, "color=orange"), 1);
+        assert!(dot.lines().any(|line| line.contains("[label=\"3\\lg=3") && line.contains("color=orange")));
+        assert_eq!(count_nodes(&dot, "color=red"), 4);
+        assert_eq!(count_edges(&dot, "color=red"), 3);
+        assert_eq!(count_edges(&dot, "label=\"d\""), 1);
+    }
+
+    #[test]
+    fn records_node_searches() {
+        let recorder = Rc::new(RefCell::new(TreeRecorder::new(|state: &(usize, usize)| format!("{:?}", state))));
+        let root = RecordedNode::new(*GridNode::root(), recorder.clone());
+        let solution = AStar::new().find_solution(Box::new(root)).unwrap();
+        solution.mark_solution();
+        let steps = std::iter::successors(solution.get_parent(), |node| node.get_parent()).count();
+        let dot = recorder.borrow().to_dot();
+        assert_eq!(count_nodes(&dot, ""), recorder.borrow().len());
+        assert_eq!(count_edges(&dot, ""), recorder.borrow().len() - 1);
+        assert_eq!(count_nodes(&dot, "color=red"), steps + 1);
+        assert_eq!(count_edges(&dot, "color=red"), steps);
+
+        // Every node left unexpanded is past the bound, except the solution which stays highlighted
+        recorder.borrow_mut().mark_pruned_by_bound(0);
+        let dot = recorder.borrow().to_dot();
+        let (nodes, expansions) = (recorder.borrow().len(), recorder.borrow().get_expansions());
+        assert_eq!(count_nodes(&dot, "color=orange"), nodes - expansions - 1);
+        assert_eq!(count_nodes(&dot, "color=gray"), 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::mem::size_of;
+
+pub type StateId = usize;
+
+/*
+ *  Every state is stored once and looked up by its 64 bit hash,
+ *  full equality is only checked between states sharing the same hash
+ */
+
+pub struct StateTable<S> {
+    states: Vec<S>,
+    first: HashMap<u64, StateId, BuildHasherDefault<PassHasher>>,
+    next: Vec<Option<StateId>>,
+}
+
+impl<S: Eq + Hash> StateTable<S> {
+    pub fn new() -> Self {
+        StateTable {
+            states: Vec::new(),
+            first: HashMap::default(),
+            next: Vec::new(),
+        }
+    }
+
+    pub fn hash_state(state: &S) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn intern(&mut self, state: S) -> (StateId, bool) {
+        self.intern_with(state, None)
+    }
+
+    // Uses the precomputed hash if there is one, `Hash` on the full state otherwise
+    pub fn intern_with(&mut self, state: S, hash: Option<u64>) -> (StateId, bool) {
+        let hash = hash.unwrap_or_else(|| Self::hash_state(&state));
+        self.intern_hashed(state, hash)
+    }
+
+    pub fn intern_hashed(&mut self, state: S, hash: u64) -> (StateId, bool) {
+        if let Some(id) = self.get_id_hashed(&state, hash) { return (id, false) }
+        let id = self.states.len();
+        self.next.push(self.first.insert(hash, id));
+        self.states.push(state);
+        (id, true)
+    }
+
+    // Must be given the same hash as `intern_with`, states interned with a precomputed hash are not
+    // found under `hash_state`
+    pub fn get_id_with(&self, state: &S, hash: Option<u64>) -> Option<StateId> {
+        self.get_id_hashed(state, hash.unwrap_or_else(|| Self::hash_state(state)))
+    }
+
+    pub fn get_id_hashed(&self, state: &S, hash: u64) -> Option<StateId> {
+        let mut current = self.first.get(&hash).copied();
+        while let Some(id) = current {
+            if &self.states[id] == state { return Some(id) }
+            current = self.next[id];
+        }
+        None
+    }
+
+    // Keeps the allocations, ids handed out before are no longer valid
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.first.clear();
+        self.next.clear();
+    }
+
+    pub fn get(&self, id: StateId) -> &S { &self.states[id] }
+    pub fn len(&self) -> usize { self.states.len() }
+    pub fn is_empty(&self) -> bool { self.states.is_empty() }
+    pub fn iter(&self) -> impl Iterator<Item = &S> { self.states.iter() }
+
+    // Bytes held by the table itself, heap data owned by the states is not included
+    pub fn get_memory_usage(&self) -> usize {
+        self.states.capacity() * size_of::<S>()
+            + self.first.capacity() * (size_of::<u64>() + size_of::<StateId>() + 1)
+            + self.next.capacity() * size_of::<Option<StateId>>()
+    }
+}
+
+impl<S: Eq + Hash> Default for StateTable<S> {
+    fn default() -> Self { Self::new() }
+}
+
+// Keys are already hashes, no need to hash them again
+#[derive(Default)]
+struct PassHasher(u64);
+
+impl Hasher for PassHasher {
+    fn finish(&self) -> u64 { self.0 }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ *byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) { self.0 = n }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::zobrist::Zobrist;
+
+    #[test]
+    fn interns_states_once() {
+        let mut table = StateTable::new();
+        assert_eq!(table.intern("a"), (0, true));
+        assert_eq!(table.intern("b"), (1, true));
+        assert_eq!(table.intern("a"), (0, false));
+        assert_eq!(table.get_id_with(&"b", None), Some(1));
+        assert_eq!(table.get_id_with(&"c", None), None);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn tells_apart_states_sharing_a_hash() {
+        let mut table = StateTable::new();
+        for state in 0..10 {
+            assert_eq!(table.intern_hashed(state, 7), (state, true));
+        }
+        for state in 0..10 {
+            assert_eq!(table.intern_hashed(state, 7), (state, false));
+            assert_eq!(table.get_id_hashed(&state, 7), Some(state));
+        }
+        assert_eq!(table.get_id_hashed(&10, 7), None);
+        assert_eq!(table.get_id_hashed(&3, 8), None);
+    }
+
+    #[test]
+    fn finds_states_interned_with_a_precomputed_hash() {
+        let zobrist = Zobrist::new(3, 2, 1);
+        let states = [[0, 1, 1], [1, 0, 1], [1, 1, 0]];
+        let hash = |state: &[usize; 3]| Some(zobrist.hash(state.iter().copied().enumerate()));
+        let mut table = StateTable::new();
+        for state in states {
+            table.intern_with(state, hash(&state));
+        }
+        for (id, state) in states.iter().enumerate() {
+            assert_eq!(table.get_id_with(state, hash(state)), Some(id));
+            assert_eq!(table.intern_with(*state, hash(state)), (id, false));
+        }
+        assert_eq!(table.get_id_with(&[0, 0, 0], hash(&[0, 0, 0])), None);
+    }
+
+    #[test]
+    fn clear_forgets_every_state() {
+        let mut table = StateTable::new();
+        table.intern(1);
+        table.intern_hashed(2, 0);
+        table.clear();
+        assert!(table.is_empty());
+        assert_eq!(table.get_id_with(&1, None), None);
+        assert_eq!(table.intern_hashed(2, 0), (0, true));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,173 @@
+use crate::traits::node::Node;
+use crate::traits::problem::Problem;
+use super::state_table::{StateId, StateTable};
+use std::rc::Rc;
+
+pub enum Violation<S, A> {
+    // h(state) > cost + h(successor), A* may return a suboptimal solution
+    Inconsistent { state: S, action: A, successor: S, cost: isize, heuristic: isize, successor_heuristic: isize },
+    // Negative step cost, the path cost is not monotonic as DFBnB requires
+    DecreasingCost { state: S, action: A, successor: S, cost: isize },
+    NegativeHeuristic { state: S, heuristic: isize },
+    NonZeroSolution { state: S, heuristic: isize },
+}
+
+pub type NodeViolation<T> = Violation<Rc<<T as Node>::State>, Option<<T as Node>::Action>>;
+pub type NodeValidator<T> = Validator<Rc<<T as Node>::State>, Option<<T as Node>::Action>>;
+
+/*
+ *  Debug checks of the heuristic and costs on every generated edge, disabled by default. States are
+ *  whatever the algorithm can hand out cheaply (`Rc` states or state ids), every violation is kept.
+ */
+
+pub struct Validator<S, A> {
+    enabled: bool,
+    violations: Vec<Violation<S, A>>,
+}
+
+impl<S: Clone, A> Validator<S, A> {
+    pub fn new() -> Self {
+        Validator { enabled: false, violations: Vec::new() }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool { self.enabled }
+    pub fn get_violations(&self) -> &[Violation<S, A>] { &self.violations }
+    pub fn clear(&mut self) { self.violations.clear() }
+
+    pub fn check_state(&mut self, state: &S, heuristic: isize, is_solution: bool) {
+        if heuristic < 0 {
+            self.violations.push(Violation::NegativeHeuristic { state: state.clone(), heuristic });
+        }
+        if is_solution && heuristic != 0 {
+            self.violations.push(Violation::NonZeroSolution { state: state.clone(), heuristic });
+        }
+    }
+
+    // (state, heuristic) pairs, the action is only built for a violation and the successor is checked too
+    pub fn check_edge<F: Fn() -> A>(&mut self, from: (&S, isize), action: F, cost: isize, to: (&S, isize), is_solution: bool) {
+        let ((state, heuristic), (successor, successor_heuristic)) = (from, to);
+        self.check_state(successor, successor_heuristic, is_solution);
+        if cost < 0 {
+            self.violations.push(Violation::DecreasingCost {
+                state: state.clone(), action: action(), successor: successor.clone(), cost,
+            });
+        }
+        if heuristic > cost + successor_heuristic {
+            self.violations.push(Violation::Inconsistent {
+                state: state.clone(), action: action(), successor: successor.clone(), cost, heuristic, successor_heuristic,
+            });
+        }
+    }
+}
+
+impl<S: Clone, A> Default for Validator<S, A> {
+    fn default() -> Self { Self::new() }
+}
+
+// State, heuristic and cost of a node about to be consumed by its expansion, only when enabled
+pub(crate) fn get_parent<T: Node>(validator: &NodeValidator<T>, node: &T) -> Option<(Rc<T::State>, isize, isize)> {
+    if !validator.is_enabled() { return None }
+    Some((node.get_state(), node.get_heuristic(), node.get_cost()))
+}
+
+pub(crate) fn check_descendant<T: Node>(validator: &mut NodeValidator<T>, parent: &Option<(Rc<T::State>, isize, isize)>, descendant: &T) {
+    let Some((state, heuristic, cost)) = parent else { return };
+    validator.check_edge(
+        (state, *heuristic),
+        || descendant.get_action(),
+        descendant.get_cost() - cost,
+        (&descendant.get_state(), descendant.get_heuristic()),
+        descendant.is_solution(),
+    );
+}
+
+pub(crate) fn check_successor<P: Problem>(
+    validator: &mut Validator<StateId, P::Action>,
+    problem: &P,
+    states: &StateTable<P::State>,
+    parent: StateId,
+    action: &P::Action,
+    cost: isize,
+    successor: StateId,
+) {
+    if !validator.is_enabled() { return }
+    let heuristic = problem.get_heuristic(states.get(parent));
+    let successor_heuristic = problem.get_heuristic(states.get(successor));
+    let is_solution = problem.is_solution(states.get(successor));
+    validator.check_edge((&parent, heuristic), || action.clone(), cost, (&successor, successor_heuristic), is_solution);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a_star::AStar;
+    use crate::traits::algorithm::Algorithm;
+
+    // States 0 to 3 on a line, the step into state i costs `costs[i - 1]`
+    struct LineNode { state: Rc<usize>, parent: Option<Rc<LineNode>>, cost: isize, costs: [isize; 3], heuristics: [isize; 4] }
+
+    impl Node for LineNode {
+        type State = usize;
+        type Action = ();
+
+        fn get_descendants(self) -> Vec<Box<Self>> {
+            let state = *self.state;
+            if state == 3 { return Vec::new() }
+            let (cost, costs, heuristics) = (self.cost + self.costs[state], self.costs, self.heuristics);
+            vec![Box::new(LineNode { state: Rc::new(state + 1), parent: Some(Rc::new(self)), cost, costs, heuristics })]
+        }
+
+        fn get_state(&self) -> Rc<usize> { self.state.clone() }
+        fn get_action(&self) -> Option<()> { self.parent.as_ref().map(|_| ()) }
+        fn get_parent(&self) -> Option<Rc<Self>> { self.parent.clone() }
+        fn get_cost(&self) -> isize { self.cost }
+        fn get_heuristic(&self) -> isize { self.heuristics[*self.state] }
+        fn is_solution(&self) -> bool { *self.state == 3 }
+    }
+
+    // (variant, offending state) of every violation
+    fn validate(costs: [isize; 3], heuristics: [isize; 4]) -> Vec<(&'static str, usize)> {
+        let mut a_star = AStar::new();
+        a_star.set_validation(true);
+        let root = LineNode { state: Rc::new(0), parent: None, cost: 0, costs, heuristics };
+        assert!(a_star.find_solution(Box::new(root)).is_some());
+        a_star.get_violations().iter()
+            .map(|violation| match violation {
+                Violation::Inconsistent { state, .. } => ("inconsistent", **state),
+                Violation::DecreasingCost { state, .. } => ("decreasing", **state),
+                Violation::NegativeHeuristic { state, .. } => ("negative", **state),
+                Violation::NonZeroSolution { state, .. } => ("solution", **state),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid_problem_has_no_violations() {
+        assert_eq!(validate([1, 2, 1], [4, 3, 1, 0]), []);
+    }
+
+    #[test]
+    fn heuristic_dropping_by_more_than_the_cost() {
+        assert_eq!(validate([1, 1, 1], [3, 0, 1, 0]), [("inconsistent", 0)]);
+    }
+
+    #[test]
+    fn negative_step_cost() {
+        assert!(validate([1, -1, 1], [0, 0, 0, 0]).contains(&("decreasing", 1)));
+    }
+
+    #[test]
+    fn negative_heuristic() {
+        assert!(validate([1, 1, 1], [0, -2, 0, 0]).contains(&("negative", 1)));
+    }
+
+    #[test]
+    fn solution_with_an_estimate() {
+        assert_eq!(validate([1, 1, 1], [0, 0, 0, 2]), [("solution", 3)]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+use super::rng::Rng;
+
+/*
+ *  Random key per (position, value) pair, the hash of a state is the xor of the keys of its pairs
+ *  so changing the value at a position only needs two xors
+ */
+
+pub struct Zobrist {
+    keys: Vec<u64>,
+    values: usize,
+}
+
+impl Zobrist {
+    pub fn new(positions: usize, values: usize, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        Zobrist {
+            keys: (0..positions * values).map(|_| rng.next_u64()).collect(),
+            values,
+        }
+    }
+
+    pub fn get_key(&self, position: usize, value: usize) -> u64 {
+        self.keys[position * self.values + value]
+    }
+
+    pub fn hash<I: IntoIterator<Item = (usize, usize)>>(&self, pairs: I) -> u64 {
+        pairs.into_iter().fold(0, |hash, (position, value)| hash ^ self.get_key(position, value))
+    }
+
+    // Adds the pair if it was not part of the hash, removes it otherwise
+    pub fn toggle(&self, hash: u64, position: usize, value: usize) -> u64 {
+        hash ^ self.get_key(position, value)
+    }
+
+    pub fn replace(&self, hash: u64, position: usize, old: usize, new: usize) -> u64 {
+        hash ^ self.get_key(position, old) ^ self.get_key(position, new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incremental_updates_match_the_full_hash() {
+        let zobrist = Zobrist::new(4, 3, 7);
+        let board = [0, 2, 1, 2];
+        let hash = zobrist.hash(board.iter().copied().enumerate());
+
+        let moved = zobrist.replace(hash, 2, 1, 0);
+        assert_eq!(moved, zobrist.hash([(0, 0), (1, 2), (2, 0), (3, 2)]));
+        assert_eq!(zobrist.replace(moved, 2, 0, 1), hash);
+
+        let removed = zobrist.toggle(hash, 3, 2);
+        assert_eq!(removed, zobrist.hash([(0, 0), (1, 2), (2, 1)]));
+        assert_eq!(zobrist.toggle(removed, 3, 2), hash);
+    }
+
+    #[test]
+    fn same_seed_same_keys() {
+        let pairs = [(0, 1), (1, 0), (2, 2)];
+        assert_eq!(Zobrist::new(3, 3, 42).hash(pairs), Zobrist::new(3, 3, 42).hash(pairs));
+        assert_ne!(Zobrist::new(3, 3, 42).hash(pairs), Zobrist::new(3, 3, 43).hash(pairs));
+    }
+}
\ No newline at end of file